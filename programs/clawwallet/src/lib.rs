@@ -1,7 +1,31 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{invoke_signed, set_return_data};
+use anchor_lang::solana_program::{
+    ed25519_program, hash, keccak, sysvar::instructions as ix_sysvar,
+};
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer as SplTransfer};
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::associated_token::{
+    self, get_associated_token_address, get_associated_token_address_with_program_id,
+    AssociatedToken,
+};
+use anchor_spl::memo::{self, BuildMemo, Memo};
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer as SplTransfer};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeConfig,
+        transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint as Token2022Mint,
+};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{
+    self as token_interface, Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount,
+    TokenInterface,
+};
+use pyth_sdk_solana::load_price_feed_from_account_info;
+use std::collections::{BTreeMap, BTreeSet};
 
 declare_id!("AJtfLHhcqThpQrV4c3wrzwFZoHiMiXVCzeHHgYt6n74M");
 
@@ -9,6 +33,260 @@ declare_id!("AJtfLHhcqThpQrV4c3wrzwFZoHiMiXVCzeHHgYt6n74M");
 pub const USDC_MINT_DEVNET: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
 pub const USDC_MINT_MAINNET: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
 
+pub const SECONDS_PER_DAY: i64 = 86_400;
+// Max per-mint entries on a spend policy (keep in sync with #[max_len] on SpendPolicy)
+pub const MAX_TOKEN_LIMITS: usize = 4;
+
+// Session key permission bits
+pub const PERM_SEND_SOL: u8 = 1 << 0;
+pub const PERM_SEND_TO_AGENT: u8 = 1 << 1;
+pub const PERM_SEND_TOKEN: u8 = 1 << 2;
+
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+
+// ProtocolConfig::paused_instructions bits
+pub const PAUSE_CREATE_WALLET: u64 = 1 << 0;
+pub const PAUSE_SEND_SOL: u64 = 1 << 1;
+pub const PAUSE_SEND_TO_AGENT: u64 = 1 << 2;
+pub const PAUSE_SEND_TOKEN: u64 = 1 << 3;
+pub const PAUSE_MULTISIG: u64 = 1 << 4;
+pub const PAUSE_WALLET_ADMIN: u64 = 1 << 5;
+pub const PAUSE_RECOVERY: u64 = 1 << 6;
+
+pub const MAX_GUARDIANS: usize = 5;
+pub const RECOVERY_DELAY: i64 = 2 * SECONDS_PER_DAY;
+
+pub const MIN_INACTIVITY_PERIOD: i64 = 30 * SECONDS_PER_DAY;
+
+// Max entries on a recipient allowlist (keep in sync with #[max_len] on RecipientAllowlist)
+pub const MAX_ALLOWLIST: usize = 16;
+
+// Max entries on a denylist (keep in sync with #[max_len] on Denylist)
+pub const MAX_DENYLIST: usize = 32;
+
+pub const DEFAULT_TIMELOCK_DELAY: i64 = SECONDS_PER_DAY;
+pub const PAUSE_QUEUE: u64 = 1 << 7;
+
+pub const MAX_APPROVERS: usize = 5;
+
+// Queued transfers and payment proposals lapse if not executed within this window
+pub const QUEUED_TRANSFER_TTL: i64 = 7 * SECONDS_PER_DAY;
+pub const PAYMENT_PROPOSAL_TTL: i64 = 7 * SECONDS_PER_DAY;
+
+// AgentMetadata field limits (keep in sync with #[max_len] on AgentMetadata)
+pub const MAX_DISPLAY_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+pub const DEFAULT_FEE_BPS: u16 = 50; // 0.5%
+pub const MAX_FEE_BPS: u16 = 100;
+
+// Treasury changes must wait this long between propose and finalize
+pub const TREASURY_CHANGE_DELAY: i64 = 2 * SECONDS_PER_DAY;
+
+// Points-based fee discounts (ascending min_points, non-increasing fee_bps)
+pub const MAX_FEE_TIERS: usize = 4;
+
+// Fee exemption reasons
+pub const EXEMPT_PARTNER: u8 = 0;
+pub const EXEMPT_INTERNAL: u8 = 1;
+pub const EXEMPT_PROMO: u8 = 2;
+
+// Fee rounding policies
+pub const FEE_ROUND_DOWN: u8 = 0;
+pub const FEE_ROUND_UP: u8 = 1;
+pub const MAX_MIN_FEE: u64 = 10_000_000; // 0.01 SOL
+
+// Share of each fee paid to the sender's referrer is capped at 50%
+pub const MAX_REFERRAL_SHARE_BPS: u16 = 5_000;
+
+pub const MIN_REFERRAL_CODE_LEN: usize = 3;
+pub const MAX_REFERRAL_CODE_LEN: usize = 16;
+
+pub const MAX_FEE_BENEFICIARIES: usize = 4;
+
+pub const PAUSE_POINTS: u64 = 1 << 8;
+
+pub const LEADERBOARD_SIZE: usize = 32;
+
+pub const MAX_EPOCH_WINNERS: usize = 10;
+
+// Points decay by config.points_decay_bps for every full period without a transfer
+pub const POINTS_DECAY_PERIOD: i64 = 30 * SECONDS_PER_DAY;
+
+pub const DEFAULT_POINTS_CAP: u64 = 1_000_000;
+
+// Achievement badges (bit index into AgentWallet.badges)
+pub const BADGE_FIRST_TRANSFER: u8 = 0;
+pub const BADGE_100_TRANSFERS: u8 = 1;
+pub const BADGE_1_SOL_VOLUME: u8 = 2;
+pub const BADGE_FIRST_AGENT_SEND: u8 = 3;
+
+// Agent levels: level N+1 requires (points, tx_count, lifetime lamports) >= LEVEL_REQUIREMENTS[N]
+pub const LEVEL_REQUIREMENTS: [(u64, u64, u64); 5] = [
+    (100, 1, 0),
+    (500, 10, 1_000_000_000),
+    (2_000, 50, 10_000_000_000),
+    (10_000, 200, 100_000_000_000),
+    (50_000, 1_000, 1_000_000_000_000),
+];
+
+// Send points are boosted by this much per level reached (5% per level, 25% at the top)
+pub const LEVEL_POINTS_BONUS_BPS: u64 = 500;
+
+// Daily streaks: the first transfer of each consecutive UTC day earns streak_days * STREAK_BONUS_PER_DAY
+pub const STREAK_BONUS_PER_DAY: u64 = 2;
+pub const MAX_STREAK_BONUS: u64 = 20;
+
+pub const REWARD_MINT_DECIMALS: u8 = 6;
+
+// Recent recipients remembered per wallet for the repeat-recipient points reduction
+pub const RECENT_RECIPIENTS: usize = 4;
+
+// Quest kinds
+pub const QUEST_SEND_COUNT: u8 = 0;
+pub const QUEST_DISTINCT_AGENTS: u8 = 1;
+pub const QUEST_SOL_VOLUME: u8 = 2;
+pub const QUEST_TOKEN_VOLUME: u8 = 3;
+pub const MAX_QUEST_AGENTS: usize = 8;
+
+// Past seasons kept on each wallet
+pub const MAX_SEASON_HISTORY: usize = 4;
+
+// Per-mint points formula overrides kept in config
+pub const MAX_MINT_POINTS_FORMULAS: usize = 4;
+
+// Per-mint Pyth price feeds kept in config
+pub const MAX_PRICE_FEEDS: usize = 4;
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
+
+// Maximum recipients in one send_sol_batch
+pub const MAX_BATCH_RECIPIENTS: usize = 10;
+
+// Maximum token legs in one send_multi
+pub const MAX_MULTI_TOKENS: usize = 4;
+
+// Maximum memo length forwarded to the Memo program
+pub const MAX_MEMO_LEN: usize = 256;
+
+// Maximum Solana Pay reference keys echoed into one send event
+pub const MAX_REFERENCES: usize = 4;
+
+// Transfer categories for on-chain expense reporting
+pub const CATEGORY_GENERAL: u8 = 0;
+pub const CATEGORY_INFERENCE: u8 = 1;
+pub const CATEGORY_COMPUTE: u8 = 2;
+pub const CATEGORY_DATA: u8 = 3;
+pub const CATEGORY_PAYROLL: u8 = 4;
+pub const CATEGORY_REFUND: u8 = 5;
+pub const CATEGORY_SUBSCRIPTION: u8 = 6;
+pub const CATEGORY_OTHER: u8 = 7;
+pub const NUM_CATEGORIES: usize = 8;
+
+// Recent idempotency keys remembered per wallet
+pub const MAX_IDEMPOTENCY_KEYS: usize = 16;
+
+pub const PAUSE_SCHEDULE: u64 = 1 << 9;
+
+// Keeper incentives: per-execution tip cap (lamports) and max share of reclaimed rent for cleanups
+pub const MAX_KEEPER_TIP: u64 = 10_000_000;
+pub const MAX_CLEANUP_BOUNTY_BPS: u16 = 5000;
+
+pub const PAUSE_SUBSCRIPTIONS: u64 = 1 << 10;
+
+// Subscription status
+pub const SUBSCRIPTION_ACTIVE: u8 = 0;
+pub const SUBSCRIPTION_PAUSED: u8 = 1;
+pub const SUBSCRIPTION_LAPSED: u8 = 2;
+pub const SUBSCRIPTION_CANCELLED: u8 = 3;
+pub const MAX_GRACE_PERIOD: i64 = 30 * SECONDS_PER_DAY;
+
+pub const PAUSE_STREAMS: u64 = 1 << 11;
+
+pub const PAUSE_VESTING: u64 = 1 << 12;
+
+// Payroll roster size and distinct token mints per roster
+pub const MAX_PAYROLL_ENTRIES: usize = 16;
+pub const MAX_PAYROLL_MINTS: usize = 4;
+
+pub const PAUSE_ESCROW: u64 = 1 << 13;
+pub const MAX_ESCROW_MILESTONES: usize = 8;
+
+// An arbiter gets this long after a dispute before the expiry crank can refund the payer
+pub const DISPUTE_RESOLUTION_PERIOD: i64 = 14 * SECONDS_PER_DAY;
+
+// Arbiter bonds: default minimum stake (lamports) and the wait before a bond can be withdrawn
+pub const DEFAULT_MIN_ARBITER_BOND: u64 = 1_000_000_000;
+pub const ARBITER_UNBONDING_PERIOD: i64 = 7 * SECONDS_PER_DAY;
+
+pub const PAUSE_HTLC: u64 = 1 << 14;
+
+pub const PAUSE_CLAIMABLE: u64 = 1 << 15;
+
+// Unclaimed claimable transfers become returnable to the sender after the configured window
+pub const DEFAULT_CLAIMABLE_TTL: i64 = 30 * SECONDS_PER_DAY;
+pub const MAX_CLAIMABLE_TTL: i64 = 365 * SECONDS_PER_DAY;
+
+pub const PAUSE_INVOICES: u64 = 1 << 16;
+pub const MAX_INVOICE_MEMO_LEN: usize = 64;
+
+pub const MAX_REVENUE_SHARES: usize = 4;
+
+// Tips: capped dust transfers with a flat fee (default free) and a fixed points reward
+pub const MAX_TIP_AMOUNT: u64 = 10_000_000; // 0.01 SOL
+pub const MAX_TIP_FEE: u64 = 10_000;
+pub const TIP_POINTS: u64 = 1;
+
+pub const PAUSE_CROWDFUND: u64 = 1 << 17;
+// A funded crowdfund left unclaimed this long after its deadline becomes refundable
+pub const CROWDFUND_CLAIM_WINDOW: i64 = 30 * SECONDS_PER_DAY;
+
+pub const PAUSE_BOUNTIES: u64 = 1 << 18;
+
+// Bounty status
+pub const BOUNTY_OPEN: u8 = 0;
+pub const BOUNTY_AWARDED: u8 = 1;
+pub const BOUNTY_CANCELLED: u8 = 2;
+
+pub const PAUSE_MARKETPLACE: u64 = 1 << 19;
+
+pub const PAUSE_CREDITS: u64 = 1 << 20;
+// Notice a consumer gives before withdrawing prepaid credits, so the provider can bill pending usage
+pub const CREDIT_WITHDRAW_NOTICE: i64 = SECONDS_PER_DAY;
+
+pub const PAUSE_CHANNELS: u64 = 1 << 21;
+pub const MAX_CHANNEL_CHALLENGE_PERIOD: i64 = 7 * SECONDS_PER_DAY;
+
+pub const MAX_CONFIDENTIAL_FEE: u64 = 100_000;
+// Token-2022 confidential transfer extension instruction tags
+pub const CT_EXTENSION_IX: u8 = 27;
+pub const CT_CONFIGURE_ACCOUNT: u8 = 2;
+pub const CT_DEPOSIT: u8 = 5;
+pub const CT_TRANSFER: u8 = 7;
+pub const CT_APPLY_PENDING_BALANCE: u8 = 8;
+
+// Interest-bearing mint math: fixed-point scale, e at that scale, and Token-2022's 365.24-day year
+pub const INTEREST_SCALE: u128 = 1_000_000_000_000;
+pub const E_SCALED: u128 = 2_718_281_828_459;
+pub const INTEREST_SECONDS_PER_YEAR: u128 = 31_556_736;
+
+// Objects that pay into or draw from a wallet (index into AgentWallet.open_objects); the wallet can only
+// be closed once every count is back to zero, so nothing pays into a recreated PDA at the same address
+pub const OPEN_ESCROW: usize = 0;
+pub const OPEN_STREAM: usize = 1;
+pub const OPEN_VESTING: usize = 2;
+pub const OPEN_HTLC: usize = 3;
+pub const OPEN_CLAIMABLE: usize = 4;
+pub const OPEN_SCHEDULED: usize = 5;
+pub const OPEN_QUEUED: usize = 6;
+pub const OPEN_SUBSCRIPTION: usize = 7;
+pub const OPEN_MANDATE: usize = 8;
+pub const OPEN_CROWDFUND: usize = 9;
+pub const OPEN_BOUNTY: usize = 10;
+pub const OPEN_CREDITS: usize = 11;
+pub const OPEN_CHANNEL: usize = 12;
+pub const OPEN_KINDS: usize = 13;
+
 #[program]
 pub mod clawwallet {
     use super::*;
@@ -22,6 +300,7 @@ pub mod clawwallet {
         wallet.created_at = Clock::get()?.unix_timestamp;
         wallet.tx_count = 0;
         wallet.bump = *ctx.bumps.get("wallet").unwrap();
+        wallet.season = ctx.accounts.config.season;
 
         emit!(WalletCreated {
             agent_id,
@@ -32,16 +311,38 @@ pub mod clawwallet {
         Ok(())
     }
 
-    /// Send SOL from agent wallet (0.5% fee)
-    pub fn send_sol(ctx: Context<SendSol>, amount: u64) -> Result<()> {
-        let fee = amount / 200; // 0.5%
+    /// Send SOL from agent wallet (protocol fee)
+    pub fn send_sol(
+        ctx: Context<SendSol>,
+        amount: u64,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts.wallet.use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        let references = collect_references(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), ctx.accounts.recipient.key()],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let fee = apply_fee_discount(&mut ctx.accounts.wallet, &ctx.accounts.config, fee);
         let send_amount = amount - fee;
-        
+        require!(send_amount > 0, ClawWalletError::AmountBelowMinFee);
+
         // Direct lamport manipulation for PDA with data
         let wallet_info = ctx.accounts.wallet.to_account_info();
         let recipient_info = ctx.accounts.recipient.to_account_info();
         let treasury_info = ctx.accounts.treasury.to_account_info();
-        
+
         // Check sufficient balance (keeping rent-exempt minimum)
         let rent = anchor_lang::prelude::Rent::get()?;
         let min_balance = rent.minimum_balance(wallet_info.data_len());
@@ -49,22 +350,130 @@ pub mod clawwallet {
             **wallet_info.lamports.borrow() >= amount + min_balance,
             ClawWalletError::InsufficientFunds
         );
-        
+
+        // Authorize signer (owner, agent key, or session key)
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            amount,
+            now,
+        )?;
+
+        // Track rolling volume
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        // Denylists apply to every signer
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[ctx.accounts.recipient.key()],
+        )?;
+
+        // Spend policy and allowlist only bind delegated signers
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                amount,
+                false,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &[ctx.accounts.recipient.key()],
+            )?;
+        }
+
         // Transfer to recipient
         **wallet_info.try_borrow_mut_lamports()? -= send_amount;
         **recipient_info.try_borrow_mut_lamports()? += send_amount;
-        
-        // Transfer fee to treasury
-        **wallet_info.try_borrow_mut_lamports()? -= fee;
-        **treasury_info.try_borrow_mut_lamports()? += fee;
+
+        // Split the fee between the referrer (if any) and the treasury
+        let referral_fee = referral_cut(
+            &ctx.accounts.wallet,
+            ctx.accounts.referrer.as_ref().map(|r| r.key()),
+            &ctx.accounts.config,
+            fee,
+        )?;
+        **wallet_info.try_borrow_mut_lamports()? -= fee - referral_fee;
+        **treasury_info.try_borrow_mut_lamports()? += fee - referral_fee;
+        if referral_fee > 0 {
+            let referrer = ctx.accounts.referrer.as_mut().unwrap();
+            transfer_lamports(&wallet_info, &referrer.to_account_info(), referral_fee)?;
+            referrer.referral_earnings = referrer
+                .referral_earnings
+                .checked_add(referral_fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            emit!(ReferralFeeSplit {
+                referrer: referrer.key(),
+                wallet: wallet_info.key(),
+                mint: None,
+                amount: referral_fee,
+            });
+        }
+
+        record_referred_send(
+            &ctx.accounts.wallet,
+            &mut ctx.accounts.referral_code,
+            amount,
+        )?;
 
         // Update wallet stats
         let wallet = &mut ctx.accounts.wallet;
-        wallet.tx_count += 1;
-        
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(category, amount)?;
+
         // Award points based on amount (1-10 points)
-        let points_earned = std::cmp::min(10, std::cmp::max(1, (amount / 100_000_000) as u64));
-        wallet.points += points_earned;
+        let base_points = send_points(
+            &ctx.accounts.config,
+            &ctx.accounts.price_feed,
+            None,
+            amount,
+            now,
+        )?;
+        let points_earned = campaign_points(
+            &ctx.accounts.points_campaign,
+            base_points,
+            None,
+            now,
+            &wallet.agent_id,
+        )?;
+        let config = &ctx.accounts.config;
+        let points_earned = wallet.earn_points(
+            points_earned,
+            amount,
+            config.min_earn_lamports,
+            ctx.accounts.recipient.key(),
+            now,
+            config,
+        );
+        advance_quest(
+            &ctx.accounts.quest,
+            &mut ctx.accounts.quest_progress,
+            wallet,
+            None,
+            amount,
+            None,
+            now,
+        )?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        write_receipt(
+            &mut ctx.accounts.receipt,
+            ctx.bumps.get("receipt"),
+            &ctx.accounts.receipt_payer,
+            wallet,
+            ctx.accounts.recipient.key(),
+            None,
+            send_amount,
+            &references,
+            [0; 32],
+        )?;
 
         emit!(SolSent {
             agent_id: wallet.agent_id.clone(),
@@ -72,15 +481,81 @@ pub mod clawwallet {
             fee,
             recipient: ctx.accounts.recipient.key(),
             points_earned,
+            memo,
+            category,
+            references,
         });
 
         Ok(())
     }
 
     /// Send SOL to another agent's wallet
-    pub fn send_to_agent(ctx: Context<SendToAgent>, amount: u64) -> Result<()> {
-        let fee = amount / 200; // 0.5%
+    pub fn send_to_agent(
+        ctx: Context<SendToAgent>,
+        amount: u64,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts
+            .from_wallet
+            .use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        let references = collect_references(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.from_wallet.key(),
+            &[ctx.accounts.from_wallet.key(), ctx.accounts.to_wallet.key()],
+            None,
+            amount,
+            ctx.accounts
+                .config
+                .fee_bps_for(ctx.accounts.from_wallet.points),
+            now,
+        )?;
+        let fee = apply_fee_discount(&mut ctx.accounts.from_wallet, &ctx.accounts.config, fee);
         let send_amount = amount - fee;
+        require!(send_amount > 0, ClawWalletError::AmountBelowMinFee);
+
+        // Authorize signer (owner, agent key, or session key)
+        let role = authorize_sender(
+            &ctx.accounts.from_wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TO_AGENT,
+            amount,
+            now,
+        )?;
+
+        // Track rolling volume
+        ctx.accounts.from_wallet.record_outflow(amount, now)?;
+
+        // Denylists apply to every signer
+        enforce_denylists(
+            &ctx.accounts.from_wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[ctx.accounts.to_wallet.key()],
+        )?;
+
+        // Spend policy and allowlist only bind delegated signers
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.from_wallet,
+                &ctx.accounts.spend_policy,
+                amount,
+                false,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.from_wallet,
+                &ctx.accounts.allowlist,
+                &[ctx.accounts.to_wallet.key()],
+            )?;
+        }
 
         // Transfer to recipient wallet
         let cpi_context = CpiContext::new(
@@ -92,7 +567,19 @@ pub mod clawwallet {
         );
         system_program::transfer(cpi_context, send_amount)?;
 
-        // Transfer fee to treasury
+        // Split the fee between the referrer (if any) and the treasury; skipped when paying the
+        // referrer itself so the two wallet accounts don't clobber each other
+        let referral_fee = if ctx.accounts.from_wallet.referrer == ctx.accounts.to_wallet.key() {
+            0
+        } else {
+            referral_cut(
+                &ctx.accounts.from_wallet,
+                ctx.accounts.referrer.as_ref().map(|r| r.key()),
+                &ctx.accounts.config,
+                fee,
+            )?
+        };
+
         let cpi_context_fee = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
@@ -100,16 +587,90 @@ pub mod clawwallet {
                 to: ctx.accounts.treasury.to_account_info(),
             },
         );
-        system_program::transfer(cpi_context_fee, fee)?;
+        system_program::transfer(cpi_context_fee, fee - referral_fee)?;
+
+        if referral_fee > 0 {
+            let from_info = ctx.accounts.from_wallet.to_account_info();
+            let referrer = ctx.accounts.referrer.as_mut().unwrap();
+            transfer_lamports(&from_info, &referrer.to_account_info(), referral_fee)?;
+            referrer.referral_earnings = referrer
+                .referral_earnings
+                .checked_add(referral_fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            emit!(ReferralFeeSplit {
+                referrer: referrer.key(),
+                wallet: from_info.key(),
+                mint: None,
+                amount: referral_fee,
+            });
+        }
+
+        record_referred_send(
+            &ctx.accounts.from_wallet,
+            &mut ctx.accounts.referral_code,
+            amount,
+        )?;
 
         // Update stats
         let from_wallet = &mut ctx.accounts.from_wallet;
-        from_wallet.tx_count += 1;
-        let points_earned = std::cmp::min(10, std::cmp::max(1, (amount / 100_000_000) as u64));
-        from_wallet.points += points_earned;
+        from_wallet.record_tx(now, &ctx.accounts.config);
+        from_wallet.record_category(category, amount)?;
+        from_wallet.unlock_badge(BADGE_FIRST_AGENT_SEND);
+        let base_points = send_points(
+            &ctx.accounts.config,
+            &ctx.accounts.price_feed,
+            None,
+            amount,
+            now,
+        )?;
+        let points_earned = campaign_points(
+            &ctx.accounts.points_campaign,
+            base_points,
+            None,
+            now,
+            &from_wallet.agent_id,
+        )?;
+        let config = &ctx.accounts.config;
+        let points_earned = from_wallet.earn_points(
+            points_earned,
+            amount,
+            config.min_earn_lamports,
+            ctx.accounts.to_wallet.key(),
+            now,
+            config,
+        );
+        advance_quest(
+            &ctx.accounts.quest,
+            &mut ctx.accounts.quest_progress,
+            from_wallet,
+            None,
+            amount,
+            Some(ctx.accounts.to_wallet.key()),
+            now,
+        )?;
 
         let to_wallet = &mut ctx.accounts.to_wallet;
-        to_wallet.points += 5; // Bonus for receiving agent-to-agent
+        to_wallet.add_points(5, &ctx.accounts.config); // Bonus for receiving agent-to-agent
+
+        update_leaderboard(
+            &ctx.accounts.leaderboard,
+            from_wallet.key(),
+            from_wallet.points,
+        )?;
+        update_leaderboard(&ctx.accounts.leaderboard, to_wallet.key(), to_wallet.points)?;
+
+        write_receipt(
+            &mut ctx.accounts.receipt,
+            ctx.bumps.get("receipt"),
+            &ctx.accounts.receipt_payer,
+            from_wallet,
+            to_wallet.key(),
+            None,
+            send_amount,
+            &references,
+            [0; 32],
+        )?;
 
         emit!(AgentTransfer {
             from_agent: from_wallet.agent_id.clone(),
@@ -117,183 +678,19883 @@ pub mod clawwallet {
             amount: send_amount,
             fee,
             points_earned,
+            memo,
+            category,
+            references,
         });
 
         Ok(())
     }
 
-    /// Send SPL tokens (USDC, etc.) from agent wallet (0.5% fee)
-    pub fn send_token(ctx: Context<SendToken>, amount: u64) -> Result<()> {
-        let fee = amount / 200; // 0.5%
+    /// Send SPL tokens (USDC, etc.) from agent wallet (protocol fee)
+    pub fn send_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendToken<'info>>,
+        amount: u64,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts.wallet.use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        // Transfer-hook accounts (if the mint has a hook) come first, then any references
+        let (hook_accounts, reference_infos) =
+            transfer_hook_accounts(&ctx.accounts.mint.to_account_info(), ctx.remaining_accounts)?;
+        let references = collect_references(reference_infos)?;
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let mint_fee = load_mint_fee(&ctx.accounts.mint_fee.to_account_info())?;
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                ctx.accounts.recipient_token_account.key(),
+                ctx.accounts.recipient_token_account.owner,
+            ],
+            Some(ctx.accounts.mint.key()),
+            amount,
+            token_fee_bps(config, &mint_fee, ctx.accounts.wallet.points),
+            now,
+        )?;
+        let fee = mint_fee.map_or(fee, |m| apply_fee_cap(fee, m.max_fee));
+        let fee = apply_fee_discount(&mut ctx.accounts.wallet, &ctx.accounts.config, fee);
         let send_amount = amount - fee;
-        
-        let wallet = &ctx.accounts.wallet;
-        let bump = wallet.bump;
-        let agent_id = wallet.agent_id.clone();
-        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
-        let signer_seeds = &[&seeds[..]];
+        // Token-2022 transfer-fee mints withhold part of the transfer from the recipient
+        let transfer_fee = mint_transfer_fee(&ctx.accounts.mint.to_account_info(), send_amount)?;
+        let received = send_amount - transfer_fee;
+
+        // Authorize signer (owner, agent key, or session key)
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            amount,
+            now,
+        )?;
+
+        // Denylists apply to every signer
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                ctx.accounts.recipient_token_account.key(),
+                ctx.accounts.recipient_token_account.owner,
+            ],
+        )?;
+
+        // Enforce spend policy for this mint and allowlist (delegated signers only)
+        let mint = ctx.accounts.mint.key();
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                amount,
+                now,
+                false,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &[
+                    ctx.accounts.recipient_token_account.key(),
+                    ctx.accounts.recipient_token_account.owner,
+                ],
+            )?;
+        }
 
         // Transfer tokens to recipient
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            SplTransfer {
-                from: ctx.accounts.wallet_token_account.to_account_info(),
-                to: ctx.accounts.recipient_token_account.to_account_info(),
-                authority: ctx.accounts.wallet.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(cpi_ctx, send_amount)?;
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            send_amount,
+        )?;
 
-        // Transfer fee to treasury
-        let cpi_ctx_fee = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            SplTransfer {
-                from: ctx.accounts.wallet_token_account.to_account_info(),
-                to: ctx.accounts.treasury_token_account.to_account_info(),
-                authority: ctx.accounts.wallet.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(cpi_ctx_fee, fee)?;
+        // Split the fee between the referrer (if any) and the treasury
+        let referral_fee = referral_cut(
+            &ctx.accounts.wallet,
+            ctx.accounts
+                .referrer_token_account
+                .as_ref()
+                .map(|t| t.owner),
+            &ctx.accounts.config,
+            fee,
+        )?;
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            fee - referral_fee,
+        )?;
+
+        if referral_fee > 0 {
+            let referrer_token_account = ctx.accounts.referrer_token_account.as_ref().unwrap();
+            transfer_checked_from_wallet(
+                &ctx.accounts.token_program,
+                &ctx.accounts.wallet_token_account,
+                &ctx.accounts.mint,
+                referrer_token_account.to_account_info(),
+                &ctx.accounts.wallet,
+                hook_accounts,
+                referral_fee,
+            )?;
+
+            emit!(ReferralFeeSplit {
+                referrer: referrer_token_account.owner,
+                wallet: ctx.accounts.wallet.key(),
+                mint: Some(mint),
+                amount: referral_fee,
+            });
+        }
+
+        record_referred_send(&ctx.accounts.wallet, &mut ctx.accounts.referral_code, 0)?;
+
+        // The fee legs are transfers too, so the mint withholds from each of them as well
+        let fee_transfer_fee =
+            mint_transfer_fee(&ctx.accounts.mint.to_account_info(), fee - referral_fee)?
+                + mint_transfer_fee(&ctx.accounts.mint.to_account_info(), referral_fee)?;
+
+        // Interest-bearing mints show holders more than the raw amount; points and reports follow that
+        let ui_amount = interest_adjusted_amount(&ctx.accounts.mint, amount, now)?;
+        let ui_received = interest_adjusted_amount(&ctx.accounts.mint, received, now)?;
 
         // Update wallet stats
         let wallet = &mut ctx.accounts.wallet;
-        wallet.tx_count += 1;
-        
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(category, 0)?;
+
         // Award more points for USDC transactions (2-20 points)
-        let points_earned = std::cmp::min(20, std::cmp::max(2, (amount / 100_000) as u64)); // USDC has 6 decimals
-        wallet.points += points_earned;
+        let base_points = send_points(
+            &ctx.accounts.config,
+            &ctx.accounts.price_feed,
+            Some(mint),
+            ui_amount,
+            now,
+        )?;
+        let points_earned = campaign_points(
+            &ctx.accounts.points_campaign,
+            base_points,
+            Some(mint),
+            now,
+            &wallet.agent_id,
+        )?;
+        let config = &ctx.accounts.config;
+        let recipient = ctx.accounts.recipient_token_account.owner;
+        let points_earned = wallet.earn_points(
+            points_earned,
+            ui_amount,
+            config.min_earn_token,
+            recipient,
+            now,
+            config,
+        );
+        advance_quest(
+            &ctx.accounts.quest,
+            &mut ctx.accounts.quest_progress,
+            wallet,
+            Some(mint),
+            ui_amount,
+            None,
+            now,
+        )?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        write_receipt(
+            &mut ctx.accounts.receipt,
+            ctx.bumps.get("receipt"),
+            &ctx.accounts.receipt_payer,
+            wallet,
+            recipient,
+            Some(mint),
+            received,
+            &references,
+            [0; 32],
+        )?;
 
         emit!(TokenSent {
             agent_id: wallet.agent_id.clone(),
             mint: ctx.accounts.mint.key(),
-            amount: send_amount,
+            amount: received,
+            ui_amount: ui_received,
             fee,
+            transfer_fee,
             recipient: ctx.accounts.recipient_token_account.key(),
             points_earned,
+            memo,
+            category,
+            references,
+            fee_transfer_fee,
         });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(agent_id: String)]
-pub struct CreateWallet<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + AgentWallet::INIT_SPACE,
-        seeds = [b"wallet", agent_id.as_bytes()],
-        bump
-    )]
-    pub wallet: Account<'info, AgentWallet>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Create a spend policy for the wallet with a daily SOL cap (0 = unlimited)
+    pub fn create_spend_policy(ctx: Context<CreateSpendPolicy>, daily_limit: u64) -> Result<()> {
+        let policy = &mut ctx.accounts.spend_policy;
+        policy.wallet = ctx.accounts.wallet.key();
+        policy.daily_limit = daily_limit;
+        policy.token_limits = Vec::new();
+        policy.max_per_tx = 0;
+        policy.timelock_threshold = 0;
+        policy.timelock_delay = 0;
+        policy.bump = *ctx.bumps.get("spend_policy").unwrap();
 
-#[derive(Accounts)]
-pub struct SendSol<'info> {
-    #[account(mut, has_one = owner)]
-    pub wallet: Account<'info, AgentWallet>,
-    pub owner: Signer<'info>,
-    /// CHECK: Recipient can be any account
-    #[account(mut)]
-    pub recipient: AccountInfo<'info>,
-    /// CHECK: Treasury account
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.has_spend_policy = true;
 
-#[derive(Accounts)]
-pub struct SendToAgent<'info> {
-    #[account(mut, has_one = owner)]
-    pub from_wallet: Account<'info, AgentWallet>,
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub to_wallet: Account<'info, AgentWallet>,
-    /// CHECK: Treasury account
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    pub system_program: Program<'info, System>,
-}
+        emit!(DailyLimitSet {
+            agent_id: wallet.agent_id.clone(),
+            mint: None,
+            daily_limit,
+        });
 
-#[derive(Accounts)]
-pub struct SendToken<'info> {
-    #[account(mut, has_one = owner)]
-    pub wallet: Account<'info, AgentWallet>,
-    pub owner: Signer<'info>,
-    
-    /// CHECK: Token mint (USDC or other SPL token)
-    pub mint: AccountInfo<'info>,
-    
-    /// Wallet's token account
-    #[account(mut)]
-    pub wallet_token_account: Account<'info, TokenAccount>,
-    
-    /// Recipient's token account
-    #[account(mut)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-    
-    /// Treasury's token account for fees
-    #[account(mut)]
-    pub treasury_token_account: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
-}
+        Ok(())
+    }
 
-#[account]
-#[derive(InitSpace)]
-pub struct AgentWallet {
-    #[max_len(64)]
-    pub agent_id: String,
-    pub owner: Pubkey,
-    pub points: u64,
-    pub created_at: i64,
-    pub tx_count: u64,
-    pub bump: u8,
-}
+    /// Update the daily SOL cap (0 = unlimited)
+    pub fn set_daily_limit(ctx: Context<UpdateSpendPolicy>, daily_limit: u64) -> Result<()> {
+        ctx.accounts.spend_policy.daily_limit = daily_limit;
 
-#[event]
-pub struct WalletCreated {
-    pub agent_id: String,
-    pub wallet: Pubkey,
-    pub owner: Pubkey,
-}
+        emit!(DailyLimitSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: None,
+            daily_limit,
+        });
 
-#[event]
-pub struct SolSent {
-    pub agent_id: String,
-    pub amount: u64,
-    pub fee: u64,
-    pub recipient: Pubkey,
-    pub points_earned: u64,
-}
+        Ok(())
+    }
 
-#[event]
-pub struct AgentTransfer {
-    pub from_agent: String,
-    pub to_agent: String,
-    pub amount: u64,
-    pub fee: u64,
-    pub points_earned: u64,
-}
+    /// Set the daily cap for an SPL token mint (0 = unlimited)
+    pub fn set_token_daily_limit(
+        ctx: Context<UpdateSpendPolicy>,
+        mint: Pubkey,
+        daily_limit: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .spend_policy
+            .update_token_limit(mint, |l| l.daily_limit = daily_limit)?;
 
-#[event]
-pub struct TokenSent {
-    pub agent_id: String,
-    pub mint: Pubkey,
-    pub amount: u64,
-    pub fee: u64,
-    pub recipient: Pubkey,
-    pub points_earned: u64,
-}
+        emit!(DailyLimitSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: Some(mint),
+            daily_limit,
+        });
 
-#[error_code]
-pub enum ClawWalletError {
-    #[msg("Insufficient funds in wallet")]
-    InsufficientFunds,
+        Ok(())
+    }
+
+    /// Set the max lamports per SOL transfer (0 = unlimited)
+    pub fn set_max_per_tx(ctx: Context<UpdateSpendPolicy>, max_per_tx: u64) -> Result<()> {
+        ctx.accounts.spend_policy.max_per_tx = max_per_tx;
+
+        emit!(MaxPerTxSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: None,
+            max_per_tx,
+        });
+
+        Ok(())
+    }
+
+    /// Set the max amount per transfer for an SPL token mint (0 = unlimited)
+    pub fn set_token_max_per_tx(
+        ctx: Context<UpdateSpendPolicy>,
+        mint: Pubkey,
+        max_per_tx: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .spend_policy
+            .update_token_limit(mint, |l| l.max_per_tx = max_per_tx)?;
+
+        emit!(MaxPerTxSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: Some(mint),
+            max_per_tx,
+        });
+
+        Ok(())
+    }
+
+    /// Register a temporary session key that may send on the owner's behalf
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at: i64,
+        permissions: u8,
+        spend_cap: u64,
+        token_spend_cap: u64,
+    ) -> Result<()> {
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            ClawWalletError::SessionExpired
+        );
+        require!(permissions != 0, ClawWalletError::SessionPermissionDenied);
+
+        let session = &mut ctx.accounts.session;
+        session.wallet = ctx.accounts.wallet.key();
+        session.signer = session_key;
+        session.expires_at = expires_at;
+        session.permissions = permissions;
+        session.spend_cap = spend_cap;
+        session.spent = 0;
+        session.token_spend_cap = token_spend_cap;
+        session.token_spent = 0;
+        session.bump = *ctx.bumps.get("session").unwrap();
+        ctx.accounts.wallet.session_count += 1;
+
+        emit!(SessionCreated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            session_key,
+            expires_at,
+            permissions,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a session key and reclaim its rent
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        ctx.accounts.wallet.session_count = ctx.accounts.wallet.session_count.saturating_sub(1);
+
+        emit!(SessionRevoked {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            session_key: ctx.accounts.session.signer,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the low-privilege agent key used for day-to-day sends
+    pub fn set_agent_authority(ctx: Context<UpdateWallet>, agent_authority: Pubkey) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            wallet.agent_authority == Pubkey::default(),
+            ClawWalletError::AgentAuthorityAlreadySet
+        );
+        require!(
+            agent_authority != Pubkey::default() && agent_authority != wallet.owner,
+            ClawWalletError::InvalidAgentAuthority
+        );
+        wallet.agent_authority = agent_authority;
+
+        emit!(AgentAuthoritySet {
+            agent_id: wallet.agent_id.clone(),
+            agent_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the agent key, e.g. after the agent runtime is redeployed
+    pub fn rotate_agent_key(ctx: Context<UpdateWallet>, new_agent_authority: Pubkey) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            wallet.agent_authority != Pubkey::default(),
+            ClawWalletError::NoAgentAuthority
+        );
+        require!(
+            new_agent_authority != Pubkey::default()
+                && new_agent_authority != wallet.owner
+                && new_agent_authority != wallet.agent_authority,
+            ClawWalletError::InvalidAgentAuthority
+        );
+
+        let old_agent_authority = wallet.agent_authority;
+        wallet.agent_authority = new_agent_authority;
+
+        emit!(AgentKeyRotated {
+            agent_id: wallet.agent_id.clone(),
+            old_agent_authority,
+            new_agent_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Cut off the agent key immediately; only the owner can send until a new one is set
+    pub fn revoke_agent_key(ctx: Context<EmergencyAction>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            wallet.agent_authority != Pubkey::default(),
+            ClawWalletError::NoAgentAuthority
+        );
+
+        let agent_authority = wallet.agent_authority;
+        wallet.agent_authority = Pubkey::default();
+
+        emit!(AgentKeyRevoked {
+            agent_id: wallet.agent_id.clone(),
+            agent_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Create an agent wallet owned by a threshold of keys
+    pub fn create_multisig_wallet(
+        ctx: Context<CreateMultisigWallet>,
+        agent_id: String,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS,
+            ClawWalletError::InvalidMultisigOwners
+        );
+        for (i, owner) in owners.iter().enumerate() {
+            require!(
+                !owners[..i].contains(owner),
+                ClawWalletError::InvalidMultisigOwners
+            );
+        }
+        require!(
+            threshold > 0 && threshold as usize <= owners.len(),
+            ClawWalletError::InvalidThreshold
+        );
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.wallet = ctx.accounts.wallet.key();
+        multisig.owners = owners.clone();
+        multisig.threshold = threshold;
+        multisig.tx_nonce = 0;
+        multisig.bump = *ctx.bumps.get("multisig").unwrap();
+
+        // The multisig PDA is the wallet owner, so owner-only instructions can't be signed by any single key
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.agent_id = agent_id.clone();
+        wallet.owner = multisig.key();
+        wallet.points = 100; // Welcome bonus
+        wallet.created_at = Clock::get()?.unix_timestamp;
+        wallet.tx_count = 0;
+        wallet.bump = *ctx.bumps.get("wallet").unwrap();
+        wallet.season = ctx.accounts.config.season;
+
+        emit!(MultisigWalletCreated {
+            agent_id,
+            wallet: wallet.key(),
+            multisig: multisig.key(),
+            owners,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a transfer from a multisig wallet (counts as the proposer's approval)
+    pub fn propose_transaction(
+        ctx: Context<ProposeTransaction>,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+
+        let multisig = &mut ctx.accounts.multisig;
+        let index = multisig.owner_index(&ctx.accounts.proposer.key())?;
+        let nonce = multisig.tx_nonce;
+        multisig.tx_nonce += 1;
+
+        let pending = &mut ctx.accounts.pending_tx;
+        pending.wallet = multisig.wallet;
+        pending.proposer = ctx.accounts.proposer.key();
+        pending.nonce = nonce;
+        pending.recipient = recipient;
+        pending.mint = mint;
+        pending.amount = amount;
+        pending.approvals = 1 << index;
+        pending.created_at = Clock::get()?.unix_timestamp;
+        pending.bump = *ctx.bumps.get("pending_tx").unwrap();
+
+        emit!(TransactionProposed {
+            wallet: pending.wallet,
+            nonce,
+            proposer: pending.proposer,
+            recipient,
+            mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Approve a pending multisig transfer
+    pub fn approve_transaction(ctx: Context<ApproveTransaction>) -> Result<()> {
+        let index = ctx
+            .accounts
+            .multisig
+            .owner_index(&ctx.accounts.owner.key())?;
+        let pending = &mut ctx.accounts.pending_tx;
+        require!(
+            pending.approvals & (1 << index) == 0,
+            ClawWalletError::AlreadyApproved
+        );
+        pending.approvals |= 1 << index;
+
+        emit!(TransactionApproved {
+            wallet: pending.wallet,
+            nonce: pending.nonce,
+            owner: ctx.accounts.owner.key(),
+            approvals: pending.approvals.count_ones() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an approved multisig SOL transfer (protocol fee)
+    pub fn execute_transaction(ctx: Context<ExecuteTransaction>) -> Result<()> {
+        let pending = &ctx.accounts.pending_tx;
+        require!(
+            pending.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            pending.approvals.count_ones() >= ctx.accounts.multisig.threshold as u32,
+            ClawWalletError::ThresholdNotMet
+        );
+
+        // Enforce denylists
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[pending.recipient],
+        )?;
+
+        let amount = pending.amount;
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), pending.recipient],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_outflow(amount, now)?;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(TransactionExecuted {
+            wallet: wallet.key(),
+            nonce: pending.nonce,
+            recipient: pending.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an approved multisig SPL token transfer (protocol fee)
+    pub fn execute_token_transaction(ctx: Context<ExecuteTokenTransaction>) -> Result<()> {
+        let pending = &ctx.accounts.pending_tx;
+        require!(
+            pending.mint == Some(ctx.accounts.wallet_token_account.mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            pending.approvals.count_ones() >= ctx.accounts.multisig.threshold as u32,
+            ClawWalletError::ThresholdNotMet
+        );
+
+        // Enforce denylists
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                pending.recipient,
+                ctx.accounts.recipient_token_account.owner,
+            ],
+        )?;
+
+        let amount = pending.amount;
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                pending.recipient,
+                ctx.accounts.recipient_token_account.owner,
+            ],
+            pending.mint,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        let wallet = &ctx.accounts.wallet;
+        let bump = wallet.bump;
+        let agent_id = wallet.agent_id.clone();
+        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, send_amount)?;
+
+        let cpi_ctx_fee = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx_fee, fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx
+            .accounts
+            .config
+            .token_points_for(&ctx.accounts.wallet_token_account.mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(TransactionExecuted {
+            wallet: wallet.key(),
+            nonce: pending.nonce,
+            recipient: pending.recipient,
+            mint: pending.mint,
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+
+        Ok(())
+    }
+
+    /// Nominate a new owner; takes effect once they call `accept_ownership` (default pubkey cancels)
+    pub fn propose_owner_transfer(ctx: Context<UpdateWallet>, new_owner: Pubkey) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(new_owner != wallet.owner, ClawWalletError::InvalidNewOwner);
+        wallet.pending_owner = new_owner;
+
+        emit!(OwnerTransferProposed {
+            agent_id: wallet.agent_id.clone(),
+            owner: wallet.owner,
+            pending_owner: new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending owner transfer
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        let previous_owner = wallet.owner;
+        wallet.owner = wallet.pending_owner;
+        wallet.pending_owner = Pubkey::default();
+
+        emit!(OwnershipAccepted {
+            agent_id: wallet.agent_id.clone(),
+            previous_owner,
+            new_owner: wallet.owner,
+        });
+
+        Ok(())
+    }
+
+    /// Register 1-5 guardians who can jointly recover the wallet; resets any recovery in progress
+    pub fn set_guardians(
+        ctx: Context<SetGuardians>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !guardians.is_empty() && guardians.len() <= MAX_GUARDIANS,
+            ClawWalletError::InvalidGuardians
+        );
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(
+                !guardians[..i].contains(guardian) && *guardian != ctx.accounts.owner.key(),
+                ClawWalletError::InvalidGuardians
+            );
+        }
+        require!(
+            threshold > 0 && threshold as usize <= guardians.len(),
+            ClawWalletError::InvalidThreshold
+        );
+
+        let recovery = &mut ctx.accounts.recovery;
+        recovery.wallet = ctx.accounts.wallet.key();
+        recovery.guardians = guardians.clone();
+        recovery.threshold = threshold;
+        recovery.clear();
+        recovery.bump = *ctx.bumps.get("recovery").unwrap();
+
+        emit!(GuardiansSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            guardians,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Guardian starts a recovery to `new_owner`; executable after quorum and RECOVERY_DELAY
+    pub fn initiate_recovery(ctx: Context<GuardianAction>, new_owner: Pubkey) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        require!(
+            recovery.initiated_at == 0,
+            ClawWalletError::RecoveryInProgress
+        );
+        require!(
+            new_owner != Pubkey::default(),
+            ClawWalletError::InvalidNewOwner
+        );
+        let index = recovery.guardian_index(&ctx.accounts.guardian.key())?;
+
+        recovery.new_owner = new_owner;
+        recovery.approvals = 1 << index;
+        recovery.initiated_at = Clock::get()?.unix_timestamp;
+
+        emit!(RecoveryInitiated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            guardian: ctx.accounts.guardian.key(),
+            new_owner,
+            executable_at: recovery.initiated_at + RECOVERY_DELAY,
+        });
+
+        Ok(())
+    }
+
+    /// Guardian approves the recovery in progress
+    pub fn approve_recovery(ctx: Context<GuardianAction>) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        require!(
+            recovery.initiated_at != 0,
+            ClawWalletError::NoRecoveryInProgress
+        );
+        let index = recovery.guardian_index(&ctx.accounts.guardian.key())?;
+        require!(
+            recovery.approvals & (1 << index) == 0,
+            ClawWalletError::AlreadyApproved
+        );
+        recovery.approvals |= 1 << index;
+
+        emit!(RecoveryApproved {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            guardian: ctx.accounts.guardian.key(),
+            approvals: recovery.approvals.count_ones() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the new owner once guardian quorum is reached and the delay has passed
+    pub fn recover_wallet(ctx: Context<RecoverWallet>) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        require!(
+            recovery.initiated_at != 0,
+            ClawWalletError::NoRecoveryInProgress
+        );
+        require!(
+            recovery.approvals.count_ones() >= recovery.threshold as u32,
+            ClawWalletError::ThresholdNotMet
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= recovery.initiated_at + RECOVERY_DELAY,
+            ClawWalletError::RecoveryDelayNotElapsed
+        );
+
+        let wallet = &mut ctx.accounts.wallet;
+        let previous_owner = wallet.owner;
+        wallet.owner = recovery.new_owner;
+        wallet.pending_owner = Pubkey::default();
+        recovery.clear();
+
+        emit!(WalletRecovered {
+            agent_id: wallet.agent_id.clone(),
+            previous_owner,
+            new_owner: wallet.owner,
+        });
+
+        Ok(())
+    }
+
+    /// Owner vetoes a recovery in progress
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let recovery = &mut ctx.accounts.recovery;
+        require!(
+            recovery.initiated_at != 0,
+            ClawWalletError::NoRecoveryInProgress
+        );
+        let new_owner = recovery.new_owner;
+        recovery.clear();
+
+        emit!(RecoveryCancelled {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    /// Name a beneficiary who can claim the wallet after `inactivity_period` seconds without sends
+    pub fn set_beneficiary(
+        ctx: Context<UpdateWallet>,
+        beneficiary: Pubkey,
+        inactivity_period: i64,
+    ) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            beneficiary == Pubkey::default() || inactivity_period >= MIN_INACTIVITY_PERIOD,
+            ClawWalletError::InactivityPeriodTooShort
+        );
+        wallet.beneficiary = beneficiary;
+        wallet.inactivity_period = inactivity_period;
+        wallet.last_activity_at = Clock::get()?.unix_timestamp;
+
+        emit!(BeneficiarySet {
+            agent_id: wallet.agent_id.clone(),
+            beneficiary,
+            inactivity_period,
+        });
+
+        Ok(())
+    }
+
+    /// Beneficiary takes ownership of a wallet that has been inactive past its threshold
+    pub fn claim_inherited_wallet(ctx: Context<ClaimInheritedWallet>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        let last_active = std::cmp::max(wallet.last_activity_at, wallet.created_at);
+        let claimable_at = last_active
+            .checked_add(wallet.inactivity_period)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= claimable_at,
+            ClawWalletError::WalletStillActive
+        );
+
+        let previous_owner = wallet.owner;
+        wallet.owner = wallet.beneficiary;
+        wallet.beneficiary = Pubkey::default();
+        wallet.inactivity_period = 0;
+        wallet.pending_owner = Pubkey::default();
+
+        emit!(WalletInherited {
+            agent_id: wallet.agent_id.clone(),
+            previous_owner,
+            new_owner: wallet.owner,
+        });
+
+        Ok(())
+    }
+
+    /// Block all sends from the wallet until unfrozen
+    pub fn freeze_wallet(ctx: Context<EmergencyAction>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(!wallet.frozen, ClawWalletError::WalletFrozen);
+        wallet.frozen = true;
+
+        emit!(WalletFrozen {
+            agent_id: wallet.agent_id.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Re-enable sends from a frozen wallet
+    pub fn unfreeze_wallet(ctx: Context<UpdateWallet>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        require!(wallet.frozen, ClawWalletError::WalletNotFrozen);
+        wallet.frozen = false;
+
+        emit!(WalletUnfrozen {
+            agent_id: wallet.agent_id.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Create the protocol config; only the program's upgrade authority can call this
+    pub fn initialize_config(ctx: Context<InitializeConfig>, treasury: Pubkey) -> Result<()> {
+        require!(
+            treasury != Pubkey::default(),
+            ClawWalletError::InvalidTreasury
+        );
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.paused = false;
+        config.paused_instructions = 0;
+        config.fee_bps = DEFAULT_FEE_BPS;
+        config.treasury = treasury;
+        config.pending_treasury = Pubkey::default();
+        config.treasury_change_at = 0;
+        config.fee_tiers = Vec::new();
+        config.min_fee = 0;
+        config.fee_rounding = FEE_ROUND_DOWN;
+        config.max_fee = 0;
+        config.max_token_fee = 0;
+        config.campaign_nonce = 0;
+        config.referral_share_bps = 0;
+        config.fee_vault = Pubkey::default();
+        config.accrue_fees = false;
+        config.fee_beneficiaries = Vec::new();
+        config.discount_cost = 0;
+        config.discount_bps = 0;
+        config.points_transfer_tax_bps = 0;
+        config.epoch = 0;
+        config.epoch_started_at = 0;
+        config.epoch_duration = 0;
+        config.points_decay_bps = 0;
+        config.points_cap = DEFAULT_POINTS_CAP;
+        config.reward_mint = Pubkey::default();
+        config.reward_rate = 0;
+        config.distributor_nonce = 0;
+        config.points_campaign_nonce = 0;
+        config.min_earn_lamports = 0;
+        config.min_earn_token = 0;
+        config.daily_points_cap = 0;
+        config.repeat_points_bps = 10_000;
+        config.quest_nonce = 0;
+        config.season = 0;
+        config.season_started_at = Clock::get()?.unix_timestamp;
+        config.sol_points = PointsFormula {
+            divisor: 100_000_000,
+            min_points: 1,
+            max_points: 10,
+        };
+        config.token_points = PointsFormula {
+            divisor: 100_000, // USDC has 6 decimals
+            min_points: 2,
+            max_points: 20,
+        };
+        config.mint_points = Vec::new();
+        config.sol_price_feed = Pubkey::default();
+        config.mint_price_feeds = Vec::new();
+        config.usd_points = PointsFormula {
+            divisor: 100_000, // $0.10 per point
+            min_points: 1,
+            max_points: 20,
+        };
+        config.max_price_age = DEFAULT_MAX_PRICE_AGE;
+        config.keeper_tip = 0;
+        config.cleanup_bounty_bps = 0;
+        config.min_arbiter_bond = DEFAULT_MIN_ARBITER_BOND;
+        config.claimable_ttl = DEFAULT_CLAIMABLE_TTL;
+        config.tip_fee = 0;
+        config.confidential_fee = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(ConfigInitialized {
+            admin: config.admin,
+            treasury,
+        });
+
+        Ok(())
+    }
+
+    /// Halt (or resume) every mutable instruction program-wide
+    pub fn set_paused(ctx: Context<AdminConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.config.paused = paused;
+
+        emit!(ProtocolPauseSet {
+            admin: ctx.accounts.admin.key(),
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// Pause individual instruction groups (PAUSE_* bits) without a global halt
+    pub fn set_paused_instructions(
+        ctx: Context<AdminConfig>,
+        paused_instructions: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.paused_instructions = paused_instructions;
+
+        emit!(PausedInstructionsSet {
+            admin: ctx.accounts.admin.key(),
+            paused_instructions,
+        });
+
+        Ok(())
+    }
+
+    /// Create the wallet's recipient allowlist (starts empty and disabled)
+    pub fn create_allowlist(ctx: Context<CreateAllowlist>) -> Result<()> {
+        let allowlist = &mut ctx.accounts.allowlist;
+        allowlist.wallet = ctx.accounts.wallet.key();
+        allowlist.recipients = Vec::new();
+        allowlist.bump = *ctx.bumps.get("allowlist").unwrap();
+
+        Ok(())
+    }
+
+    /// Approve a recipient (system account, token account, token account owner, or agent wallet)
+    pub fn add_allowed_recipient(ctx: Context<ManageAllowlist>, recipient: Pubkey) -> Result<()> {
+        let allowlist = &mut ctx.accounts.allowlist;
+        if !allowlist.recipients.contains(&recipient) {
+            require!(
+                allowlist.recipients.len() < MAX_ALLOWLIST,
+                ClawWalletError::AllowlistFull
+            );
+            allowlist.recipients.push(recipient);
+        }
+
+        emit!(AllowlistUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            recipient,
+            allowed: true,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a recipient from the allowlist
+    pub fn remove_allowed_recipient(
+        ctx: Context<ManageAllowlist>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts
+            .allowlist
+            .recipients
+            .retain(|r| *r != recipient);
+
+        emit!(AllowlistUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            recipient,
+            allowed: false,
+        });
+
+        Ok(())
+    }
+
+    /// Turn allowlist enforcement on or off for delegated signers
+    pub fn set_allowlist_enabled(ctx: Context<ManageAllowlist>, enabled: bool) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.allowlist_enabled = enabled;
+
+        emit!(AllowlistToggled {
+            agent_id: wallet.agent_id.clone(),
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Create the wallet's own recipient denylist; enforced for every signer once created
+    pub fn create_denylist(ctx: Context<CreateDenylist>) -> Result<()> {
+        let denylist = &mut ctx.accounts.denylist;
+        denylist.entries = Vec::new();
+        denylist.bump = *ctx.bumps.get("denylist").unwrap();
+
+        ctx.accounts.wallet.denylist_enabled = true;
+
+        Ok(())
+    }
+
+    /// Block a recipient on the wallet's denylist
+    pub fn add_denied_recipient(ctx: Context<ManageDenylist>, recipient: Pubkey) -> Result<()> {
+        ctx.accounts.denylist.insert(recipient)?;
+
+        emit!(DenylistUpdated {
+            wallet: Some(ctx.accounts.wallet.key()),
+            recipient,
+            denied: true,
+        });
+
+        Ok(())
+    }
+
+    /// Unblock a recipient on the wallet's denylist
+    pub fn remove_denied_recipient(ctx: Context<ManageDenylist>, recipient: Pubkey) -> Result<()> {
+        ctx.accounts.denylist.entries.retain(|r| *r != recipient);
+
+        emit!(DenylistUpdated {
+            wallet: Some(ctx.accounts.wallet.key()),
+            recipient,
+            denied: false,
+        });
+
+        Ok(())
+    }
+
+    /// Create the protocol-wide denylist (admin only); enforced in every send once created
+    pub fn create_protocol_denylist(ctx: Context<CreateProtocolDenylist>) -> Result<()> {
+        let denylist = &mut ctx.accounts.denylist;
+        denylist.entries = Vec::new();
+        denylist.bump = *ctx.bumps.get("denylist").unwrap();
+
+        ctx.accounts.config.denylist_enabled = true;
+
+        Ok(())
+    }
+
+    /// Block a recipient protocol-wide (admin only)
+    pub fn add_protocol_denied_recipient(
+        ctx: Context<ManageProtocolDenylist>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.denylist.insert(recipient)?;
+
+        emit!(DenylistUpdated {
+            wallet: None,
+            recipient,
+            denied: true,
+        });
+
+        Ok(())
+    }
+
+    /// Unblock a recipient protocol-wide (admin only)
+    pub fn remove_protocol_denied_recipient(
+        ctx: Context<ManageProtocolDenylist>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.denylist.entries.retain(|r| *r != recipient);
+
+        emit!(DenylistUpdated {
+            wallet: None,
+            recipient,
+            denied: false,
+        });
+
+        Ok(())
+    }
+
+    /// Require delegated sends above `threshold` to go through `queue_transfer` (0 disables)
+    pub fn set_timelock(ctx: Context<UpdateSpendPolicy>, threshold: u64, delay: i64) -> Result<()> {
+        require!(delay >= 0, ClawWalletError::InvalidTimelockDelay);
+        let policy = &mut ctx.accounts.spend_policy;
+        policy.timelock_threshold = threshold;
+        policy.timelock_delay = delay;
+
+        emit!(TimelockSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: None,
+            threshold,
+            delay,
+        });
+
+        Ok(())
+    }
+
+    /// Set the timelock threshold for an SPL token mint (0 disables)
+    pub fn set_token_timelock(
+        ctx: Context<UpdateSpendPolicy>,
+        mint: Pubkey,
+        threshold: u64,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.spend_policy;
+        policy.update_token_limit(mint, |l| l.timelock_threshold = threshold)?;
+
+        emit!(TimelockSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: Some(mint),
+            threshold,
+            delay: policy.timelock_delay,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a transfer for later execution at or after `execute_after`; delegated transfers
+    /// above the timelock threshold additionally wait out the wallet's timelock delay
+    pub fn queue_transfer(
+        ctx: Context<QueueTransfer>,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        execute_after: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let permission = if mint.is_some() {
+            PERM_SEND_TOKEN
+        } else {
+            PERM_SEND_SOL
+        };
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            permission,
+            amount,
+            now,
+        )?;
+
+        let mut delay = 0;
+        if role != SenderRole::Owner && ctx.accounts.wallet.has_spend_policy {
+            let policy = ctx
+                .accounts
+                .spend_policy
+                .as_ref()
+                .ok_or(ClawWalletError::SpendPolicyRequired)?;
+            let threshold = policy.timelock_threshold_for(mint);
+            if threshold != 0 && amount > threshold {
+                delay = if policy.timelock_delay > 0 {
+                    policy.timelock_delay
+                } else {
+                    DEFAULT_TIMELOCK_DELAY
+                };
+            }
+        }
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.queue_nonce;
+        wallet.queue_nonce += 1;
+        wallet.open_object(OPEN_QUEUED)?;
+
+        let queued = &mut ctx.accounts.queued_transfer;
+        queued.wallet = wallet.key();
+        queued.nonce = nonce;
+        queued.queued_by = ctx.accounts.authority.key();
+        queued.delegated = role != SenderRole::Owner;
+        queued.recipient = recipient;
+        queued.mint = mint;
+        queued.amount = amount;
+        queued.queued_at = now;
+        queued.executable_at = std::cmp::max(
+            now.checked_add(delay)
+                .ok_or(ClawWalletError::MathOverflow)?,
+            execute_after,
+        );
+        queued.expires_at = queued
+            .executable_at
+            .checked_add(QUEUED_TRANSFER_TTL)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        queued.bump = *ctx.bumps.get("queued_transfer").unwrap();
+
+        emit!(TransferQueued {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint,
+            amount,
+            executable_at: queued.executable_at,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a queued SOL transfer once its delay has passed (protocol fee)
+    pub fn execute_queued_transfer(ctx: Context<ExecuteQueuedTransfer>) -> Result<()> {
+        let queued = &ctx.accounts.queued_transfer;
+        require!(queued.mint.is_none(), ClawWalletError::WrongTransactionKind);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= queued.executable_at,
+            ClawWalletError::TimelockNotElapsed
+        );
+        require!(now < queued.expires_at, ClawWalletError::ApprovalExpired);
+
+        let amount = queued.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), queued.recipient],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[queued.recipient],
+        )?;
+
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+        if queued.delegated {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                amount,
+                true,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &[queued.recipient],
+            )?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+        pay_keeper_tip(
+            &wallet_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(QueuedTransferExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: queued.nonce,
+            recipient: queued.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+        ctx.accounts.wallet.close_object(OPEN_QUEUED);
+
+        Ok(())
+    }
+
+    /// Execute a queued SPL token transfer once its delay has passed (protocol fee)
+    pub fn execute_queued_token_transfer(ctx: Context<ExecuteQueuedTokenTransfer>) -> Result<()> {
+        let queued = &ctx.accounts.queued_transfer;
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            queued.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= queued.executable_at,
+            ClawWalletError::TimelockNotElapsed
+        );
+        require!(now < queued.expires_at, ClawWalletError::ApprovalExpired);
+
+        let amount = queued.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), queued.recipient],
+            Some(mint),
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        let destinations = [queued.recipient, ctx.accounts.recipient_token_account.owner];
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &destinations,
+        )?;
+        if queued.delegated {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                amount,
+                now,
+                true,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &destinations)?;
+        }
+
+        let wallet = &ctx.accounts.wallet;
+        let bump = wallet.bump;
+        let agent_id = wallet.agent_id.clone();
+        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, send_amount)?;
+
+        let cpi_ctx_fee = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx_fee, fee)?;
+        pay_keeper_tip(
+            &ctx.accounts.wallet.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx
+            .accounts
+            .config
+            .token_points_for(&ctx.accounts.wallet_token_account.mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(QueuedTransferExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: queued.nonce,
+            recipient: queued.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+        ctx.accounts.wallet.close_object(OPEN_QUEUED);
+
+        Ok(())
+    }
+
+    /// Owner escape hatch: drop a queued transfer before it executes
+    pub fn cancel_queued_transfer(ctx: Context<CancelQueuedTransfer>) -> Result<()> {
+        let queued = &ctx.accounts.queued_transfer;
+
+        emit!(QueuedTransferCancelled {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: queued.nonce,
+            recipient: queued.recipient,
+            amount: queued.amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_QUEUED);
+
+        Ok(())
+    }
+
+    /// Register additional approvers who can jointly approve payment proposals. Bumps the set's version,
+    /// so approvals gathered under the previous set no longer count
+    pub fn set_approvers(
+        ctx: Context<SetApprovers>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            approvers.len() <= MAX_APPROVERS,
+            ClawWalletError::InvalidApprovers
+        );
+        for (i, approver) in approvers.iter().enumerate() {
+            require!(
+                !approvers[..i].contains(approver),
+                ClawWalletError::InvalidApprovers
+            );
+        }
+        require!(
+            approvers.is_empty() || (threshold > 0 && threshold as usize <= approvers.len()),
+            ClawWalletError::InvalidThreshold
+        );
+
+        let approver_set = &mut ctx.accounts.approver_set;
+        approver_set.wallet = ctx.accounts.wallet.key();
+        approver_set.approvers = approvers.clone();
+        approver_set.threshold = threshold;
+        approver_set.bump = *ctx.bumps.get("approver_set").unwrap();
+        approver_set.version += 1;
+
+        emit!(ApproversSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            approvers,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a payment that executes only after owner (or approver quorum) approval
+    pub fn propose_payment(
+        ctx: Context<ProposePayment>,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let permission = if mint.is_some() {
+            PERM_SEND_TOKEN
+        } else {
+            PERM_SEND_SOL
+        };
+        authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            permission,
+            amount,
+            now,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.proposal_nonce;
+        wallet.proposal_nonce += 1;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.wallet = wallet.key();
+        proposal.nonce = nonce;
+        proposal.proposer = ctx.accounts.authority.key();
+        proposal.recipient = recipient;
+        proposal.mint = mint;
+        proposal.amount = amount;
+        proposal.approvals = 0;
+        proposal.approved = false;
+        proposal.approvers_version = 0;
+        proposal.created_at = now;
+        proposal.expires_at = now
+            .checked_add(PAYMENT_PROPOSAL_TTL)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+        emit!(PaymentProposed {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            proposer: proposal.proposer,
+            recipient,
+            mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Approve a payment proposal; the owner approves outright, approvers count toward a quorum
+    pub fn approve_payment(ctx: Context<ApprovePayment>) -> Result<()> {
+        let approver = ctx.accounts.approver.key();
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.approved, ClawWalletError::AlreadyApproved);
+        require!(
+            Clock::get()?.unix_timestamp < proposal.expires_at,
+            ClawWalletError::ApprovalExpired
+        );
+
+        if approver == ctx.accounts.wallet.owner {
+            proposal.approved = true;
+            proposal.approvals = 0; // owner approval doesn't depend on the approver set
+        } else {
+            let approver_set = ctx
+                .accounts
+                .approver_set
+                .as_ref()
+                .ok_or(ClawWalletError::Unauthorized)?;
+            let index = approver_set
+                .approvers
+                .iter()
+                .position(|a| *a == approver)
+                .ok_or(ClawWalletError::Unauthorized)?;
+            if proposal.approvers_version != approver_set.version {
+                // Bits index the set they were given under; start over against the current one
+                proposal.approvals = 0;
+                proposal.approvers_version = approver_set.version;
+            }
+            require!(
+                proposal.approvals & (1 << index) == 0,
+                ClawWalletError::AlreadyApproved
+            );
+            proposal.approvals |= 1 << index;
+            proposal.approved = proposal.approvals.count_ones() >= approver_set.threshold as u32;
+        }
+
+        emit!(PaymentApproved {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: proposal.nonce,
+            approver,
+            approved: proposal.approved,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an approved SOL payment proposal; callable by anyone (protocol fee)
+    pub fn execute_payment(ctx: Context<ExecutePayment>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            proposal.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(proposal.approved, ClawWalletError::ProposalNotApproved);
+        proposal.check_quorum_current(&ctx.accounts.approver_set)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < proposal.expires_at, ClawWalletError::ApprovalExpired);
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[proposal.recipient],
+        )?;
+
+        let amount = proposal.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), proposal.recipient],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_outflow(amount, now)?;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(PaymentExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: proposal.nonce,
+            recipient: proposal.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an approved SPL token payment proposal; callable by anyone (protocol fee)
+    pub fn execute_token_payment(ctx: Context<ExecuteTokenPayment>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            proposal.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(proposal.approved, ClawWalletError::ProposalNotApproved);
+        proposal.check_quorum_current(&ctx.accounts.approver_set)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < proposal.expires_at, ClawWalletError::ApprovalExpired);
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                proposal.recipient,
+                ctx.accounts.recipient_token_account.owner,
+            ],
+        )?;
+
+        let amount = proposal.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                proposal.recipient,
+                ctx.accounts.recipient_token_account.owner,
+            ],
+            proposal.mint,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.recipient_token_account,
+            &ctx.accounts.wallet,
+            send_amount,
+        )?;
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.wallet,
+            fee,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx
+            .accounts
+            .config
+            .token_points_for(&ctx.accounts.wallet_token_account.mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(PaymentExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: proposal.nonce,
+            recipient: proposal.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+
+        Ok(())
+    }
+
+    /// Owner rejects a payment proposal and returns its rent to the proposer
+    pub fn reject_payment(ctx: Context<RejectPayment>) -> Result<()> {
+        emit!(PaymentRejected {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.proposal.nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Execute several due SOL queue entries at once with a single aggregate fee;
+    /// remaining accounts are (queued_transfer, recipient, queued_by) triples
+    pub fn execute_queued_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteQueuedBatch<'info>>,
+    ) -> Result<()> {
+        let entries = ctx.remaining_accounts;
+        require!(
+            !entries.is_empty() && entries.len() % 3 == 0,
+            ClawWalletError::InvalidBatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        let mut total_fee: u64 = 0;
+        let mut points_earned: u64 = 0;
+
+        for entry in entries.chunks(3) {
+            let (queued_info, recipient_info, queued_by_info) = (&entry[0], &entry[1], &entry[2]);
+            let queued = Account::<QueuedTransfer>::try_from(queued_info)?;
+            require!(
+                queued.wallet == ctx.accounts.wallet.key(),
+                ClawWalletError::InvalidBatch
+            );
+            require!(queued.mint.is_none(), ClawWalletError::WrongTransactionKind);
+            require!(
+                queued.recipient == recipient_info.key(),
+                ClawWalletError::InvalidBatch
+            );
+            require!(
+                queued.queued_by == queued_by_info.key(),
+                ClawWalletError::InvalidBatch
+            );
+            require!(
+                now >= queued.executable_at,
+                ClawWalletError::TimelockNotElapsed
+            );
+            require!(now < queued.expires_at, ClawWalletError::ApprovalExpired);
+
+            let amount = queued.amount;
+            // Recipient exemptions don't apply across a batch
+            let fee = protocol_fee(
+                &ctx.accounts.config,
+                &ctx.accounts.fee_exemption,
+                &ctx.accounts.campaign,
+                ctx.accounts.wallet.key(),
+                &[ctx.accounts.wallet.key()],
+                None,
+                amount,
+                ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+                now,
+            )?;
+            let send_amount = amount - fee;
+
+            enforce_denylists(
+                &ctx.accounts.wallet,
+                &ctx.accounts.config,
+                &ctx.accounts.denylist,
+                &ctx.accounts.protocol_denylist,
+                &[queued.recipient],
+            )?;
+            ctx.accounts.wallet.record_outflow(amount, now)?;
+            if queued.delegated {
+                enforce_sol_policy(
+                    &ctx.accounts.wallet,
+                    &ctx.accounts.spend_policy,
+                    amount,
+                    true,
+                )?;
+                enforce_allowlist(
+                    &ctx.accounts.wallet,
+                    &ctx.accounts.allowlist,
+                    &[queued.recipient],
+                )?;
+            }
+
+            require_spendable(&wallet_info, amount)?;
+            transfer_lamports(&wallet_info, recipient_info, send_amount)?;
+            total_fee = total_fee
+                .checked_add(fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            let wallet = &mut ctx.accounts.wallet;
+            wallet.record_tx(now, &ctx.accounts.config);
+            let points = ctx.accounts.config.sol_points_for(amount);
+            points_earned = points_earned
+                .checked_add(points)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            emit!(QueuedTransferExecuted {
+                agent_id: wallet.agent_id.clone(),
+                nonce: queued.nonce,
+                recipient: queued.recipient,
+                mint: None,
+                amount: send_amount,
+                fee,
+                points_earned: points,
+            });
+
+            queued.close(queued_by_info.clone())?;
+            ctx.accounts.wallet.close_object(OPEN_QUEUED);
+        }
+
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.treasury.to_account_info(),
+            total_fee,
+        )?;
+        pay_keeper_tip(
+            &wallet_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+        ctx.accounts
+            .wallet
+            .add_points(points_earned, &ctx.accounts.config);
+
+        Ok(())
+    }
+
+    /// Permissionless cleanup: close an expired session key, returning rent to the wallet owner
+    pub fn close_expired_session(ctx: Context<CloseExpiredSession>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.session.expires_at,
+            ClawWalletError::NotExpired
+        );
+        ctx.accounts.wallet.session_count = ctx.accounts.wallet.session_count.saturating_sub(1);
+        pay_cleanup_bounty(
+            &ctx.accounts.session.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(ExpiredAccountClosed {
+            wallet: ctx.accounts.wallet.key(),
+            account: ctx.accounts.session.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless cleanup: close a lapsed queued transfer, returning rent to whoever queued it
+    pub fn close_expired_queued_transfer(ctx: Context<CloseExpiredQueuedTransfer>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.queued_transfer.expires_at,
+            ClawWalletError::NotExpired
+        );
+        pay_cleanup_bounty(
+            &ctx.accounts.queued_transfer.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(ExpiredAccountClosed {
+            wallet: ctx.accounts.queued_transfer.wallet,
+            account: ctx.accounts.queued_transfer.key(),
+        });
+        ctx.accounts.wallet.close_object(OPEN_QUEUED);
+
+        Ok(())
+    }
+
+    /// Permissionless cleanup: close a lapsed payment proposal, returning rent to the proposer
+    pub fn close_expired_proposal(ctx: Context<CloseExpiredProposal>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.proposal.expires_at,
+            ClawWalletError::NotExpired
+        );
+        pay_cleanup_bounty(
+            &ctx.accounts.proposal.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(ExpiredAccountClosed {
+            wallet: ctx.accounts.proposal.wallet,
+            account: ctx.accounts.proposal.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Decommission a wallet: close any token accounts passed as remaining accounts (must be empty and
+    /// belong to `token_program`, SPL Token or Token-2022) and the wallet's companion PDAs, then close the
+    /// wallet PDA and return all lamports to the owner. Session keys must be revoked and every escrow,
+    /// stream and other open object closed first, so a later wallet with the same agent_id starts clean and
+    /// nothing pays into it
+    pub fn close_wallet<'info>(ctx: Context<'_, '_, '_, 'info, CloseWallet<'info>>) -> Result<()> {
+        require!(
+            ctx.accounts.wallet.session_count == 0,
+            ClawWalletError::SessionsOpen
+        );
+        require!(
+            ctx.accounts.wallet.open_objects.iter().all(|n| *n == 0),
+            ClawWalletError::ObjectsOpen
+        );
+        let owner_info = ctx.accounts.owner.to_account_info();
+        let mut companions_closed = 0u8;
+        for companion in [
+            &ctx.accounts.spend_policy,
+            &ctx.accounts.allowlist,
+            &ctx.accounts.denylist,
+            &ctx.accounts.recovery,
+            &ctx.accounts.approver_set,
+            &ctx.accounts.metadata,
+            &ctx.accounts.multisig,
+        ] {
+            if close_companion(companion, &owner_info)? {
+                companions_closed += 1;
+            }
+        }
+
+        let wallet = &ctx.accounts.wallet;
+        let seeds = &[
+            b"wallet".as_ref(),
+            wallet.agent_id.as_bytes(),
+            &[wallet.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        for token_account in ctx.remaining_accounts.iter() {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: token_account.clone(),
+                    destination: ctx.accounts.owner.to_account_info(),
+                    authority: wallet.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token_interface::close_account(cpi_ctx)?;
+        }
+
+        emit!(WalletClosed {
+            agent_id: wallet.agent_id.clone(),
+            owner: wallet.owner,
+            lamports: wallet.to_account_info().lamports(),
+            token_accounts_closed: ctx.remaining_accounts.len() as u8,
+            companions_closed,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: grow a wallet created before fields were added to AgentWallet to the current size,
+    /// so it deserializes again with the new fields at zero; `payer` covers the extra rent
+    pub fn migrate_wallet(ctx: Context<MigrateAccount>) -> Result<()> {
+        grow_account(
+            &ctx.accounts.account,
+            AgentWallet::DISCRIMINATOR,
+            8 + AgentWallet::INIT_SPACE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )
+    }
+
+    /// Permissionless: the same for the protocol config after fields were added to ProtocolConfig
+    pub fn migrate_config(ctx: Context<MigrateAccount>) -> Result<()> {
+        grow_account(
+            &ctx.accounts.account,
+            ProtocolConfig::DISCRIMINATOR,
+            8 + ProtocolConfig::INIT_SPACE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )
+    }
+
+    /// Set the agent's display name, metadata URI, and description
+    pub fn update_metadata(
+        ctx: Context<UpdateMetadata>,
+        display_name: String,
+        uri: String,
+        description: String,
+    ) -> Result<()> {
+        require!(
+            display_name.len() <= MAX_DISPLAY_NAME_LEN
+                && uri.len() <= MAX_METADATA_URI_LEN
+                && description.len() <= MAX_DESCRIPTION_LEN,
+            ClawWalletError::MetadataTooLong
+        );
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.wallet = ctx.accounts.wallet.key();
+        metadata.display_name = display_name.clone();
+        metadata.uri = uri.clone();
+        metadata.description = description;
+        metadata.updated_at = Clock::get()?.unix_timestamp;
+        metadata.bump = *ctx.bumps.get("metadata").unwrap();
+
+        emit!(MetadataUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            display_name,
+            uri,
+        });
+
+        Ok(())
+    }
+
+    /// Set the protocol fee in basis points (capped at MAX_FEE_BPS)
+    pub fn set_fee_bps(ctx: Context<AdminConfig>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, ClawWalletError::FeeTooHigh);
+        ctx.accounts.config.fee_bps = fee_bps;
+
+        emit!(FeeBpsSet {
+            admin: ctx.accounts.admin.key(),
+            fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new treasury; takes effect via finalize_treasury after TREASURY_CHANGE_DELAY
+    pub fn set_treasury(ctx: Context<AdminConfig>, new_treasury: Pubkey) -> Result<()> {
+        require!(
+            new_treasury != Pubkey::default(),
+            ClawWalletError::InvalidTreasury
+        );
+        let effective_at = Clock::get()?.unix_timestamp + TREASURY_CHANGE_DELAY;
+        let config = &mut ctx.accounts.config;
+        config.pending_treasury = new_treasury;
+        config.treasury_change_at = effective_at;
+
+        emit!(TreasuryChangeProposed {
+            admin: ctx.accounts.admin.key(),
+            new_treasury,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a proposed treasury change once its delay has elapsed
+    pub fn finalize_treasury(ctx: Context<AdminConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.pending_treasury != Pubkey::default(),
+            ClawWalletError::NoPendingTreasury
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.treasury_change_at,
+            ClawWalletError::TreasuryChangeNotReady
+        );
+
+        let old_treasury = config.treasury;
+        config.treasury = config.pending_treasury;
+        config.pending_treasury = Pubkey::default();
+        config.treasury_change_at = 0;
+
+        emit!(TreasuryChanged {
+            admin: ctx.accounts.admin.key(),
+            old_treasury,
+            new_treasury: config.treasury,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the points-based fee tiers (e.g. 0.5% -> 0.3% -> 0.1% as points grow)
+    pub fn set_fee_tiers(ctx: Context<AdminConfig>, tiers: Vec<FeeTier>) -> Result<()> {
+        require!(
+            tiers.len() <= MAX_FEE_TIERS,
+            ClawWalletError::TooManyFeeTiers
+        );
+        let config = &mut ctx.accounts.config;
+        let mut prev: Option<&FeeTier> = None;
+        for tier in tiers.iter() {
+            require!(
+                tier.fee_bps <= config.fee_bps,
+                ClawWalletError::InvalidFeeTiers
+            );
+            if let Some(prev) = prev {
+                require!(
+                    tier.min_points > prev.min_points && tier.fee_bps <= prev.fee_bps,
+                    ClawWalletError::InvalidFeeTiers
+                );
+            }
+            prev = Some(tier);
+        }
+        config.fee_tiers = tiers.clone();
+
+        emit!(FeeTiersSet {
+            admin: ctx.accounts.admin.key(),
+            tiers,
+        });
+
+        Ok(())
+    }
+
+    /// Waive protocol fees for a wallet or recipient (admin only)
+    pub fn add_fee_exemption(
+        ctx: Context<AddFeeExemption>,
+        subject: Pubkey,
+        reason: u8,
+    ) -> Result<()> {
+        let exemption = &mut ctx.accounts.exemption;
+        exemption.subject = subject;
+        exemption.reason = reason;
+        exemption.created_at = Clock::get()?.unix_timestamp;
+        exemption.bump = *ctx.bumps.get("exemption").unwrap();
+
+        emit!(FeeExemptionUpdated {
+            subject,
+            reason,
+            exempt: true,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a fee exemption, returning its rent to the admin
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+        emit!(FeeExemptionUpdated {
+            subject: ctx.accounts.exemption.subject,
+            reason: ctx.accounts.exemption.reason,
+            exempt: false,
+        });
+
+        Ok(())
+    }
+
+    /// Set the minimum SOL fee (lamports) and the fee rounding policy. The fee never exceeds the amount;
+    /// direct sends that the minimum would consume entirely are rejected
+    pub fn set_fee_rules(ctx: Context<AdminConfig>, min_fee: u64, rounding: u8) -> Result<()> {
+        require!(min_fee <= MAX_MIN_FEE, ClawWalletError::FeeTooHigh);
+        require!(
+            rounding == FEE_ROUND_DOWN || rounding == FEE_ROUND_UP,
+            ClawWalletError::InvalidFeeRounding
+        );
+        let config = &mut ctx.accounts.config;
+        config.min_fee = min_fee;
+        config.fee_rounding = rounding;
+
+        emit!(FeeRulesSet {
+            admin: ctx.accounts.admin.key(),
+            min_fee,
+            rounding,
+        });
+
+        Ok(())
+    }
+
+    /// Read-only fee quote for a send of `mint` (None = SOL) from `wallet` to `recipient`
+    /// (simulate to read the result)
+    pub fn quote_fee(
+        ctx: Context<QuoteFee>,
+        amount: u64,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+    ) -> Result<FeeQuote> {
+        let wallet = &ctx.accounts.wallet;
+        let config = &ctx.accounts.config;
+        let exempt = ctx.accounts.fee_exemption.as_ref().map_or(false, |e| {
+            e.subject == wallet.key() || e.subject == recipient
+        });
+
+        let mint_fee = ctx
+            .accounts
+            .mint_fee
+            .as_ref()
+            .map(|m| m.clone().into_inner());
+        if let Some(m) = &mint_fee {
+            require!(Some(m.mint) == mint, ClawWalletError::MintMismatch);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let base_bps = match mint {
+            Some(_) => token_fee_bps(config, &mint_fee, wallet.points),
+            None => config.fee_bps_for(wallet.points),
+        };
+        let bps = campaign_fee_bps(&ctx.accounts.campaign, base_bps, mint, now)?;
+        let fee = if exempt {
+            0
+        } else if mint.is_some() {
+            let fee = config.token_fee_at(amount, bps);
+            mint_fee.map_or(fee, |m| apply_fee_cap(fee, m.max_fee))
+        } else {
+            config.sol_fee_at(amount, bps)
+        };
+        let fee = if wallet.fee_discount_credits > 0 {
+            config.discounted_fee(fee)
+        } else {
+            fee
+        };
+
+        Ok(FeeQuote {
+            fee_bps: if exempt { 0 } else { bps },
+            fee,
+            send_amount: amount - fee,
+        })
+    }
+
+    /// Cap the absolute fee per transfer (lamports for SOL, raw units for tokens; 0 = uncapped)
+    pub fn set_fee_cap(ctx: Context<AdminConfig>, max_fee: u64, max_token_fee: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.max_fee = max_fee;
+        config.max_token_fee = max_token_fee;
+
+        emit!(FeeCapSet {
+            admin: ctx.accounts.admin.key(),
+            max_fee,
+            max_token_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule a time-boxed fee campaign (fee_bps = 0 waives fees; mint = None covers every transfer)
+    pub fn create_fee_campaign(
+        ctx: Context<CreateFeeCampaign>,
+        start_at: i64,
+        end_at: i64,
+        mint: Option<Pubkey>,
+        fee_bps: u16,
+    ) -> Result<()> {
+        require!(end_at > start_at, ClawWalletError::InvalidCampaignWindow);
+        require!(
+            fee_bps <= ctx.accounts.config.fee_bps,
+            ClawWalletError::FeeTooHigh
+        );
+
+        let config = &mut ctx.accounts.config;
+        let id = config.campaign_nonce;
+        config.campaign_nonce += 1;
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.id = id;
+        campaign.start_at = start_at;
+        campaign.end_at = end_at;
+        campaign.mint = mint;
+        campaign.fee_bps = fee_bps;
+        campaign.bump = *ctx.bumps.get("campaign").unwrap();
+
+        emit!(FeeCampaignCreated {
+            id,
+            start_at,
+            end_at,
+            mint,
+            fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// End a fee campaign early (or clean up a finished one), returning its rent to the admin
+    pub fn close_fee_campaign(ctx: Context<CloseFeeCampaign>) -> Result<()> {
+        emit!(FeeCampaignClosed {
+            id: ctx.accounts.campaign.id,
+        });
+
+        Ok(())
+    }
+
+    /// Set (or update) the fee rate and optional cap for one SPL mint (admin only)
+    pub fn set_mint_fee(ctx: Context<SetMintFee>, fee_bps: u16, max_fee: u64) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, ClawWalletError::FeeTooHigh);
+        let mint_fee = &mut ctx.accounts.mint_fee;
+        mint_fee.mint = ctx.accounts.mint.key();
+        mint_fee.fee_bps = fee_bps;
+        mint_fee.max_fee = max_fee;
+        mint_fee.bump = *ctx.bumps.get("mint_fee").unwrap();
+
+        emit!(MintFeeSet {
+            mint: mint_fee.mint,
+            fee_bps,
+            max_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Record the agent that referred this wallet; only allowed before the wallet's first transfer, and
+    /// not to the wallet itself or another wallet of the same owner
+    pub fn register_referral(
+        ctx: Context<RegisterReferral>,
+        referrer_agent_id: String,
+    ) -> Result<()> {
+        let referrer = ctx.accounts.referrer.key();
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            wallet.referrer == Pubkey::default(),
+            ClawWalletError::ReferrerAlreadySet
+        );
+        require!(wallet.tx_count == 0, ClawWalletError::ReferralWindowClosed);
+        require!(referrer != wallet.key(), ClawWalletError::InvalidReferrer);
+        require!(
+            ctx.accounts.referrer.owner != wallet.owner,
+            ClawWalletError::InvalidReferrer
+        );
+        wallet.referrer = referrer;
+
+        emit!(ReferralRegistered {
+            agent_id: wallet.agent_id.clone(),
+            referrer_agent_id,
+            referrer,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of the fee (in bps of the fee) paid to the sender's referrer. It is taken on send_sol,
+    /// send_to_agent, send_token, the batch sends and send_multi's SOL leg; other payment paths pay no share
+    pub fn set_referral_share(ctx: Context<AdminConfig>, share_bps: u16) -> Result<()> {
+        require!(
+            share_bps <= MAX_REFERRAL_SHARE_BPS,
+            ClawWalletError::FeeTooHigh
+        );
+        ctx.accounts.config.referral_share_bps = share_bps;
+
+        emit!(ReferralShareSet {
+            admin: ctx.accounts.admin.key(),
+            share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Mint a human-readable referral code (letters, digits, '-' and '_') pointing at this wallet
+    pub fn create_referral_code(ctx: Context<CreateReferralCode>, code: String) -> Result<()> {
+        require!(
+            (MIN_REFERRAL_CODE_LEN..=MAX_REFERRAL_CODE_LEN).contains(&code.len())
+                && code
+                    .bytes()
+                    .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'),
+            ClawWalletError::InvalidReferralCode
+        );
+
+        let referral_code = &mut ctx.accounts.referral_code;
+        referral_code.code = code.clone();
+        referral_code.wallet = ctx.accounts.wallet.key();
+        referral_code.signups = 0;
+        referral_code.referred_tx_count = 0;
+        referral_code.referred_volume = 0;
+        referral_code.created_at = Clock::get()?.unix_timestamp;
+        referral_code.bump = *ctx.bumps.get("referral_code").unwrap();
+
+        emit!(ReferralCodeCreated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            code,
+        });
+
+        Ok(())
+    }
+
+    /// Register this wallet's referrer by code; only allowed before the wallet's first transfer
+    pub fn register_referral_code(ctx: Context<RegisterReferralCode>, code: String) -> Result<()> {
+        let referral_code = &mut ctx.accounts.referral_code;
+        let wallet = &mut ctx.accounts.wallet;
+        require!(
+            wallet.referrer == Pubkey::default(),
+            ClawWalletError::ReferrerAlreadySet
+        );
+        require!(wallet.tx_count == 0, ClawWalletError::ReferralWindowClosed);
+        require!(
+            referral_code.wallet != wallet.key(),
+            ClawWalletError::InvalidReferrer
+        );
+        require!(
+            ctx.accounts.referrer.owner != wallet.owner,
+            ClawWalletError::InvalidReferrer
+        );
+
+        wallet.referrer = referral_code.wallet;
+        wallet.referral_code = referral_code.key();
+        referral_code.signups = referral_code
+            .signups
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(ReferralCodeUsed {
+            agent_id: wallet.agent_id.clone(),
+            code,
+            referrer: referral_code.wallet,
+            signups: referral_code.signups,
+        });
+
+        Ok(())
+    }
+
+    /// Create the protocol fee vault that fees accrue into while accrual is enabled
+    pub fn create_fee_vault(ctx: Context<CreateFeeVault>) -> Result<()> {
+        let vault = &mut ctx.accounts.fee_vault;
+        vault.total_claimed = 0;
+        vault.bump = *ctx.bumps.get("fee_vault").unwrap();
+
+        ctx.accounts.config.fee_vault = vault.key();
+
+        Ok(())
+    }
+
+    /// Route fees into the fee vault (true) or straight to the treasury (false)
+    pub fn set_fee_accrual(ctx: Context<AdminConfig>, enabled: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.fee_vault != Pubkey::default(),
+            ClawWalletError::FeeVaultRequired
+        );
+        config.accrue_fees = enabled;
+
+        emit!(FeeAccrualSet {
+            admin: ctx.accounts.admin.key(),
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Treasury withdraws accrued SOL fees from the fee vault
+    pub fn claim_fees(ctx: Context<ClaimFees>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.config.fee_beneficiaries.is_empty(),
+            ClawWalletError::FeeSplitActive
+        );
+        let vault_info = ctx.accounts.fee_vault.to_account_info();
+        require_spendable(&vault_info, amount)?;
+        transfer_lamports(
+            &vault_info,
+            &ctx.accounts.treasury.to_account_info(),
+            amount,
+        )?;
+
+        let vault = &mut ctx.accounts.fee_vault;
+        vault.total_claimed = vault
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(FeesClaimed {
+            treasury: ctx.accounts.treasury.key(),
+            mint: None,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Treasury withdraws accrued token fees from the fee vault's token account
+    pub fn claim_token_fees(ctx: Context<ClaimTokenFees>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.config.fee_beneficiaries.is_empty(),
+            ClawWalletError::FeeSplitActive
+        );
+        let seeds = &[b"fee_vault".as_ref(), &[ctx.accounts.fee_vault.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.fee_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(FeesClaimed {
+            treasury: ctx.accounts.treasury.key(),
+            mint: Some(ctx.accounts.vault_token_account.mint),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Split claimed fees among beneficiaries by share (shares must total 10_000 bps; empty = treasury only)
+    pub fn set_fee_beneficiaries(
+        ctx: Context<AdminConfig>,
+        beneficiaries: Vec<FeeBeneficiary>,
+    ) -> Result<()> {
+        require!(
+            beneficiaries.len() <= MAX_FEE_BENEFICIARIES,
+            ClawWalletError::InvalidFeeBeneficiaries
+        );
+        if !beneficiaries.is_empty() {
+            let total: u32 = beneficiaries.iter().map(|b| b.share_bps as u32).sum();
+            require!(
+                total == 10_000
+                    && beneficiaries
+                        .iter()
+                        .all(|b| b.recipient != Pubkey::default()),
+                ClawWalletError::InvalidFeeBeneficiaries
+            );
+        }
+        ctx.accounts.config.fee_beneficiaries = beneficiaries.clone();
+
+        emit!(FeeBeneficiariesSet {
+            admin: ctx.accounts.admin.key(),
+            beneficiaries,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay accrued SOL fees from the vault to the beneficiaries;
+    /// remaining accounts are the beneficiary recipients in config order
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let beneficiaries = &ctx.accounts.config.fee_beneficiaries;
+        require!(
+            !beneficiaries.is_empty(),
+            ClawWalletError::InvalidFeeBeneficiaries
+        );
+        require!(
+            ctx.remaining_accounts.len() == beneficiaries.len(),
+            ClawWalletError::InvalidFeeBeneficiaries
+        );
+
+        let vault_info = ctx.accounts.fee_vault.to_account_info();
+        require_spendable(&vault_info, amount)?;
+
+        let shares = fee_shares(beneficiaries, amount);
+        for ((beneficiary, recipient), share) in
+            beneficiaries.iter().zip(ctx.remaining_accounts).zip(shares)
+        {
+            require!(
+                recipient.key() == beneficiary.recipient,
+                ClawWalletError::InvalidFeeBeneficiaries
+            );
+            transfer_lamports(&vault_info, recipient, share)?;
+        }
+
+        let vault = &mut ctx.accounts.fee_vault;
+        vault.total_claimed = vault
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(FeesDistributed {
+            mint: None,
+            amount,
+            beneficiaries: ctx.accounts.config.fee_beneficiaries.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay accrued token fees to the beneficiaries;
+    /// remaining accounts are each beneficiary's token account for the mint, in config order
+    pub fn distribute_token_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeTokenFees<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let beneficiaries = &ctx.accounts.config.fee_beneficiaries;
+        require!(
+            !beneficiaries.is_empty(),
+            ClawWalletError::InvalidFeeBeneficiaries
+        );
+        require!(
+            ctx.remaining_accounts.len() == beneficiaries.len(),
+            ClawWalletError::InvalidFeeBeneficiaries
+        );
+
+        let mint = ctx.accounts.vault_token_account.mint;
+        let seeds = &[b"fee_vault".as_ref(), &[ctx.accounts.fee_vault.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let shares = fee_shares(beneficiaries, amount);
+        for ((beneficiary, info), share) in
+            beneficiaries.iter().zip(ctx.remaining_accounts).zip(shares)
+        {
+            let token_account = Account::<TokenAccount>::try_from(info)?;
+            require!(
+                token_account.owner == beneficiary.recipient && token_account.mint == mint,
+                ClawWalletError::InvalidFeeBeneficiaries
+            );
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SplTransfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: info.clone(),
+                    authority: ctx.accounts.fee_vault.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(cpi_ctx, share)?;
+        }
+
+        emit!(FeesDistributed {
+            mint: Some(mint),
+            amount,
+            beneficiaries: beneficiaries.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Burn points for fee-discount credits; each credit discounts one future send
+    pub fn redeem_points_for_discount(ctx: Context<BurnPoints>, points: u64) -> Result<()> {
+        let cost = ctx.accounts.config.discount_cost;
+        require!(cost > 0, ClawWalletError::DiscountsDisabled);
+        let credits = points / cost;
+        require!(credits > 0, ClawWalletError::InvalidAmount);
+        let burned = credits * cost;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.settle_points(&ctx.accounts.config, Clock::get()?.unix_timestamp);
+        wallet.points = wallet
+            .points
+            .checked_sub(burned)
+            .ok_or(ClawWalletError::InsufficientPoints)?;
+        wallet.fee_discount_credits = wallet
+            .fee_discount_credits
+            .checked_add(credits)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(PointsRedeemed {
+            agent_id: wallet.agent_id.clone(),
+            points_burned: burned,
+            credits,
+        });
+
+        Ok(())
+    }
+
+    /// Set the points cost of one discount credit (0 disables redemption) and its discount off the fee
+    pub fn set_points_discount(
+        ctx: Context<AdminConfig>,
+        discount_cost: u64,
+        discount_bps: u16,
+    ) -> Result<()> {
+        require!(discount_bps <= 10_000, ClawWalletError::InvalidAmount);
+        let config = &mut ctx.accounts.config;
+        config.discount_cost = discount_cost;
+        config.discount_bps = discount_bps;
+
+        emit!(PointsDiscountSet {
+            admin: ctx.accounts.admin.key(),
+            discount_cost,
+            discount_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Gift points to another agent; the configured transfer tax is burned
+    pub fn transfer_points(ctx: Context<TransferPoints>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let tax =
+            (amount as u128 * ctx.accounts.config.points_transfer_tax_bps as u128 / 10_000) as u64;
+        let received = amount - tax;
+
+        let now = Clock::get()?.unix_timestamp;
+        let from_wallet = &mut ctx.accounts.from_wallet;
+        from_wallet.settle_points(&ctx.accounts.config, now);
+        from_wallet.points = from_wallet
+            .points
+            .checked_sub(amount)
+            .ok_or(ClawWalletError::InsufficientPoints)?;
+        let to_wallet = &mut ctx.accounts.to_wallet;
+        to_wallet.settle_points(&ctx.accounts.config, now);
+        to_wallet.points = to_wallet
+            .points
+            .checked_add(received)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let cap = ctx.accounts.config.points_cap;
+        require!(
+            cap == 0 || to_wallet.points <= cap,
+            ClawWalletError::PointsCapExceeded
+        );
+
+        update_leaderboard(
+            &ctx.accounts.leaderboard,
+            from_wallet.key(),
+            from_wallet.points,
+        )?;
+        update_leaderboard(&ctx.accounts.leaderboard, to_wallet.key(), to_wallet.points)?;
+
+        emit!(PointsTransferred {
+            from_agent: from_wallet.agent_id.clone(),
+            to_agent: to_wallet.agent_id.clone(),
+            amount: received,
+            tax,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of transferred points burned as tax (bps)
+    pub fn set_points_transfer_tax(ctx: Context<AdminConfig>, tax_bps: u16) -> Result<()> {
+        require!(tax_bps <= 10_000, ClawWalletError::InvalidAmount);
+        ctx.accounts.config.points_transfer_tax_bps = tax_bps;
+
+        emit!(PointsTransferTaxSet {
+            admin: ctx.accounts.admin.key(),
+            tax_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Create the protocol-wide points leaderboard (admin only)
+    pub fn create_leaderboard(ctx: Context<CreateLeaderboard>) -> Result<()> {
+        let mut board = ctx.accounts.leaderboard.load_init()?;
+        board.bump = *ctx.bumps.get("leaderboard").unwrap();
+        Ok(())
+    }
+
+    /// Permissionless: place a wallet on the leaderboard if its points qualify
+    pub fn submit_score(ctx: Context<SubmitScore>) -> Result<()> {
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.settle_points(&ctx.accounts.config, Clock::get()?.unix_timestamp);
+        let points = wallet.points;
+        let rank = ctx
+            .accounts
+            .leaderboard
+            .load_mut()?
+            .submit(wallet.key(), points);
+
+        emit!(ScoreSubmitted {
+            agent_id: wallet.agent_id.clone(),
+            points,
+            rank: rank.map(|r| r as u8),
+        });
+
+        Ok(())
+    }
+
+    /// Create the vault that funds epoch rewards (fund it by transferring SOL or tokens to it)
+    pub fn create_reward_vault(ctx: Context<CreateRewardVault>) -> Result<()> {
+        ctx.accounts.reward_vault.bump = *ctx.bumps.get("reward_vault").unwrap();
+        Ok(())
+    }
+
+    /// Set the reward epoch length; the current epoch restarts now
+    pub fn set_epoch_duration(ctx: Context<AdminConfig>, epoch_duration: i64) -> Result<()> {
+        require!(epoch_duration > 0, ClawWalletError::InvalidEpoch);
+        let config = &mut ctx.accounts.config;
+        config.epoch_duration = epoch_duration;
+        config.epoch_started_at = Clock::get()?.unix_timestamp;
+
+        emit!(EpochDurationSet {
+            admin: ctx.accounts.admin.key(),
+            epoch: config.epoch,
+            epoch_duration,
+        });
+
+        Ok(())
+    }
+
+    /// Close the current epoch: snapshot the top leaderboard wallets with a reward per rank
+    /// (`mint` = None pays SOL), then clear the board and start the next epoch, so each epoch ranks
+    /// the wallets that were active (or submitted a score) during it
+    pub fn finalize_epoch(
+        ctx: Context<FinalizeEpoch>,
+        rewards: Vec<u64>,
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.epoch_duration > 0 && now >= config.epoch_started_at + config.epoch_duration,
+            ClawWalletError::InvalidEpoch
+        );
+
+        let mut board = ctx.accounts.leaderboard.load_mut()?;
+        require!(
+            rewards.len() <= MAX_EPOCH_WINNERS && rewards.len() <= board.count as usize,
+            ClawWalletError::InvalidEpoch
+        );
+
+        let epoch_reward = &mut ctx.accounts.epoch_reward;
+        epoch_reward.epoch = config.epoch;
+        epoch_reward.mint = mint;
+        epoch_reward.winners = board.entries[..rewards.len()]
+            .iter()
+            .map(|e| e.wallet)
+            .collect();
+        epoch_reward.amounts = rewards;
+        epoch_reward.claimed = 0;
+        epoch_reward.finalized_at = now;
+        epoch_reward.bump = *ctx.bumps.get("epoch_reward").unwrap();
+        board.clear();
+
+        config.epoch += 1;
+        config.epoch_started_at = now;
+
+        emit!(EpochFinalized {
+            epoch: epoch_reward.epoch,
+            mint,
+            winners: epoch_reward.winners.clone(),
+            amounts: epoch_reward.amounts.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay a winner's SOL epoch reward from the reward vault into its wallet
+    pub fn claim_epoch_reward(ctx: Context<ClaimEpochReward>) -> Result<()> {
+        let epoch_reward = &mut ctx.accounts.epoch_reward;
+        require!(
+            epoch_reward.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        let amount = epoch_reward.claim(&ctx.accounts.wallet.key())?;
+
+        let vault_info = ctx.accounts.reward_vault.to_account_info();
+        require_spendable(&vault_info, amount)?;
+        transfer_lamports(&vault_info, &ctx.accounts.wallet.to_account_info(), amount)?;
+
+        emit!(EpochRewardClaimed {
+            epoch: epoch_reward.epoch,
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: None,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay a winner's token epoch reward into its wallet's token account
+    pub fn claim_epoch_token_reward(ctx: Context<ClaimEpochTokenReward>) -> Result<()> {
+        let epoch_reward = &mut ctx.accounts.epoch_reward;
+        let mint = epoch_reward
+            .mint
+            .ok_or(ClawWalletError::WrongTransactionKind)?;
+        require!(
+            ctx.accounts.vault_token_account.mint == mint,
+            ClawWalletError::MintMismatch
+        );
+        let amount = epoch_reward.claim(&ctx.accounts.wallet.key())?;
+
+        let seeds = &[b"reward_vault".as_ref(), &[ctx.accounts.reward_vault.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.wallet_token_account.to_account_info(),
+                authority: ctx.accounts.reward_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(EpochRewardClaimed {
+            epoch: epoch_reward.epoch,
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: Some(mint),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of points lost per POINTS_DECAY_PERIOD of inactivity (0 disables decay)
+    pub fn set_points_decay(ctx: Context<AdminConfig>, decay_bps: u16) -> Result<()> {
+        require!(decay_bps <= 10_000, ClawWalletError::InvalidAmount);
+        ctx.accounts.config.points_decay_bps = decay_bps;
+
+        emit!(PointsDecaySet {
+            admin: ctx.accounts.admin.key(),
+            decay_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-wallet points cap (0 = uncapped)
+    pub fn set_points_cap(ctx: Context<AdminConfig>, points_cap: u64) -> Result<()> {
+        ctx.accounts.config.points_cap = points_cap;
+
+        emit!(PointsCapSet {
+            admin: ctx.accounts.admin.key(),
+            points_cap,
+        });
+
+        Ok(())
+    }
+
+    /// Reset a capped-out points balance to zero in exchange for a permanent prestige rank
+    pub fn prestige(ctx: Context<BurnPoints>) -> Result<()> {
+        let cap = ctx.accounts.config.points_cap;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.settle_points(&ctx.accounts.config, Clock::get()?.unix_timestamp);
+        require!(
+            cap > 0 && wallet.points >= cap,
+            ClawWalletError::PointsCapNotReached
+        );
+        let points_reset = wallet.points;
+        wallet.points = 0;
+        wallet.prestige = wallet
+            .prestige
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(Prestiged {
+            agent_id: wallet.agent_id.clone(),
+            prestige: wallet.prestige,
+            points_reset,
+        });
+
+        Ok(())
+    }
+
+    /// Burn points for an integration-defined sink; `reason` is a free-form code for indexers
+    pub fn burn_points(ctx: Context<BurnPoints>, amount: u64, reason: u16) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.settle_points(&ctx.accounts.config, Clock::get()?.unix_timestamp);
+        wallet.points = wallet
+            .points
+            .checked_sub(amount)
+            .ok_or(ClawWalletError::InsufficientPoints)?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(PointsBurned {
+            agent_id: wallet.agent_id.clone(),
+            amount,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    /// Mint the badge PDA for an achievement the wallet has unlocked (anyone may pay the rent). Badges
+    /// unlock on their own as sends cross the thresholds; this only materialises the PDA
+    pub fn mint_badge(ctx: Context<MintBadge>, badge: u8) -> Result<()> {
+        let wallet = &ctx.accounts.wallet;
+        require!(
+            badge < 32 && wallet.badges & (1 << badge) != 0,
+            ClawWalletError::BadgeLocked
+        );
+
+        let badge_account = &mut ctx.accounts.badge;
+        badge_account.wallet = wallet.key();
+        badge_account.badge = badge;
+        badge_account.minted_at = Clock::get()?.unix_timestamp;
+        badge_account.bump = *ctx.bumps.get("badge").unwrap();
+
+        Ok(())
+    }
+
+    /// Create the program-controlled reward mint (config PDA is the mint authority)
+    pub fn create_reward_mint(ctx: Context<CreateRewardMint>, reward_rate: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.reward_mint = ctx.accounts.reward_mint.key();
+        config.reward_rate = reward_rate;
+
+        emit!(RewardRateSet {
+            admin: ctx.accounts.admin.key(),
+            reward_mint: config.reward_mint,
+            reward_rate,
+        });
+
+        Ok(())
+    }
+
+    /// Set how many raw reward-token units one point converts into (0 disables claims)
+    pub fn set_reward_rate(ctx: Context<AdminConfig>, reward_rate: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.reward_mint != Pubkey::default(),
+            ClawWalletError::RewardsDisabled
+        );
+        config.reward_rate = reward_rate;
+
+        emit!(RewardRateSet {
+            admin: ctx.accounts.admin.key(),
+            reward_mint: config.reward_mint,
+            reward_rate,
+        });
+
+        Ok(())
+    }
+
+    /// Burn points and mint reward tokens to the wallet's ATA at the configured rate
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, points: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.reward_rate > 0, ClawWalletError::RewardsDisabled);
+        require!(points > 0, ClawWalletError::InvalidAmount);
+        let amount = points
+            .checked_mul(config.reward_rate)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.settle_points(config, Clock::get()?.unix_timestamp);
+        wallet.points = wallet
+            .points
+            .checked_sub(points)
+            .ok_or(ClawWalletError::InsufficientPoints)?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        let seeds = &[b"config".as_ref(), &[config.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                to: ctx.accounts.wallet_token_account.to_account_info(),
+                authority: ctx.accounts.config.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(cpi_ctx, amount)?;
+
+        emit!(RewardsClaimed {
+            agent_id: wallet.agent_id.clone(),
+            points,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create a merkle distributor for points (`mint` = None) or tokens held by the distributor PDA
+    pub fn create_distributor(
+        ctx: Context<CreateDistributor>,
+        root: [u8; 32],
+        mint: Option<Pubkey>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let id = config.distributor_nonce;
+        config.distributor_nonce += 1;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.id = id;
+        distributor.root = root;
+        distributor.mint = mint;
+        distributor.total_claimed = 0;
+        distributor.claims = 0;
+        distributor.bump = *ctx.bumps.get("distributor").unwrap();
+
+        emit!(DistributorRootSet { id, root, mint });
+
+        Ok(())
+    }
+
+    /// Replace a distributor's merkle root (wallets that already claimed stay claimed)
+    pub fn set_distributor_root(ctx: Context<UpdateDistributor>, root: [u8; 32]) -> Result<()> {
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.root = root;
+
+        emit!(DistributorRootSet {
+            id: distributor.id,
+            root,
+            mint: distributor.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: credit a wallet's points from a points distributor given a merkle proof
+    pub fn claim_distribution(
+        ctx: Context<ClaimDistribution>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let distributor = &mut ctx.accounts.distributor;
+        require!(
+            distributor.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        distributor.verify_and_record(&ctx.accounts.wallet.key(), amount, &proof)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.add_points(amount, &ctx.accounts.config);
+
+        let claim = &mut ctx.accounts.claim_status;
+        claim.claimed_at = Clock::get()?.unix_timestamp;
+        claim.amount = amount;
+
+        emit!(DistributionClaimed {
+            id: distributor.id,
+            agent_id: wallet.agent_id.clone(),
+            mint: None,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay a wallet's token allocation from a token distributor given a merkle proof
+    pub fn claim_token_distribution(
+        ctx: Context<ClaimTokenDistribution>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let distributor = &mut ctx.accounts.distributor;
+        let mint = distributor
+            .mint
+            .ok_or(ClawWalletError::WrongTransactionKind)?;
+        require!(
+            ctx.accounts.vault_token_account.mint == mint,
+            ClawWalletError::MintMismatch
+        );
+        distributor.verify_and_record(&ctx.accounts.wallet.key(), amount, &proof)?;
+
+        let id_bytes = distributor.id.to_le_bytes();
+        let seeds = &[
+            b"distributor".as_ref(),
+            id_bytes.as_ref(),
+            &[distributor.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.wallet_token_account.to_account_info(),
+                authority: distributor.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let claim = &mut ctx.accounts.claim_status;
+        claim.claimed_at = Clock::get()?.unix_timestamp;
+        claim.amount = amount;
+
+        emit!(DistributionClaimed {
+            id: distributor.id,
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            mint: Some(mint),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule a time-boxed points multiplier (multiplier_bps 20_000 = 2x; mint = None covers every send)
+    pub fn create_points_campaign(
+        ctx: Context<CreatePointsCampaign>,
+        start_at: i64,
+        end_at: i64,
+        mint: Option<Pubkey>,
+        multiplier_bps: u16,
+    ) -> Result<()> {
+        require!(end_at > start_at, ClawWalletError::InvalidCampaignWindow);
+        require!(multiplier_bps > 0, ClawWalletError::InvalidAmount);
+
+        let config = &mut ctx.accounts.config;
+        let id = config.points_campaign_nonce;
+        config.points_campaign_nonce += 1;
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.id = id;
+        campaign.start_at = start_at;
+        campaign.end_at = end_at;
+        campaign.mint = mint;
+        campaign.multiplier_bps = multiplier_bps;
+        campaign.bump = *ctx.bumps.get("campaign").unwrap();
+
+        emit!(PointsCampaignCreated {
+            id,
+            start_at,
+            end_at,
+            mint,
+            multiplier_bps,
+        });
+
+        Ok(())
+    }
+
+    /// End a points campaign early (or clean up a finished one), returning its rent to the admin
+    pub fn close_points_campaign(ctx: Context<ClosePointsCampaign>) -> Result<()> {
+        emit!(PointsCampaignClosed {
+            id: ctx.accounts.campaign.id,
+        });
+
+        Ok(())
+    }
+
+    /// Set anti-farming rules for send points: minimum amounts (lamports / raw token units),
+    /// a per-day earn cap (0 = none) and the share of points paid for repeat recipients (bps)
+    pub fn set_earning_rules(
+        ctx: Context<AdminConfig>,
+        min_earn_lamports: u64,
+        min_earn_token: u64,
+        daily_points_cap: u64,
+        repeat_points_bps: u16,
+    ) -> Result<()> {
+        require!(repeat_points_bps <= 10_000, ClawWalletError::InvalidAmount);
+        let config = &mut ctx.accounts.config;
+        config.min_earn_lamports = min_earn_lamports;
+        config.min_earn_token = min_earn_token;
+        config.daily_points_cap = daily_points_cap;
+        config.repeat_points_bps = repeat_points_bps;
+
+        emit!(EarningRulesSet {
+            admin: ctx.accounts.admin.key(),
+            min_earn_lamports,
+            min_earn_token,
+            daily_points_cap,
+            repeat_points_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: define a quest (["quest", id]) completed by sends before `end_at`
+    pub fn create_quest(
+        ctx: Context<CreateQuest>,
+        kind: u8,
+        mint: Option<Pubkey>,
+        target: u64,
+        reward_points: u64,
+        end_at: i64,
+    ) -> Result<()> {
+        require!(kind <= QUEST_TOKEN_VOLUME, ClawWalletError::InvalidQuest);
+        require!(target > 0, ClawWalletError::InvalidQuest);
+        require!(
+            kind != QUEST_DISTINCT_AGENTS || target <= MAX_QUEST_AGENTS as u64,
+            ClawWalletError::InvalidQuest
+        );
+        require!(
+            kind != QUEST_TOKEN_VOLUME || mint.is_some(),
+            ClawWalletError::InvalidQuest
+        );
+        require!(
+            end_at > Clock::get()?.unix_timestamp,
+            ClawWalletError::InvalidCampaignWindow
+        );
+
+        let config = &mut ctx.accounts.config;
+        let id = config.quest_nonce;
+        config.quest_nonce += 1;
+
+        let quest = &mut ctx.accounts.quest;
+        quest.id = id;
+        quest.kind = kind;
+        quest.mint = mint;
+        quest.target = target;
+        quest.reward_points = reward_points;
+        quest.end_at = end_at;
+        quest.bump = *ctx.bumps.get("quest").unwrap();
+
+        emit!(QuestCreated {
+            id,
+            kind,
+            mint,
+            target,
+            reward_points,
+            end_at,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: remove a quest, returning its rent to the admin
+    pub fn close_quest(ctx: Context<CloseQuest>) -> Result<()> {
+        emit!(QuestClosed {
+            id: ctx.accounts.quest.id,
+        });
+
+        Ok(())
+    }
+
+    /// Enroll a wallet in a quest by creating its progress account
+    pub fn start_quest(ctx: Context<StartQuest>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.quest.end_at,
+            ClawWalletError::CampaignNotActive
+        );
+
+        let progress = &mut ctx.accounts.progress;
+        progress.quest = ctx.accounts.quest.key();
+        progress.wallet = ctx.accounts.wallet.key();
+        progress.progress = 0;
+        progress.agents = Vec::new();
+        progress.completed_at = 0;
+        progress.claimed = false;
+        progress.bump = *ctx.bumps.get("progress").unwrap();
+
+        emit!(QuestStarted {
+            id: ctx.accounts.quest.id,
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Credit the reward points of a completed quest
+    pub fn claim_quest_reward(ctx: Context<ClaimQuestReward>) -> Result<()> {
+        let progress = &mut ctx.accounts.progress;
+        require!(
+            progress.completed_at != 0,
+            ClawWalletError::QuestNotCompleted
+        );
+        require!(!progress.claimed, ClawWalletError::RewardAlreadyClaimed);
+        progress.claimed = true;
+
+        let reward_points = ctx.accounts.quest.reward_points;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.add_points(reward_points, &ctx.accounts.config);
+
+        emit!(QuestRewardClaimed {
+            id: ctx.accounts.quest.id,
+            agent_id: wallet.agent_id.clone(),
+            points: reward_points,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: close the current points season; wallets snapshot and reset their points lazily, and the
+    /// leaderboard is cleared of the previous season's entries
+    pub fn roll_season(ctx: Context<RollSeason>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(leaderboard) = &ctx.accounts.leaderboard {
+            leaderboard.load_mut()?.clear();
+        }
+        let config = &mut ctx.accounts.config;
+        config.season = config
+            .season
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        config.season_started_at = now;
+
+        emit!(SeasonRolled {
+            admin: ctx.accounts.admin.key(),
+            season: config.season,
+            started_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set the points formulas for SOL sends, token sends and per-mint overrides (admin only)
+    pub fn set_points_formulas(
+        ctx: Context<AdminConfig>,
+        sol_points: PointsFormula,
+        token_points: PointsFormula,
+        mint_points: Vec<MintPointsFormula>,
+    ) -> Result<()> {
+        require!(
+            mint_points.len() <= MAX_MINT_POINTS_FORMULAS,
+            ClawWalletError::TooManyPointsFormulas
+        );
+        sol_points.validate()?;
+        token_points.validate()?;
+        for (i, entry) in mint_points.iter().enumerate() {
+            entry.formula.validate()?;
+            require!(
+                !mint_points[..i].iter().any(|e| e.mint == entry.mint),
+                ClawWalletError::InvalidPointsFormula
+            );
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.sol_points = sol_points.clone();
+        config.token_points = token_points.clone();
+        config.mint_points = mint_points.clone();
+
+        emit!(PointsFormulasSet {
+            admin: ctx.accounts.admin.key(),
+            sol_points,
+            token_points,
+            mint_points,
+        });
+
+        Ok(())
+    }
+
+    /// Configure Pyth feeds for USD-value points (admin only); `sol_price_feed` default = lamport-based points.
+    /// USD points apply to send_sol, send_to_agent and send_token; other paths award raw-amount points
+    pub fn set_price_feeds(
+        ctx: Context<AdminConfig>,
+        sol_price_feed: Pubkey,
+        mint_price_feeds: Vec<MintPriceFeed>,
+        usd_points: PointsFormula,
+        max_price_age: u64,
+    ) -> Result<()> {
+        require!(
+            mint_price_feeds.len() <= MAX_PRICE_FEEDS,
+            ClawWalletError::TooManyPriceFeeds
+        );
+        require!(max_price_age > 0, ClawWalletError::InvalidAmount);
+        usd_points.validate()?;
+        for (i, entry) in mint_price_feeds.iter().enumerate() {
+            require!(
+                !mint_price_feeds[..i].iter().any(|e| e.mint == entry.mint),
+                ClawWalletError::InvalidPriceFeed
+            );
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.sol_price_feed = sol_price_feed;
+        config.mint_price_feeds = mint_price_feeds.clone();
+        config.usd_points = usd_points.clone();
+        config.max_price_age = max_price_age;
+
+        emit!(PriceFeedsSet {
+            admin: ctx.accounts.admin.key(),
+            sol_price_feed,
+            mint_price_feeds,
+            usd_points,
+            max_price_age,
+        });
+
+        Ok(())
+    }
+
+    /// Send SOL to several recipients (remaining_accounts, in order of `amounts`) with one aggregate fee.
+    /// Recipients receive their full amounts; the fee is charged on top. Any trailing accounts are references
+    pub fn send_sol_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendSolBatch<'info>>,
+        amounts: Vec<u64>,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts.wallet.use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        require!(
+            !amounts.is_empty()
+                && amounts.len() <= MAX_BATCH_RECIPIENTS
+                && amounts.len() <= ctx.remaining_accounts.len(),
+            ClawWalletError::InvalidBatch
+        );
+        let (recipient_infos, reference_infos) = ctx.remaining_accounts.split_at(amounts.len());
+        let references = collect_references(reference_infos)?;
+        require!(
+            recipient_infos.iter().all(|r| r.is_writable),
+            ClawWalletError::InvalidBatch
+        );
+        require!(
+            amounts.iter().all(|a| *a > 0),
+            ClawWalletError::InvalidAmount
+        );
+        let total = amounts
+            .iter()
+            .try_fold(0u64, |sum, a| sum.checked_add(*a))
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let recipients: Vec<Pubkey> = recipient_infos.iter().map(|r| r.key()).collect();
+
+        let now = Clock::get()?.unix_timestamp;
+        // Recipient exemptions don't apply: the fee covers the whole batch
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key()],
+            None,
+            total,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let fee = apply_fee_discount(&mut ctx.accounts.wallet, &ctx.accounts.config, fee);
+        let outflow = total
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, outflow)?;
+
+        // Authorize signer (owner, agent key, or session key)
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            outflow,
+            now,
+        )?;
+
+        ctx.accounts.wallet.record_outflow(outflow, now)?;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &recipients,
+        )?;
+
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                outflow,
+                false,
+            )?;
+            enforce_allowlist_each(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &recipients,
+                1,
+            )?;
+        }
+
+        for (recipient_info, amount) in recipient_infos.iter().zip(amounts.iter()) {
+            transfer_lamports(&wallet_info, recipient_info, *amount)?;
+        }
+
+        // Split the fee between the referrer (if any) and the treasury
+        let referral_fee = referral_cut(
+            &ctx.accounts.wallet,
+            ctx.accounts.referrer.as_ref().map(|r| r.key()),
+            &ctx.accounts.config,
+            fee,
+        )?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.treasury.to_account_info(),
+            fee - referral_fee,
+        )?;
+        if referral_fee > 0 {
+            let referrer = ctx.accounts.referrer.as_mut().unwrap();
+            transfer_lamports(&wallet_info, &referrer.to_account_info(), referral_fee)?;
+            referrer.referral_earnings = referrer
+                .referral_earnings
+                .checked_add(referral_fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            emit!(ReferralFeeSplit {
+                referrer: referrer.key(),
+                wallet: wallet_info.key(),
+                mint: None,
+                amount: referral_fee,
+            });
+        }
+
+        record_referred_send(&ctx.accounts.wallet, &mut ctx.accounts.referral_code, total)?;
+
+        // Stats are updated once for the whole batch; points still follow the per-recipient earning rules
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(category, total)?;
+        let config = &ctx.accounts.config;
+        let mut points_earned: u64 = 0;
+        for (recipient, amount) in recipients.iter().zip(amounts.iter()) {
+            let base_points = config.sol_points_for(*amount);
+            points_earned += wallet.earn_points(
+                base_points,
+                *amount,
+                config.min_earn_lamports,
+                *recipient,
+                now,
+                config,
+            );
+        }
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(SolBatchSent {
+            agent_id: wallet.agent_id.clone(),
+            recipients,
+            amounts,
+            fee,
+            points_earned,
+            memo,
+            category,
+            references,
+        });
+
+        Ok(())
+    }
+
+    /// Send one SPL Token or Token-2022 token to several recipient token accounts (remaining_accounts, in
+    /// order of `amounts`) with a single fee transfer at the end. The fee is charged on top; a Token-2022
+    /// transfer-fee mint still withholds its own fee from each leg. Transfer-hook accounts (if the mint has
+    /// a hook) come before the recipients, and any trailing accounts are references
+    pub fn send_token_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendTokenBatch<'info>>,
+        amounts: Vec<u64>,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts.wallet.use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        require!(
+            !amounts.is_empty() && amounts.len() <= MAX_BATCH_RECIPIENTS,
+            ClawWalletError::InvalidBatch
+        );
+        let (hook_accounts, batch_infos) =
+            transfer_hook_accounts(&ctx.accounts.mint.to_account_info(), ctx.remaining_accounts)?;
+        require!(
+            amounts.len() <= batch_infos.len(),
+            ClawWalletError::InvalidBatch
+        );
+        let (recipient_infos, reference_infos) = batch_infos.split_at(amounts.len());
+        let references = collect_references(reference_infos)?;
+        require!(
+            amounts.iter().all(|a| *a > 0),
+            ClawWalletError::InvalidAmount
+        );
+        let total = amounts
+            .iter()
+            .try_fold(0u64, |sum, a| sum.checked_add(*a))
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let mint = ctx.accounts.mint.key();
+        let mut recipient_owners: Vec<Pubkey> = Vec::with_capacity(recipient_infos.len());
+        let mut parties: Vec<Pubkey> = Vec::with_capacity(recipient_infos.len() * 2);
+        for info in recipient_infos.iter() {
+            require!(info.is_writable, ClawWalletError::InvalidBatch);
+            let token_account = InterfaceAccount::<InterfaceTokenAccount>::try_from(info)?;
+            require!(token_account.mint == mint, ClawWalletError::MintMismatch);
+            recipient_owners.push(token_account.owner);
+            parties.push(info.key());
+            parties.push(token_account.owner);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let mint_fee = load_mint_fee(&ctx.accounts.mint_fee.to_account_info())?;
+        // Recipient exemptions don't apply: the fee covers the whole batch
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key()],
+            Some(mint),
+            total,
+            token_fee_bps(config, &mint_fee, ctx.accounts.wallet.points),
+            now,
+        )?;
+        let fee = mint_fee.map_or(fee, |m| apply_fee_cap(fee, m.max_fee));
+        let fee = apply_fee_discount(&mut ctx.accounts.wallet, &ctx.accounts.config, fee);
+        let outflow = total
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        // Authorize signer (owner, agent key, or session key)
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            outflow,
+            now,
+        )?;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &parties,
+        )?;
+
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                outflow,
+                now,
+                false,
+            )?;
+            // `parties` holds each recipient's (token account, owner) pair
+            enforce_allowlist_each(&ctx.accounts.wallet, &ctx.accounts.allowlist, &parties, 2)?;
+        }
+
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mut transfer_fee: u64 = 0;
+        for (recipient_info, amount) in recipient_infos.iter().zip(amounts.iter()) {
+            transfer_checked_from_wallet(
+                &ctx.accounts.token_program,
+                &ctx.accounts.wallet_token_account,
+                &ctx.accounts.mint,
+                recipient_info.clone(),
+                &ctx.accounts.wallet,
+                hook_accounts,
+                *amount,
+            )?;
+            transfer_fee = transfer_fee
+                .checked_add(mint_transfer_fee(&mint_info, *amount)?)
+                .ok_or(ClawWalletError::MathOverflow)?;
+        }
+
+        // Split the fee between the referrer (if any) and the treasury
+        let referral_fee = referral_cut(
+            &ctx.accounts.wallet,
+            ctx.accounts
+                .referrer_token_account
+                .as_ref()
+                .map(|t| t.owner),
+            &ctx.accounts.config,
+            fee,
+        )?;
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            fee - referral_fee,
+        )?;
+
+        if referral_fee > 0 {
+            let referrer_token_account = ctx.accounts.referrer_token_account.as_ref().unwrap();
+            transfer_checked_from_wallet(
+                &ctx.accounts.token_program,
+                &ctx.accounts.wallet_token_account,
+                &ctx.accounts.mint,
+                referrer_token_account.to_account_info(),
+                &ctx.accounts.wallet,
+                hook_accounts,
+                referral_fee,
+            )?;
+
+            emit!(ReferralFeeSplit {
+                referrer: referrer_token_account.owner,
+                wallet: ctx.accounts.wallet.key(),
+                mint: Some(mint),
+                amount: referral_fee,
+            });
+        }
+
+        record_referred_send(&ctx.accounts.wallet, &mut ctx.accounts.referral_code, 0)?;
+
+        // Stats are updated once for the whole batch; points still follow the per-recipient earning rules
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(category, 0)?;
+        let config = &ctx.accounts.config;
+        let mut points_earned: u64 = 0;
+        for (owner, amount) in recipient_owners.iter().zip(amounts.iter()) {
+            let base_points = config.token_points_for(&mint, *amount);
+            points_earned += wallet.earn_points(
+                base_points,
+                *amount,
+                config.min_earn_token,
+                *owner,
+                now,
+                config,
+            );
+        }
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(TokenBatchSent {
+            agent_id: wallet.agent_id.clone(),
+            mint,
+            recipients: recipient_infos.iter().map(|r| r.key()).collect(),
+            amounts,
+            fee,
+            points_earned,
+            memo,
+            category,
+            references,
+            transfer_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Atomically send SOL and several tokens of `token_program` (SPL Token or Token-2022) to one recipient. Each
+    /// token leg is five remaining_accounts, [mint, wallet token account, recipient token account, treasury token
+    /// account, mint fee PDA], followed by the mint's transfer-hook accounts if it has a hook. Each asset's fee is
+    /// charged on top; Token-2022 transfer-fee mints still withhold their own. Any trailing accounts are references.
+    /// Exemptions, campaigns, discounts and quests apply as for single sends; the referral share is taken from the
+    /// SOL leg's fee
+    pub fn send_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendMulti<'info>>,
+        sol_amount: u64,
+        token_amounts: Vec<u64>,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        ctx.accounts.wallet.use_idempotency_key(idempotency_key)?;
+        forward_memo(&ctx.accounts.memo_program, &memo)?;
+        require!(
+            token_amounts.len() <= MAX_MULTI_TOKENS,
+            ClawWalletError::InvalidBatch
+        );
+        require!(
+            sol_amount > 0 || !token_amounts.is_empty(),
+            ClawWalletError::InvalidAmount
+        );
+        require!(
+            token_amounts.iter().all(|a| *a > 0),
+            ClawWalletError::InvalidAmount
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let recipient = ctx.accounts.recipient.key();
+        let wallet_key = ctx.accounts.wallet.key();
+        let points = ctx.accounts.wallet.points;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        let exempt = apply_fee_exemption(
+            &ctx.accounts.fee_exemption,
+            wallet_key,
+            &[wallet_key, recipient],
+            sol_amount,
+        )?;
+        // One discount credit covers every leg of the settlement
+        let discount = !exempt && ctx.accounts.wallet.fee_discount_credits > 0;
+        if discount {
+            ctx.accounts.wallet.fee_discount_credits -= 1;
+        }
+
+        let agent_id = ctx.accounts.wallet.agent_id.clone();
+        let mut base_points: u64 = 0;
+        let mut mints: Vec<Pubkey> = Vec::with_capacity(token_amounts.len());
+        let mut token_fees: Vec<u64> = Vec::with_capacity(token_amounts.len());
+        let mut transfer_fees: Vec<u64> = Vec::with_capacity(token_amounts.len());
+        let mut rest = ctx.remaining_accounts;
+
+        for amount in token_amounts.iter() {
+            let amount = *amount;
+            require!(rest.len() >= 5, ClawWalletError::InvalidBatch);
+            let (leg, after) = rest.split_at(5);
+            let (hook_accounts, after) = transfer_hook_accounts(&leg[0], after)?;
+            rest = after;
+
+            let mint_account = InterfaceAccount::<InterfaceMint>::try_from(&leg[0])?;
+            let wallet_token_account =
+                InterfaceAccount::<InterfaceTokenAccount>::try_from(&leg[1])?;
+            let recipient_token_account =
+                InterfaceAccount::<InterfaceTokenAccount>::try_from(&leg[2])?;
+            let mint = mint_account.key();
+            require!(!mints.contains(&mint), ClawWalletError::InvalidBatch);
+            require!(
+                wallet_token_account.owner == wallet_key,
+                ClawWalletError::InvalidBatch
+            );
+            require!(
+                wallet_token_account.mint == mint,
+                ClawWalletError::MintMismatch
+            );
+            require!(
+                recipient_token_account.owner == recipient,
+                ClawWalletError::InvalidBatch
+            );
+            require!(
+                recipient_token_account.mint == mint,
+                ClawWalletError::MintMismatch
+            );
+            require!(
+                leg[3].key()
+                    == get_associated_token_address_with_program_id(
+                        &ctx.accounts.config.fee_recipient(),
+                        &mint,
+                        &ctx.accounts.token_program.key()
+                    ),
+                ClawWalletError::InvalidTreasury
+            );
+            let (mint_fee_key, _) =
+                Pubkey::find_program_address(&[b"mint_fee", mint.as_ref()], &crate::ID);
+            require!(leg[4].key() == mint_fee_key, ClawWalletError::InvalidBatch);
+
+            let mint_fee = load_mint_fee(&leg[4])?;
+            let config = &ctx.accounts.config;
+            let bps = leg_fee_bps(
+                &ctx.accounts.campaign,
+                token_fee_bps(config, &mint_fee, points),
+                Some(mint),
+                now,
+            );
+            let fee = if exempt {
+                0
+            } else {
+                config.token_fee_at(amount, bps)
+            };
+            let fee = mint_fee.map_or(fee, |m| apply_fee_cap(fee, m.max_fee));
+            let fee = if discount {
+                config.discounted_fee(fee)
+            } else {
+                fee
+            };
+            let outflow = amount
+                .checked_add(fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+
+            let role = authorize_sender(
+                &ctx.accounts.wallet,
+                &ctx.accounts.authority.key(),
+                &mut ctx.accounts.session,
+                PERM_SEND_TOKEN,
+                outflow,
+                now,
+            )?;
+            if role != SenderRole::Owner {
+                enforce_token_policy(
+                    &ctx.accounts.wallet,
+                    &mut ctx.accounts.spend_policy,
+                    &mint,
+                    outflow,
+                    now,
+                    false,
+                )?;
+                enforce_allowlist(
+                    &ctx.accounts.wallet,
+                    &ctx.accounts.allowlist,
+                    &[leg[2].key(), recipient],
+                )?;
+            }
+
+            transfer_checked_from_wallet(
+                &ctx.accounts.token_program,
+                &wallet_token_account,
+                &mint_account,
+                leg[2].clone(),
+                &ctx.accounts.wallet,
+                hook_accounts,
+                amount,
+            )?;
+            transfer_checked_from_wallet(
+                &ctx.accounts.token_program,
+                &wallet_token_account,
+                &mint_account,
+                leg[3].clone(),
+                &ctx.accounts.wallet,
+                hook_accounts,
+                fee,
+            )?;
+
+            if amount >= ctx.accounts.config.min_earn_token {
+                let leg_points = ctx.accounts.config.token_points_for(&mint, amount);
+                base_points += leg_campaign_points(
+                    &ctx.accounts.points_campaign,
+                    leg_points,
+                    Some(mint),
+                    now,
+                    &agent_id,
+                )?;
+            }
+            mints.push(mint);
+            token_fees.push(fee);
+            transfer_fees.push(mint_transfer_fee(&leg[0], amount)?);
+        }
+        let references = collect_references(rest)?;
+
+        let mut sol_fee: u64 = 0;
+        if sol_amount > 0 {
+            let config = &ctx.accounts.config;
+            let bps = leg_fee_bps(
+                &ctx.accounts.campaign,
+                config.fee_bps_for(points),
+                None,
+                now,
+            );
+            sol_fee = if exempt {
+                0
+            } else {
+                config.sol_fee_at(sol_amount, bps)
+            };
+            if discount {
+                sol_fee = config.discounted_fee(sol_fee);
+            }
+            let outflow = sol_amount
+                .checked_add(sol_fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+            let wallet_info = ctx.accounts.wallet.to_account_info();
+            require_spendable(&wallet_info, outflow)?;
+
+            let role = authorize_sender(
+                &ctx.accounts.wallet,
+                &ctx.accounts.authority.key(),
+                &mut ctx.accounts.session,
+                PERM_SEND_SOL,
+                outflow,
+                now,
+            )?;
+            ctx.accounts.wallet.record_outflow(outflow, now)?;
+            if role != SenderRole::Owner {
+                enforce_sol_policy(
+                    &ctx.accounts.wallet,
+                    &ctx.accounts.spend_policy,
+                    outflow,
+                    false,
+                )?;
+                enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[recipient])?;
+            }
+
+            transfer_lamports(
+                &wallet_info,
+                &ctx.accounts.recipient.to_account_info(),
+                sol_amount,
+            )?;
+            // The referral share comes out of the SOL leg's fee; token legs' fees go to the treasury whole
+            let referral_fee = referral_cut(
+                &ctx.accounts.wallet,
+                ctx.accounts.referrer.as_ref().map(|r| r.key()),
+                &ctx.accounts.config,
+                sol_fee,
+            )?;
+            transfer_lamports(
+                &wallet_info,
+                &ctx.accounts.treasury.to_account_info(),
+                sol_fee - referral_fee,
+            )?;
+            if referral_fee > 0 {
+                let referrer = ctx.accounts.referrer.as_mut().unwrap();
+                transfer_lamports(&wallet_info, &referrer.to_account_info(), referral_fee)?;
+                referrer.referral_earnings = referrer
+                    .referral_earnings
+                    .checked_add(referral_fee)
+                    .ok_or(ClawWalletError::MathOverflow)?;
+
+                emit!(ReferralFeeSplit {
+                    referrer: referrer.key(),
+                    wallet: wallet_key,
+                    mint: None,
+                    amount: referral_fee,
+                });
+            }
+
+            if sol_amount >= ctx.accounts.config.min_earn_lamports {
+                let leg_points = ctx.accounts.config.sol_points_for(sol_amount);
+                base_points += leg_campaign_points(
+                    &ctx.accounts.points_campaign,
+                    leg_points,
+                    None,
+                    now,
+                    &agent_id,
+                )?;
+            }
+        }
+        record_referred_send(
+            &ctx.accounts.wallet,
+            &mut ctx.accounts.referral_code,
+            sol_amount,
+        )?;
+
+        // One settlement counts as one transaction; per-asset minimums were applied above
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(category, sol_amount)?;
+        let points_earned =
+            wallet.earn_points(base_points, 0, 0, recipient, now, &ctx.accounts.config);
+        // A quest advances once for the settlement, by the leg in its asset when there is one
+        let (quest_mint, quest_amount) = match ctx.accounts.quest.as_ref().and_then(|q| q.mint) {
+            Some(quest_mint) => match mints.iter().position(|m| *m == quest_mint) {
+                Some(i) => (Some(quest_mint), token_amounts[i]),
+                None => (None, sol_amount),
+            },
+            None => (None, sol_amount),
+        };
+        advance_quest(
+            &ctx.accounts.quest,
+            &mut ctx.accounts.quest_progress,
+            wallet,
+            quest_mint,
+            quest_amount,
+            None,
+            now,
+        )?;
+        update_leaderboard(&ctx.accounts.leaderboard, wallet.key(), wallet.points)?;
+
+        emit!(MultiSent {
+            agent_id: wallet.agent_id.clone(),
+            recipient,
+            sol_amount,
+            sol_fee,
+            mints,
+            token_amounts,
+            token_fees,
+            points_earned,
+            memo,
+            category,
+            references,
+            transfer_fees,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: schedule a payment that anyone can execute once `execute_after` has passed
+    pub fn schedule_payment(
+        ctx: Context<SchedulePayment>,
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        execute_after: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.schedule_nonce;
+        wallet.schedule_nonce += 1;
+        wallet.open_object(OPEN_SCHEDULED)?;
+
+        let scheduled = &mut ctx.accounts.scheduled_payment;
+        scheduled.wallet = wallet.key();
+        scheduled.nonce = nonce;
+        scheduled.payer = ctx.accounts.owner.key();
+        scheduled.recipient = recipient;
+        scheduled.mint = mint;
+        scheduled.amount = amount;
+        scheduled.created_at = Clock::get()?.unix_timestamp;
+        scheduled.execute_after = execute_after;
+        scheduled.bump = *ctx.bumps.get("scheduled_payment").unwrap();
+
+        emit!(PaymentScheduled {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint,
+            amount,
+            execute_after,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: execute a due scheduled SOL payment (protocol fee)
+    pub fn execute_scheduled_payment(ctx: Context<ExecuteScheduledPayment>) -> Result<()> {
+        let scheduled = &ctx.accounts.scheduled_payment;
+        require!(
+            scheduled.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= scheduled.execute_after,
+            ClawWalletError::PaymentNotDue
+        );
+
+        let amount = scheduled.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), scheduled.recipient],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[scheduled.recipient],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+        pay_keeper_tip(
+            &wallet_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(ScheduledPaymentExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: scheduled.nonce,
+            recipient: scheduled.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+        ctx.accounts.wallet.close_object(OPEN_SCHEDULED);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: execute a due scheduled SPL token payment (protocol fee)
+    pub fn execute_scheduled_token_payment(
+        ctx: Context<ExecuteScheduledTokenPayment>,
+    ) -> Result<()> {
+        let scheduled = &ctx.accounts.scheduled_payment;
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            scheduled.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= scheduled.execute_after,
+            ClawWalletError::PaymentNotDue
+        );
+
+        let amount = scheduled.amount;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), scheduled.recipient],
+            Some(mint),
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                scheduled.recipient,
+                ctx.accounts.recipient_token_account.owner,
+            ],
+        )?;
+
+        let wallet = &ctx.accounts.wallet;
+        let bump = wallet.bump;
+        let agent_id = wallet.agent_id.clone();
+        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, send_amount)?;
+
+        let cpi_ctx_fee = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx_fee, fee)?;
+        pay_keeper_tip(
+            &ctx.accounts.wallet.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.token_points_for(&mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        emit!(ScheduledPaymentExecuted {
+            agent_id: wallet.agent_id.clone(),
+            nonce: scheduled.nonce,
+            recipient: scheduled.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            points_earned,
+        });
+        ctx.accounts.wallet.close_object(OPEN_SCHEDULED);
+
+        Ok(())
+    }
+
+    /// Owner: cancel a scheduled payment before it executes
+    pub fn cancel_scheduled_payment(ctx: Context<CancelScheduledPayment>) -> Result<()> {
+        let scheduled = &ctx.accounts.scheduled_payment;
+
+        emit!(ScheduledPaymentCancelled {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: scheduled.nonce,
+            recipient: scheduled.recipient,
+            amount: scheduled.amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_SCHEDULED);
+
+        Ok(())
+    }
+
+    /// Set the tip paid by wallets to whoever executes their cranks, and the share of reclaimed
+    /// rent paid to whoever runs an expiry cleanup (admin only)
+    pub fn set_keeper_incentives(
+        ctx: Context<AdminConfig>,
+        keeper_tip: u64,
+        cleanup_bounty_bps: u16,
+    ) -> Result<()> {
+        require!(keeper_tip <= MAX_KEEPER_TIP, ClawWalletError::InvalidAmount);
+        require!(
+            cleanup_bounty_bps <= MAX_CLEANUP_BOUNTY_BPS,
+            ClawWalletError::InvalidAmount
+        );
+        let config = &mut ctx.accounts.config;
+        config.keeper_tip = keeper_tip;
+        config.cleanup_bounty_bps = cleanup_bounty_bps;
+
+        emit!(KeeperIncentivesSet {
+            admin: ctx.accounts.admin.key(),
+            keeper_tip,
+            cleanup_bounty_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: pre-approve a recurring charge of `amount` every `interval` seconds by a registered
+    /// merchant, up to `max_total` over the subscription's life
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        mint: Option<Pubkey>,
+        amount: u64,
+        interval: i64,
+        first_due: i64,
+        max_total: u64,
+        grace_period: i64,
+    ) -> Result<()> {
+        require!(
+            amount > 0 && max_total >= amount,
+            ClawWalletError::InvalidAmount
+        );
+        require!(interval > 0, ClawWalletError::InvalidSubscription);
+        require!(
+            (0..=MAX_GRACE_PERIOD).contains(&grace_period),
+            ClawWalletError::InvalidSubscription
+        );
+
+        ctx.accounts.wallet.open_object(OPEN_SUBSCRIPTION)?;
+
+        let merchant = ctx.accounts.merchant.key();
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.wallet = ctx.accounts.wallet.key();
+        subscription.merchant = merchant;
+        subscription.mint = mint;
+        subscription.amount = amount;
+        subscription.interval = interval;
+        subscription.next_due = first_due;
+        subscription.max_total = max_total;
+        subscription.total_charged = 0;
+        subscription.charges = 0;
+        subscription.created_at = Clock::get()?.unix_timestamp;
+        subscription.bump = *ctx.bumps.get("subscription").unwrap();
+        subscription.status = SUBSCRIPTION_ACTIVE;
+        subscription.grace_period = grace_period;
+        subscription.failed_since = 0;
+
+        emit!(SubscriptionCreated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            merchant,
+            mint,
+            amount,
+            interval,
+            next_due: first_due,
+            max_total,
+            grace_period,
+        });
+
+        Ok(())
+    }
+
+    /// Charge a due SOL subscription into the merchant's settlement account; callable by the
+    /// merchant or any keeper (protocol fee, or the merchant's terms)
+    pub fn charge_subscription(ctx: Context<ChargeSubscription>) -> Result<()> {
+        require!(
+            ctx.accounts.subscription.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        let min_balance = Rent::get()?.minimum_balance(wallet_info.data_len());
+        let funded =
+            wallet_info.lamports() >= ctx.accounts.subscription.amount.saturating_add(min_balance);
+        if !ctx
+            .accounts
+            .subscription
+            .chargeable(funded, now, &ctx.accounts.wallet.agent_id)?
+        {
+            return Ok(());
+        }
+        let amount = ctx.accounts.subscription.record_charge(now)?;
+        let config = &ctx.accounts.config;
+        let bps = ctx
+            .accounts
+            .merchant
+            .fee_bps_or(config.fee_bps_for(ctx.accounts.wallet.points));
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                ctx.accounts.merchant.key(),
+                ctx.accounts.settlement.key(),
+            ],
+            None,
+            amount,
+            bps,
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[ctx.accounts.merchant.key(), ctx.accounts.settlement.key()],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.settlement.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+        if ctx.accounts.keeper.key() != ctx.accounts.merchant.authority {
+            pay_keeper_tip(
+                &wallet_info,
+                &ctx.accounts.keeper.to_account_info(),
+                &ctx.accounts.config,
+            )?;
+        }
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let subscription = &ctx.accounts.subscription;
+        emit!(SubscriptionCharged {
+            agent_id: wallet.agent_id.clone(),
+            merchant: subscription.merchant,
+            mint: None,
+            amount: send_amount,
+            fee,
+            next_due: subscription.next_due,
+        });
+
+        Ok(())
+    }
+
+    /// Charge a due SPL token subscription into the merchant's settlement token account
+    pub fn charge_subscription_token(ctx: Context<ChargeSubscriptionToken>) -> Result<()> {
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            ctx.accounts.subscription.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let funded = ctx.accounts.wallet_token_account.amount >= ctx.accounts.subscription.amount;
+        if !ctx
+            .accounts
+            .subscription
+            .chargeable(funded, now, &ctx.accounts.wallet.agent_id)?
+        {
+            return Ok(());
+        }
+        let amount = ctx.accounts.subscription.record_charge(now)?;
+        let config = &ctx.accounts.config;
+        let bps = ctx
+            .accounts
+            .merchant
+            .fee_bps_or(config.fee_bps_for(ctx.accounts.wallet.points));
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                ctx.accounts.merchant.key(),
+                ctx.accounts.merchant_token_account.owner,
+            ],
+            Some(mint),
+            amount,
+            bps,
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                ctx.accounts.merchant.key(),
+                ctx.accounts.merchant_token_account.key(),
+                ctx.accounts.merchant_token_account.owner,
+            ],
+        )?;
+
+        let wallet = &ctx.accounts.wallet;
+        let bump = wallet.bump;
+        let agent_id = wallet.agent_id.clone();
+        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.merchant_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, send_amount)?;
+
+        let cpi_ctx_fee = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx_fee, fee)?;
+        if ctx.accounts.keeper.key() != ctx.accounts.merchant.authority {
+            pay_keeper_tip(
+                &ctx.accounts.wallet.to_account_info(),
+                &ctx.accounts.keeper.to_account_info(),
+                &ctx.accounts.config,
+            )?;
+        }
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.token_points_for(&mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let subscription = &ctx.accounts.subscription;
+        emit!(SubscriptionCharged {
+            agent_id: wallet.agent_id.clone(),
+            merchant: subscription.merchant,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            next_due: subscription.next_due,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: pause a subscription; charges are rejected until it is resumed
+    pub fn pause_subscription(ctx: Context<UpdateSubscription>) -> Result<()> {
+        let subscription = &mut ctx.accounts.subscription;
+        require!(
+            subscription.status == SUBSCRIPTION_ACTIVE,
+            ClawWalletError::SubscriptionInactive
+        );
+        subscription.set_status(SUBSCRIPTION_PAUSED, &ctx.accounts.wallet.agent_id);
+
+        Ok(())
+    }
+
+    /// Owner: resume a paused or lapsed subscription; the next charge is due no earlier than now
+    pub fn resume_subscription(ctx: Context<UpdateSubscription>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &mut ctx.accounts.subscription;
+        require!(
+            subscription.status == SUBSCRIPTION_PAUSED
+                || subscription.status == SUBSCRIPTION_LAPSED,
+            ClawWalletError::InvalidSubscription
+        );
+        subscription.next_due = std::cmp::max(subscription.next_due, now);
+        subscription.failed_since = 0;
+        subscription.set_status(SUBSCRIPTION_ACTIVE, &ctx.accounts.wallet.agent_id);
+
+        Ok(())
+    }
+
+    /// Owner: cancel a subscription, returning its rent
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        ctx.accounts
+            .subscription
+            .set_status(SUBSCRIPTION_CANCELLED, &ctx.accounts.wallet.agent_id);
+        ctx.accounts.wallet.close_object(OPEN_SUBSCRIPTION);
+
+        Ok(())
+    }
+
+    /// Register the signer as a merchant that subscriptions and mandates can pay
+    pub fn register_merchant(
+        ctx: Context<RegisterMerchant>,
+        name: String,
+        settlement: Pubkey,
+        settlement_token_account: Pubkey,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= 32,
+            ClawWalletError::InvalidMerchant
+        );
+
+        let merchant = &mut ctx.accounts.merchant;
+        merchant.authority = ctx.accounts.authority.key();
+        merchant.name = name.clone();
+        merchant.settlement = settlement;
+        merchant.settlement_token_account = settlement_token_account;
+        merchant.fee_bps = None;
+        merchant.active = true;
+        merchant.registered_at = Clock::get()?.unix_timestamp;
+        merchant.bump = *ctx.bumps.get("merchant").unwrap();
+
+        emit!(MerchantRegistered {
+            merchant: merchant.key(),
+            authority: merchant.authority,
+            name,
+            settlement,
+            settlement_token_account,
+        });
+
+        Ok(())
+    }
+
+    /// Merchant: change where charges settle
+    pub fn update_merchant(
+        ctx: Context<UpdateMerchant>,
+        settlement: Pubkey,
+        settlement_token_account: Pubkey,
+    ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+        merchant.settlement = settlement;
+        merchant.settlement_token_account = settlement_token_account;
+
+        emit!(MerchantUpdated {
+            merchant: merchant.key(),
+            settlement,
+            settlement_token_account,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: set a merchant's protocol fee terms (None = default schedule) and suspend or reinstate it
+    pub fn set_merchant_terms(
+        ctx: Context<SetMerchantTerms>,
+        fee_bps: Option<u16>,
+        active: bool,
+    ) -> Result<()> {
+        require!(
+            fee_bps.map_or(true, |bps| bps <= MAX_FEE_BPS),
+            ClawWalletError::FeeTooHigh
+        );
+        let merchant = &mut ctx.accounts.merchant;
+        merchant.fee_bps = fee_bps;
+        merchant.active = active;
+
+        emit!(MerchantTermsSet {
+            admin: ctx.accounts.admin.key(),
+            merchant: merchant.key(),
+            fee_bps,
+            active,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: authorize a registered merchant to pull up to `max_per_period` every `period` seconds
+    pub fn approve_mandate(
+        ctx: Context<ApproveMandate>,
+        mint: Option<Pubkey>,
+        max_per_period: u64,
+        period: i64,
+    ) -> Result<()> {
+        require!(max_per_period > 0, ClawWalletError::InvalidAmount);
+        require!(period > 0, ClawWalletError::InvalidMandate);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.wallet.open_object(OPEN_MANDATE)?;
+        let mandate = &mut ctx.accounts.mandate;
+        mandate.wallet = ctx.accounts.wallet.key();
+        mandate.merchant = ctx.accounts.merchant.key();
+        mandate.mint = mint;
+        mandate.max_per_period = max_per_period;
+        mandate.period = period;
+        mandate.period_start = now;
+        mandate.spent_in_period = 0;
+        mandate.total_charged = 0;
+        mandate.created_at = now;
+        mandate.bump = *ctx.bumps.get("mandate").unwrap();
+
+        emit!(MandateApproved {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            merchant: mandate.merchant,
+            mint,
+            max_per_period,
+            period,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: revoke a mandate, returning its rent
+    pub fn revoke_mandate(ctx: Context<RevokeMandate>) -> Result<()> {
+        emit!(MandateRevoked {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            merchant: ctx.accounts.mandate.merchant,
+        });
+        ctx.accounts.wallet.close_object(OPEN_MANDATE);
+
+        Ok(())
+    }
+
+    /// Merchant: pull SOL under a mandate into the settlement account (protocol fee, or the merchant's terms)
+    pub fn charge_mandate(ctx: Context<ChargeMandate>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.mandate.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.mandate.record_charge(amount, now)?;
+
+        let config = &ctx.accounts.config;
+        let bps = ctx
+            .accounts
+            .merchant
+            .fee_bps_or(config.fee_bps_for(ctx.accounts.wallet.points));
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                ctx.accounts.merchant.key(),
+                ctx.accounts.settlement.key(),
+            ],
+            None,
+            amount,
+            bps,
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[ctx.accounts.merchant.key(), ctx.accounts.settlement.key()],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.settlement.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let mandate = &ctx.accounts.mandate;
+        emit!(MandateCharged {
+            agent_id: wallet.agent_id.clone(),
+            merchant: mandate.merchant,
+            mint: None,
+            amount: send_amount,
+            fee,
+            spent_in_period: mandate.spent_in_period,
+        });
+
+        Ok(())
+    }
+
+    /// Merchant: pull SPL tokens under a mandate into the settlement token account
+    pub fn charge_mandate_token(ctx: Context<ChargeMandateToken>, amount: u64) -> Result<()> {
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            ctx.accounts.mandate.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.mandate.record_charge(amount, now)?;
+
+        let config = &ctx.accounts.config;
+        let bps = ctx
+            .accounts
+            .merchant
+            .fee_bps_or(config.fee_bps_for(ctx.accounts.wallet.points));
+        let fee = protocol_fee(
+            config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[
+                ctx.accounts.wallet.key(),
+                ctx.accounts.merchant.key(),
+                ctx.accounts.merchant_token_account.owner,
+            ],
+            Some(mint),
+            amount,
+            bps,
+            now,
+        )?;
+        let send_amount = amount - fee;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[
+                ctx.accounts.merchant.key(),
+                ctx.accounts.merchant_token_account.key(),
+                ctx.accounts.merchant_token_account.owner,
+            ],
+        )?;
+
+        let wallet = &ctx.accounts.wallet;
+        let bump = wallet.bump;
+        let agent_id = wallet.agent_id.clone();
+        let seeds = &[b"wallet".as_ref(), agent_id.as_bytes(), &[bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.merchant_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, send_amount)?;
+
+        let cpi_ctx_fee = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.wallet_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx_fee, fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.token_points_for(&mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let mandate = &ctx.accounts.mandate;
+        emit!(MandateCharged {
+            agent_id: wallet.agent_id.clone(),
+            merchant: mandate.merchant,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            spent_in_period: mandate.spent_in_period,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: lock `deposit` lamports in a stream that accrues to `recipient` at `rate_per_second`
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        recipient: Pubkey,
+        deposit: u64,
+        rate_per_second: u64,
+        start_time: Option<i64>, // None = now
+        cliff_time: Option<i64>, // None = no cliff
+        transferable: bool,
+    ) -> Result<()> {
+        require!(deposit > 0, ClawWalletError::InvalidAmount);
+        require!(rate_per_second > 0, ClawWalletError::InvalidStream);
+        let now = Clock::get()?.unix_timestamp;
+        let start_time = start_time.unwrap_or(now);
+        require!(start_time >= now, ClawWalletError::InvalidStream);
+        let cliff_time = cliff_time.unwrap_or(0);
+        require!(
+            cliff_time == 0 || cliff_time >= start_time,
+            ClawWalletError::InvalidStream
+        );
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        ctx.accounts.wallet.record_outflow(deposit, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, deposit)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.stream.to_account_info(),
+            deposit,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.stream_nonce;
+        wallet.stream_nonce += 1;
+        wallet.open_object(OPEN_STREAM)?;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.wallet = wallet.key();
+        stream.nonce = nonce;
+        stream.recipient = recipient;
+        stream.deposit = deposit;
+        stream.withdrawn = 0;
+        stream.rate_per_second = rate_per_second;
+        stream.start_time = start_time;
+        stream.created_at = now;
+        stream.bump = *ctx.bumps.get("stream").unwrap();
+        stream.cliff_time = cliff_time;
+        stream.checkpoint_time = start_time;
+        stream.checkpoint_streamed = 0;
+        stream.transferable = transferable;
+
+        emit!(StreamCreated {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            deposit,
+            rate_per_second,
+            start_time,
+            cliff_time,
+            transferable,
+        });
+
+        Ok(())
+    }
+
+    /// Recipient: withdraw everything streamed so far (protocol fee at the sender's tier)
+    pub fn withdraw_from_stream(ctx: Context<WithdrawFromStream>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.stream.withdrawable(now);
+        require!(amount > 0, ClawWalletError::NothingToWithdraw);
+
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        let stream_info = ctx.accounts.stream.to_account_info();
+        transfer_lamports(
+            &stream_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&stream_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.withdrawn = stream
+            .withdrawn
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(StreamWithdrawn {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: stream.nonce,
+            recipient: stream.recipient,
+            amount: send_amount,
+            fee,
+            remaining: stream.deposit - stream.withdrawn,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: stop a stream, paying out what has accrued and returning the unstreamed remainder to the wallet
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let stream = &ctx.accounts.stream;
+        let accrued = stream.withdrawable(now);
+        let refund = stream.deposit - stream.streamed(now);
+
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(accrued, ctx.accounts.wallet.points);
+        let send_amount = accrued - fee;
+
+        let stream_info = ctx.accounts.stream.to_account_info();
+        transfer_lamports(
+            &stream_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&stream_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+        transfer_lamports(&stream_info, &ctx.accounts.wallet.to_account_info(), refund)?;
+
+        emit!(StreamCancelled {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.stream.nonce,
+            recipient: ctx.accounts.stream.recipient,
+            paid_out: send_amount,
+            fee,
+            refunded: refund,
+        });
+        ctx.accounts.wallet.close_object(OPEN_STREAM);
+
+        Ok(())
+    }
+
+    /// Owner: add lamports to a stream, extending how long it runs at the current rate
+    pub fn top_up_stream(ctx: Context<UpdateStream>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.stream.to_account_info(), amount)?;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.checkpoint(now);
+        stream.deposit = stream
+            .deposit
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(StreamToppedUp {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: stream.nonce,
+            amount,
+            deposit: stream.deposit,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: change a stream's rate; what has already accrued is unaffected
+    pub fn set_stream_rate(ctx: Context<UpdateStream>, rate_per_second: u64) -> Result<()> {
+        require!(rate_per_second > 0, ClawWalletError::InvalidStream);
+        let now = Clock::get()?.unix_timestamp;
+
+        let stream = &mut ctx.accounts.stream;
+        stream.checkpoint(now);
+        let old_rate = stream.rate_per_second;
+        stream.rate_per_second = rate_per_second;
+
+        emit!(StreamRateChanged {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: stream.nonce,
+            old_rate,
+            new_rate: rate_per_second,
+            effective_at: stream.checkpoint_time,
+        });
+
+        Ok(())
+    }
+
+    /// Recipient: hand the remaining flow of a transferable stream to another address
+    pub fn transfer_stream(ctx: Context<TransferStream>, new_recipient: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.stream.transferable,
+            ClawWalletError::StreamNotTransferable
+        );
+        require!(
+            new_recipient != ctx.accounts.stream.recipient,
+            ClawWalletError::InvalidStream
+        );
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[new_recipient],
+        )?;
+
+        let stream = &mut ctx.accounts.stream;
+        let old_recipient = stream.recipient;
+        stream.recipient = new_recipient;
+
+        emit!(StreamRecipientChanged {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: stream.nonce,
+            old_recipient,
+            new_recipient,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless cleanup: close a fully withdrawn stream, returning its rent to the wallet owner
+    pub fn close_stream(ctx: Context<CloseStream>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        require!(
+            stream.withdrawn == stream.deposit,
+            ClawWalletError::NotDrained
+        );
+
+        emit!(StreamClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: stream.nonce,
+            recipient: stream.recipient,
+            rent_refunded: stream.to_account_info().lamports(),
+        });
+        ctx.accounts.wallet.close_object(OPEN_STREAM);
+
+        Ok(())
+    }
+
+    /// Owner: lock `amount` lamports in a grant that vests to `beneficiary` linearly from start to end after a cliff
+    pub fn create_vesting_grant(
+        ctx: Context<CreateVestingGrant>,
+        beneficiary: Pubkey,
+        amount: u64,
+        start_time: Option<i64>, // None = now
+        cliff_time: Option<i64>, // None = start
+        end_time: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let start_time = start_time.unwrap_or(now);
+        let cliff_time = cliff_time.unwrap_or(start_time);
+        require!(start_time >= now, ClawWalletError::InvalidVesting);
+        require!(end_time > start_time, ClawWalletError::InvalidVesting);
+        require!(
+            cliff_time >= start_time && cliff_time <= end_time,
+            ClawWalletError::InvalidVesting
+        );
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[beneficiary],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.grant.to_account_info(), amount)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.vesting_nonce;
+        wallet.vesting_nonce += 1;
+        wallet.open_object(OPEN_VESTING)?;
+
+        let grant = &mut ctx.accounts.grant;
+        grant.wallet = wallet.key();
+        grant.nonce = nonce;
+        grant.beneficiary = beneficiary;
+        grant.total = amount;
+        grant.claimed = 0;
+        grant.start_time = start_time;
+        grant.cliff_time = cliff_time;
+        grant.end_time = end_time;
+        grant.revoked_at = 0;
+        grant.created_at = now;
+        grant.bump = *ctx.bumps.get("grant").unwrap();
+
+        emit!(VestingGrantCreated {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            beneficiary,
+            amount,
+            start_time,
+            cliff_time,
+            end_time,
+        });
+
+        Ok(())
+    }
+
+    /// Beneficiary: claim everything vested so far (protocol fee at the grantor's tier)
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let grant = &ctx.accounts.grant;
+        let amount = grant.vested(now) - grant.claimed;
+        require!(amount > 0, ClawWalletError::NothingToWithdraw);
+
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        let grant_info = ctx.accounts.grant.to_account_info();
+        transfer_lamports(
+            &grant_info,
+            &ctx.accounts.beneficiary.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&grant_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let grant = &mut ctx.accounts.grant;
+        grant.claimed = grant
+            .claimed
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(VestedClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: grant.nonce,
+            beneficiary: grant.beneficiary,
+            amount: send_amount,
+            fee,
+            remaining: grant.total - grant.claimed,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: return the unvested remainder of a grant to the wallet; what has vested stays claimable
+    pub fn revoke_unvested(ctx: Context<RevokeUnvested>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.grant.revoked_at == 0,
+            ClawWalletError::InvalidVesting
+        );
+
+        let grant = &ctx.accounts.grant;
+        let vested = grant.vested(now);
+        let unvested = grant.total - vested;
+        transfer_lamports(
+            &ctx.accounts.grant.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            unvested,
+        )?;
+
+        let grant = &mut ctx.accounts.grant;
+        grant.total = vested;
+        grant.revoked_at = now;
+
+        emit!(VestingRevoked {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: grant.nonce,
+            beneficiary: grant.beneficiary,
+            vested,
+            refunded: unvested,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless cleanup: close a fully claimed grant, returning its rent to the wallet owner
+    pub fn close_vesting_grant(ctx: Context<CloseVestingGrant>) -> Result<()> {
+        let grant = &ctx.accounts.grant;
+        require!(grant.claimed == grant.total, ClawWalletError::NotDrained);
+
+        emit!(VestingGrantClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: grant.nonce,
+            beneficiary: grant.beneficiary,
+            rent_refunded: grant.to_account_info().lamports(),
+        });
+        ctx.accounts.wallet.close_object(OPEN_VESTING);
+
+        Ok(())
+    }
+
+    /// Owner: set up a payroll that pays `entries` every `period` seconds starting at `first_run`
+    pub fn create_payroll(
+        ctx: Context<CreatePayroll>,
+        entries: Vec<PayrollEntry>,
+        period: i64,
+        first_run: i64,
+    ) -> Result<()> {
+        require!(period > 0, ClawWalletError::InvalidPayroll);
+        let now = Clock::get()?.unix_timestamp;
+
+        let payroll = &mut ctx.accounts.payroll;
+        payroll.set_roster(entries)?;
+        payroll.wallet = ctx.accounts.wallet.key();
+        payroll.period = period;
+        payroll.next_run = std::cmp::max(first_run, now);
+        payroll.runs = 0;
+        payroll.created_at = now;
+        payroll.bump = *ctx.bumps.get("payroll").unwrap();
+
+        emit!(PayrollUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            entries: payroll.entries.len() as u8,
+            period,
+            next_run: payroll.next_run,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: replace the payroll roster and period; the next run date is kept
+    pub fn update_payroll(
+        ctx: Context<UpdatePayroll>,
+        entries: Vec<PayrollEntry>,
+        period: i64,
+    ) -> Result<()> {
+        require!(period > 0, ClawWalletError::InvalidPayroll);
+
+        let payroll = &mut ctx.accounts.payroll;
+        payroll.set_roster(entries)?;
+        payroll.period = period;
+
+        emit!(PayrollUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            entries: payroll.entries.len() as u8,
+            period,
+            next_run: payroll.next_run,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: delete the payroll and reclaim its rent
+    pub fn close_payroll(ctx: Context<ClosePayroll>) -> Result<()> {
+        emit!(PayrollClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            runs: ctx.accounts.payroll.runs,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: pay the whole roster once due, with one fee per asset charged on top.
+    /// Remaining accounts: one payee per entry (the agent wallet for SOL, its token account for SPL),
+    /// then [wallet token account, treasury token account] per distinct mint in roster order.
+    pub fn run_payroll<'info>(ctx: Context<'_, '_, '_, 'info, RunPayroll<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.payroll.next_run,
+            ClawWalletError::PaymentNotDue
+        );
+
+        let entries = ctx.accounts.payroll.entries.clone();
+        let mints = ctx.accounts.payroll.mints();
+        let accounts = ctx.remaining_accounts;
+        require!(
+            accounts.len() == entries.len() + mints.len() * 2,
+            ClawWalletError::InvalidBatch
+        );
+        let (payees, fee_legs) = accounts.split_at(entries.len());
+
+        let agents: Vec<Pubkey> = entries.iter().map(|e| e.agent).collect();
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &agents,
+        )?;
+
+        let wallet_key = ctx.accounts.wallet.key();
+        let points = ctx.accounts.wallet.points;
+        let agent_id = ctx.accounts.wallet.agent_id.clone();
+        let seeds = &[
+            b"wallet".as_ref(),
+            agent_id.as_bytes(),
+            &[ctx.accounts.wallet.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+
+        let sol_total = entries
+            .iter()
+            .filter(|e| e.mint.is_none())
+            .try_fold(0u64, |acc, e| acc.checked_add(e.amount))
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let mut sol_fee: u64 = 0;
+        if sol_total > 0 {
+            sol_fee = ctx.accounts.config.sol_fee_for(sol_total, points);
+            let outflow = sol_total
+                .checked_add(sol_fee)
+                .ok_or(ClawWalletError::MathOverflow)?;
+            ctx.accounts.wallet.record_outflow(outflow, now)?;
+            require_spendable(&wallet_info, outflow)?;
+            transfer_lamports(
+                &wallet_info,
+                &ctx.accounts.treasury.to_account_info(),
+                sol_fee,
+            )?;
+        }
+
+        let mut token_totals: Vec<u64> = Vec::with_capacity(mints.len());
+        let mut token_fees: Vec<u64> = Vec::with_capacity(mints.len());
+        for (mint, leg) in mints.iter().zip(fee_legs.chunks(2)) {
+            let wallet_token_account = Account::<TokenAccount>::try_from(&leg[0])?;
+            require!(
+                wallet_token_account.owner == wallet_key,
+                ClawWalletError::InvalidBatch
+            );
+            require!(
+                wallet_token_account.mint == *mint,
+                ClawWalletError::MintMismatch
+            );
+            require!(
+                leg[1].key()
+                    == get_associated_token_address(&ctx.accounts.config.fee_recipient(), mint),
+                ClawWalletError::InvalidTreasury
+            );
+
+            let total = entries
+                .iter()
+                .filter(|e| e.mint == Some(*mint))
+                .try_fold(0u64, |acc, e| acc.checked_add(e.amount))
+                .ok_or(ClawWalletError::MathOverflow)?;
+            let fee = ctx.accounts.config.token_fee_for(total, points);
+            let cpi_ctx_fee = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SplTransfer {
+                    from: leg[0].clone(),
+                    to: leg[1].clone(),
+                    authority: wallet_info.clone(),
+                },
+                signer_seeds,
+            );
+            token::transfer(cpi_ctx_fee, fee)?;
+            token_totals.push(total);
+            token_fees.push(fee);
+        }
+
+        for (entry, payee) in entries.iter().zip(payees.iter()) {
+            match entry.mint {
+                None => {
+                    require!(payee.key() == entry.agent, ClawWalletError::InvalidBatch);
+                    transfer_lamports(&wallet_info, payee, entry.amount)?;
+                }
+                Some(mint) => {
+                    let payee_token_account = Account::<TokenAccount>::try_from(payee)?;
+                    require!(
+                        payee_token_account.owner == entry.agent,
+                        ClawWalletError::InvalidBatch
+                    );
+                    require!(
+                        payee_token_account.mint == mint,
+                        ClawWalletError::MintMismatch
+                    );
+                    let leg = mints.iter().position(|m| *m == mint).unwrap() * 2;
+                    let cpi_ctx = CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        SplTransfer {
+                            from: fee_legs[leg].clone(),
+                            to: payee.clone(),
+                            authority: wallet_info.clone(),
+                        },
+                        signer_seeds,
+                    );
+                    token::transfer(cpi_ctx, entry.amount)?;
+                }
+            }
+        }
+        pay_keeper_tip(
+            &wallet_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        // Payroll runs count as one transaction and do not earn points
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        wallet.record_category(CATEGORY_PAYROLL, sol_total)?;
+
+        let payroll = &mut ctx.accounts.payroll;
+        payroll.runs += 1;
+        // A late run restarts the schedule from now rather than allowing back-to-back catch-up runs
+        let scheduled = payroll
+            .next_run
+            .checked_add(payroll.period)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let restarted = now
+            .checked_add(payroll.period)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        payroll.next_run = std::cmp::max(scheduled, restarted);
+
+        emit!(PayrollRun {
+            agent_id: wallet.agent_id.clone(),
+            run: payroll.runs,
+            entries: entries.len() as u8,
+            sol_total,
+            sol_fee,
+            mints,
+            token_totals,
+            token_fees,
+            next_run: payroll.next_run,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: lock lamports in escrow for the `payee` agent wallet, releasable milestone by milestone
+    pub fn create_escrow(
+        ctx: Context<CreateEscrow>,
+        milestones: Vec<u64>,
+        expires_at: i64,
+        arbiter: Option<Pubkey>,
+    ) -> Result<()> {
+        let amount = Escrow::total_of(&milestones)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, ClawWalletError::InvalidEscrow);
+        let arbiter = arbiter.unwrap_or_default();
+        require!(
+            arbiter != ctx.accounts.wallet.owner && arbiter != ctx.accounts.payee_wallet.owner,
+            ClawWalletError::InvalidArbiter
+        );
+        require_bonded_arbiter(arbiter, &ctx.accounts.arbiter_bond, &ctx.accounts.config)?;
+        let payee = ctx.accounts.payee_wallet.key();
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[payee],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.escrow.to_account_info(), amount)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.escrow_nonce;
+        wallet.escrow_nonce += 1;
+        wallet.open_object(OPEN_ESCROW)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.wallet = wallet.key();
+        escrow.payee = payee;
+        escrow.nonce = nonce;
+        escrow.rent_payer = ctx.accounts.owner.key();
+        escrow.mint = None;
+        escrow.amount = amount;
+        escrow.created_at = now;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        escrow.milestones = milestones;
+        escrow.released_mask = 0;
+        escrow.released = 0;
+        escrow.expires_at = expires_at;
+        escrow.arbiter = arbiter;
+        escrow.disputed_at = 0;
+        escrow.disputed_by = Pubkey::default();
+
+        emit!(EscrowCreated {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            payee,
+            mint: None,
+            amount,
+            milestones: escrow.milestones.len() as u8,
+            expires_at,
+            arbiter,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: lock SPL tokens in an escrow vault for the `payee` agent wallet, releasable milestone by milestone
+    pub fn create_token_escrow(
+        ctx: Context<CreateTokenEscrow>,
+        milestones: Vec<u64>,
+        expires_at: i64,
+        arbiter: Option<Pubkey>,
+    ) -> Result<()> {
+        let amount = Escrow::total_of(&milestones)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, ClawWalletError::InvalidEscrow);
+        let arbiter = arbiter.unwrap_or_default();
+        require!(
+            arbiter != ctx.accounts.wallet.owner && arbiter != ctx.accounts.payee_wallet.owner,
+            ClawWalletError::InvalidArbiter
+        );
+        require_bonded_arbiter(arbiter, &ctx.accounts.arbiter_bond, &ctx.accounts.config)?;
+        let payee = ctx.accounts.payee_wallet.key();
+        let mint = ctx.accounts.mint.key();
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[payee],
+        )?;
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.vault,
+            &ctx.accounts.wallet,
+            amount,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.escrow_nonce;
+        wallet.escrow_nonce += 1;
+        wallet.open_object(OPEN_ESCROW)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.wallet = wallet.key();
+        escrow.payee = payee;
+        escrow.nonce = nonce;
+        escrow.rent_payer = ctx.accounts.owner.key();
+        escrow.mint = Some(mint);
+        escrow.amount = amount;
+        escrow.created_at = now;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        escrow.milestones = milestones;
+        escrow.released_mask = 0;
+        escrow.released = 0;
+        escrow.expires_at = expires_at;
+        escrow.arbiter = arbiter;
+        escrow.disputed_at = 0;
+        escrow.disputed_by = Pubkey::default();
+
+        emit!(EscrowCreated {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            payee,
+            mint: Some(mint),
+            amount,
+            milestones: escrow.milestones.len() as u8,
+            expires_at,
+            arbiter,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: release one milestone of escrowed SOL to the payee (protocol fee); closes once all are released
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>, milestone: u8) -> Result<()> {
+        require!(
+            ctx.accounts.escrow.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            ctx.accounts.escrow.disputed_at == 0,
+            ClawWalletError::EscrowDisputed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.escrow.release_milestone(milestone)?;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        transfer_lamports(
+            &escrow_info,
+            &ctx.accounts.payee.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&escrow_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.sol_points_for(amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let remaining = ctx.accounts.escrow.remaining();
+        emit!(EscrowReleased {
+            agent_id: wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: None,
+            milestone,
+            amount: send_amount,
+            fee,
+            remaining,
+        });
+
+        if remaining == 0 {
+            ctx.accounts.wallet.close_object(OPEN_ESCROW);
+            ctx.accounts
+                .escrow
+                .close(ctx.accounts.rent_payer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Owner: release one milestone of escrowed SPL tokens to the payee's token account (protocol fee)
+    pub fn release_token_escrow(ctx: Context<ReleaseTokenEscrow>, milestone: u8) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.escrow.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            ctx.accounts.escrow.disputed_at == 0,
+            ClawWalletError::EscrowDisputed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.escrow.release_milestone(milestone)?;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.payee_token_account.to_account_info(),
+            send_amount,
+        )?;
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            fee,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+        let points_earned = ctx.accounts.config.token_points_for(&mint, amount);
+        wallet.add_points(points_earned, &ctx.accounts.config);
+
+        let remaining = ctx.accounts.escrow.remaining();
+        emit!(EscrowReleased {
+            agent_id: wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: Some(mint),
+            milestone,
+            amount: send_amount,
+            fee,
+            remaining,
+        });
+
+        if remaining == 0 {
+            ctx.accounts.wallet.close_object(OPEN_ESCROW);
+            close_escrow_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.escrow,
+                &ctx.accounts.vault,
+                ctx.accounts.rent_payer.to_account_info(),
+            )?;
+            ctx.accounts
+                .escrow
+                .close(ctx.accounts.rent_payer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Payee owner: hand the unreleased escrowed SOL back to the payer wallet
+    pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+        require!(
+            ctx.accounts.escrow.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            ctx.accounts.escrow.disputed_at == 0,
+            ClawWalletError::EscrowDisputed
+        );
+        let amount = ctx.accounts.escrow.remaining();
+        transfer_lamports(
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            amount,
+        )?;
+
+        emit!(EscrowRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: None,
+            amount,
+            expired: false,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Payee owner: hand the unreleased escrowed SPL tokens back to the payer wallet's token account
+    pub fn refund_token_escrow(ctx: Context<RefundTokenEscrow>) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.escrow.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            ctx.accounts.escrow.disputed_at == 0,
+            ClawWalletError::EscrowDisputed
+        );
+        let amount = ctx.accounts.escrow.remaining();
+
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            amount,
+        )?;
+        close_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(EscrowRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: Some(mint),
+            amount,
+            expired: false,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return an expired escrow's unreleased SOL to the payer wallet
+    pub fn refund_expired_escrow(ctx: Context<RefundExpiredEscrow>) -> Result<()> {
+        require!(
+            ctx.accounts.escrow.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.escrow.refundable_at(),
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.escrow.remaining();
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        transfer_lamports(&escrow_info, &ctx.accounts.wallet.to_account_info(), amount)?;
+        pay_cleanup_bounty(
+            &escrow_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(EscrowRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: None,
+            amount,
+            expired: true,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return an expired escrow's unreleased SPL tokens to the payer wallet
+    pub fn refund_expired_token_escrow(ctx: Context<RefundExpiredTokenEscrow>) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.escrow.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.escrow.refundable_at(),
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.escrow.remaining();
+
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            amount,
+        )?;
+        close_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+        pay_cleanup_bounty(
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(EscrowRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            mint: Some(mint),
+            amount,
+            expired: true,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Payer or payee owner: freeze an escrow for the designated arbiter to resolve
+    pub fn dispute_escrow(ctx: Context<DisputeEscrow>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == ctx.accounts.wallet.owner || authority == ctx.accounts.payee_wallet.owner,
+            ClawWalletError::Unauthorized
+        );
+        let escrow = &mut ctx.accounts.escrow;
+        require!(
+            escrow.arbiter != Pubkey::default(),
+            ClawWalletError::InvalidArbiter
+        );
+        require!(escrow.disputed_at == 0, ClawWalletError::EscrowDisputed);
+        escrow.disputed_at = Clock::get()?.unix_timestamp;
+        escrow.disputed_by = authority;
+
+        emit!(EscrowDisputeOpened {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: escrow.nonce,
+            payee: escrow.payee,
+            arbiter: escrow.arbiter,
+            disputed_by: authority,
+        });
+
+        Ok(())
+    }
+
+    /// Arbiter: split a disputed SOL escrow, `payee_amount` to the payee (protocol fee) and the rest back to the payer
+    pub fn resolve_escrow_dispute(
+        ctx: Context<ResolveEscrowDispute>,
+        payee_amount: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.escrow.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        let remaining = ctx.accounts.escrow.remaining();
+        require!(payee_amount <= remaining, ClawWalletError::InvalidAmount);
+        let payer_amount = remaining - payee_amount;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(payee_amount, ctx.accounts.wallet.points);
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        transfer_lamports(
+            &escrow_info,
+            &ctx.accounts.payee.to_account_info(),
+            payee_amount - fee,
+        )?;
+        transfer_lamports(&escrow_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+        transfer_lamports(
+            &escrow_info,
+            &ctx.accounts.wallet.to_account_info(),
+            payer_amount,
+        )?;
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        arbiter_bond.disputes_resolved = arbiter_bond
+            .disputes_resolved
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(EscrowDisputeResolved {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            arbiter: ctx.accounts.arbiter.key(),
+            mint: None,
+            payee_amount: payee_amount - fee,
+            payer_amount,
+            fee,
+            resolved_at: Clock::get()?.unix_timestamp,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Arbiter: split a disputed token escrow, `payee_amount` to the payee (protocol fee) and the rest back to the payer
+    pub fn resolve_token_escrow_dispute(
+        ctx: Context<ResolveTokenEscrowDispute>,
+        payee_amount: u64,
+    ) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.escrow.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        let remaining = ctx.accounts.escrow.remaining();
+        require!(payee_amount <= remaining, ClawWalletError::InvalidAmount);
+        let payer_amount = remaining - payee_amount;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(payee_amount, ctx.accounts.wallet.points);
+
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.payee_token_account.to_account_info(),
+            payee_amount - fee,
+        )?;
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            fee,
+        )?;
+        escrow_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            payer_amount,
+        )?;
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        arbiter_bond.disputes_resolved = arbiter_bond
+            .disputes_resolved
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        close_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(EscrowDisputeResolved {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.escrow.nonce,
+            payee: ctx.accounts.escrow.payee,
+            arbiter: ctx.accounts.arbiter.key(),
+            mint: Some(mint),
+            payee_amount: payee_amount - fee,
+            payer_amount,
+            fee,
+            resolved_at: Clock::get()?.unix_timestamp,
+        });
+        ctx.accounts.wallet.close_object(OPEN_ESCROW);
+
+        Ok(())
+    }
+
+    /// Admin: set the minimum bond an arbiter must hold to be picked for or resolve disputes
+    pub fn set_min_arbiter_bond(ctx: Context<AdminConfig>, min_arbiter_bond: u64) -> Result<()> {
+        ctx.accounts.config.min_arbiter_bond = min_arbiter_bond;
+
+        emit!(MinArbiterBondSet {
+            admin: ctx.accounts.admin.key(),
+            min_arbiter_bond,
+        });
+
+        Ok(())
+    }
+
+    /// Stake `bond` lamports to become an eligible escrow arbiter
+    pub fn register_arbiter(ctx: Context<RegisterArbiter>, bond: u64) -> Result<()> {
+        require!(
+            bond >= ctx.accounts.config.min_arbiter_bond,
+            ClawWalletError::InsufficientBond
+        );
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.arbiter_bond.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, bond)?;
+
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        arbiter_bond.authority = ctx.accounts.authority.key();
+        arbiter_bond.bond = bond;
+        arbiter_bond.slashed = 0;
+        arbiter_bond.disputes_resolved = 0;
+        arbiter_bond.registered_at = Clock::get()?.unix_timestamp;
+        arbiter_bond.unbonding_at = 0;
+        arbiter_bond.bump = *ctx.bumps.get("arbiter_bond").unwrap();
+
+        emit!(ArbiterBondChanged {
+            arbiter: arbiter_bond.authority,
+            bond: arbiter_bond.bond,
+            unbonding_at: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Arbiter: add to the bond; also cancels a pending unbond
+    pub fn top_up_arbiter_bond(ctx: Context<UpdateArbiterBond>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.arbiter_bond.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)?;
+
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        arbiter_bond.bond = arbiter_bond
+            .bond
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        arbiter_bond.unbonding_at = 0;
+
+        emit!(ArbiterBondChanged {
+            arbiter: arbiter_bond.authority,
+            bond: arbiter_bond.bond,
+            unbonding_at: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Arbiter: stop taking disputes and start the unbonding period
+    pub fn request_arbiter_unbond(ctx: Context<UpdateArbiterBond>) -> Result<()> {
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        require!(
+            arbiter_bond.unbonding_at == 0,
+            ClawWalletError::InvalidArbiter
+        );
+        arbiter_bond.unbonding_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(ARBITER_UNBONDING_PERIOD)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(ArbiterBondChanged {
+            arbiter: arbiter_bond.authority,
+            bond: arbiter_bond.bond,
+            unbonding_at: arbiter_bond.unbonding_at,
+        });
+
+        Ok(())
+    }
+
+    /// Arbiter: once unbonded, close the registration and take back the bond and rent
+    pub fn withdraw_arbiter_bond(ctx: Context<WithdrawArbiterBond>) -> Result<()> {
+        let arbiter_bond = &ctx.accounts.arbiter_bond;
+        require!(
+            arbiter_bond.unbonding_at != 0,
+            ClawWalletError::InvalidArbiter
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= arbiter_bond.unbonding_at,
+            ClawWalletError::NotExpired
+        );
+
+        emit!(ArbiterBondChanged {
+            arbiter: arbiter_bond.authority,
+            bond: 0,
+            unbonding_at: arbiter_bond.unbonding_at,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: slash part of an arbiter's bond to the treasury for provable misconduct
+    pub fn slash_arbiter(
+        ctx: Context<SlashArbiter>,
+        amount: u64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            amount > 0 && amount <= ctx.accounts.arbiter_bond.bond,
+            ClawWalletError::InvalidAmount
+        );
+        transfer_lamports(
+            &ctx.accounts.arbiter_bond.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            amount,
+        )?;
+
+        let arbiter_bond = &mut ctx.accounts.arbiter_bond;
+        arbiter_bond.bond -= amount;
+        arbiter_bond.slashed += amount;
+
+        emit!(ArbiterSlashed {
+            admin: ctx.accounts.admin.key(),
+            arbiter: arbiter_bond.authority,
+            amount,
+            bond: arbiter_bond.bond,
+            evidence_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: lock lamports for `recipient`, claimable with the sha256 preimage of `hashlock` before `timeout`
+    pub fn lock_htlc(
+        ctx: Context<LockHtlc>,
+        recipient: Pubkey,
+        amount: u64,
+        hashlock: [u8; 32],
+        timeout: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(timeout > now, ClawWalletError::InvalidHtlc);
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.htlc.to_account_info(), amount)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.htlc_nonce;
+        wallet.htlc_nonce += 1;
+        wallet.open_object(OPEN_HTLC)?;
+
+        let htlc = &mut ctx.accounts.htlc;
+        htlc.wallet = wallet.key();
+        htlc.nonce = nonce;
+        htlc.recipient = recipient;
+        htlc.rent_payer = ctx.accounts.owner.key();
+        htlc.mint = None;
+        htlc.amount = amount;
+        htlc.hashlock = hashlock;
+        htlc.timeout = timeout;
+        htlc.created_at = now;
+        htlc.bump = *ctx.bumps.get("htlc").unwrap();
+
+        emit!(HtlcLocked {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint: None,
+            amount,
+            hashlock,
+            timeout,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: lock SPL tokens for `recipient`, claimable with the sha256 preimage of `hashlock` before `timeout`
+    pub fn lock_token_htlc(
+        ctx: Context<LockTokenHtlc>,
+        recipient: Pubkey,
+        amount: u64,
+        hashlock: [u8; 32],
+        timeout: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(timeout > now, ClawWalletError::InvalidHtlc);
+        let mint = ctx.accounts.mint.key();
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.vault,
+            &ctx.accounts.wallet,
+            amount,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.htlc_nonce;
+        wallet.htlc_nonce += 1;
+        wallet.open_object(OPEN_HTLC)?;
+
+        let htlc = &mut ctx.accounts.htlc;
+        htlc.wallet = wallet.key();
+        htlc.nonce = nonce;
+        htlc.recipient = recipient;
+        htlc.rent_payer = ctx.accounts.owner.key();
+        htlc.mint = Some(mint);
+        htlc.amount = amount;
+        htlc.hashlock = hashlock;
+        htlc.timeout = timeout;
+        htlc.created_at = now;
+        htlc.bump = *ctx.bumps.get("htlc").unwrap();
+
+        emit!(HtlcLocked {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint: Some(mint),
+            amount,
+            hashlock,
+            timeout,
+        });
+
+        Ok(())
+    }
+
+    /// Anyone holding the preimage: pay a SOL HTLC to its recipient before the timeout (protocol fee)
+    pub fn claim_htlc(ctx: Context<ClaimHtlc>, preimage: [u8; 32]) -> Result<()> {
+        let htlc = &ctx.accounts.htlc;
+        require!(htlc.mint.is_none(), ClawWalletError::WrongTransactionKind);
+        require!(
+            Clock::get()?.unix_timestamp < htlc.timeout,
+            ClawWalletError::HtlcExpired
+        );
+        require!(
+            hash::hash(&preimage).to_bytes() == htlc.hashlock,
+            ClawWalletError::InvalidPreimage
+        );
+
+        let amount = htlc.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        let htlc_info = ctx.accounts.htlc.to_account_info();
+        transfer_lamports(
+            &htlc_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(&htlc_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        emit!(HtlcClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.htlc.nonce,
+            recipient: ctx.accounts.htlc.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+            preimage,
+        });
+        ctx.accounts.wallet.close_object(OPEN_HTLC);
+
+        Ok(())
+    }
+
+    /// Anyone holding the preimage: pay a token HTLC to its recipient's token account before the timeout
+    pub fn claim_token_htlc(ctx: Context<ClaimTokenHtlc>, preimage: [u8; 32]) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        let htlc = &ctx.accounts.htlc;
+        require!(
+            htlc.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp < htlc.timeout,
+            ClawWalletError::HtlcExpired
+        );
+        require!(
+            hash::hash(&preimage).to_bytes() == htlc.hashlock,
+            ClawWalletError::InvalidPreimage
+        );
+
+        let amount = htlc.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        htlc_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.htlc,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            send_amount,
+        )?;
+        htlc_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.htlc,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            fee,
+        )?;
+        close_htlc_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.htlc,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(HtlcClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.htlc.nonce,
+            recipient: ctx.accounts.htlc.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+            preimage,
+        });
+        ctx.accounts.wallet.close_object(OPEN_HTLC);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return a timed-out SOL HTLC to the wallet
+    pub fn refund_htlc(ctx: Context<RefundHtlc>) -> Result<()> {
+        require!(
+            ctx.accounts.htlc.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.htlc.timeout,
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.htlc.amount;
+        let htlc_info = ctx.accounts.htlc.to_account_info();
+        transfer_lamports(&htlc_info, &ctx.accounts.wallet.to_account_info(), amount)?;
+        pay_cleanup_bounty(
+            &htlc_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(HtlcRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.htlc.nonce,
+            mint: None,
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_HTLC);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return a timed-out token HTLC to the wallet's token account
+    pub fn refund_token_htlc(ctx: Context<RefundTokenHtlc>) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.htlc.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.htlc.timeout,
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.htlc.amount;
+
+        htlc_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.htlc,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            amount,
+        )?;
+        close_htlc_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.htlc,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+        pay_cleanup_bounty(
+            &ctx.accounts.htlc.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(HtlcRefunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.htlc.nonce,
+            mint: Some(mint),
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_HTLC);
+
+        Ok(())
+    }
+
+    /// Owner: park lamports in a PDA keyed by `recipient` until they claim them
+    pub fn send_claimable(
+        ctx: Context<SendClaimable>,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let references = collect_references(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.claimable.to_account_info(),
+            amount,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.claimable_nonce;
+        wallet.claimable_nonce += 1;
+        wallet.open_object(OPEN_CLAIMABLE)?;
+
+        let claimable = &mut ctx.accounts.claimable;
+        claimable.wallet = wallet.key();
+        claimable.nonce = nonce;
+        claimable.recipient = recipient;
+        claimable.rent_payer = ctx.accounts.owner.key();
+        claimable.mint = None;
+        claimable.amount = amount;
+        claimable.created_at = now;
+        claimable.bump = *ctx.bumps.get("claimable").unwrap();
+        claimable.expires_at = now
+            .checked_add(ctx.accounts.config.claimable_ttl)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(ClaimableSent {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint: None,
+            amount,
+            expires_at: claimable.expires_at,
+            references,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: park SPL tokens in a vault keyed by `recipient` until they claim them
+    pub fn send_claimable_token(
+        ctx: Context<SendClaimableToken>,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let references = collect_references(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.mint.key();
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.vault,
+            &ctx.accounts.wallet,
+            amount,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.claimable_nonce;
+        wallet.claimable_nonce += 1;
+        wallet.open_object(OPEN_CLAIMABLE)?;
+
+        let claimable = &mut ctx.accounts.claimable;
+        claimable.wallet = wallet.key();
+        claimable.nonce = nonce;
+        claimable.recipient = recipient;
+        claimable.rent_payer = ctx.accounts.owner.key();
+        claimable.mint = Some(mint);
+        claimable.amount = amount;
+        claimable.created_at = now;
+        claimable.bump = *ctx.bumps.get("claimable").unwrap();
+        claimable.expires_at = now
+            .checked_add(ctx.accounts.config.claimable_ttl)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(ClaimableSent {
+            agent_id: wallet.agent_id.clone(),
+            nonce,
+            recipient,
+            mint: Some(mint),
+            amount,
+            expires_at: claimable.expires_at,
+            references,
+        });
+
+        Ok(())
+    }
+
+    /// Recipient: collect a claimable SOL transfer (protocol fee at the sender's tier)
+    pub fn claim_transfer(ctx: Context<ClaimTransfer>) -> Result<()> {
+        require!(
+            ctx.accounts.claimable.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.claimable.expires_at,
+            ClawWalletError::ClaimExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        let claimable_info = ctx.accounts.claimable.to_account_info();
+        transfer_lamports(
+            &claimable_info,
+            &ctx.accounts.recipient.to_account_info(),
+            send_amount,
+        )?;
+        transfer_lamports(
+            &claimable_info,
+            &ctx.accounts.treasury.to_account_info(),
+            fee,
+        )?;
+
+        emit!(ClaimableClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Recipient: collect a claimable token transfer into their associated token account, creating it if needed
+    pub fn claim_token_transfer(ctx: Context<ClaimTokenTransfer>) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        require!(
+            ctx.accounts.claimable.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.claimable.expires_at,
+            ClawWalletError::ClaimExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+
+        claimable_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            send_amount,
+        )?;
+        claimable_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            fee,
+        )?;
+        close_claimable_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(ClaimableClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Owner: take back an unclaimed SOL transfer, e.g. one sent to a mistyped address
+    pub fn cancel_claimable(ctx: Context<CancelClaimable>) -> Result<()> {
+        require!(
+            ctx.accounts.claimable.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        let amount = ctx.accounts.claimable.amount;
+        transfer_lamports(
+            &ctx.accounts.claimable.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            amount,
+        )?;
+
+        emit!(ClaimableReturned {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: None,
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Owner: take back an unclaimed token transfer
+    pub fn cancel_claimable_token(ctx: Context<CancelClaimableToken>) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.claimable.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        let amount = ctx.accounts.claimable.amount;
+
+        claimable_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            amount,
+        )?;
+        close_claimable_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(ClaimableReturned {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: Some(mint),
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Admin: set how long claimable transfers wait for their recipient before they can be returned
+    pub fn set_claimable_ttl(ctx: Context<AdminConfig>, claimable_ttl: i64) -> Result<()> {
+        require!(
+            claimable_ttl >= SECONDS_PER_DAY && claimable_ttl <= MAX_CLAIMABLE_TTL,
+            ClawWalletError::InvalidAmount
+        );
+        ctx.accounts.config.claimable_ttl = claimable_ttl;
+
+        emit!(ClaimableTtlSet {
+            admin: ctx.accounts.admin.key(),
+            claimable_ttl,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return an expired SOL claimable to the sender wallet
+    pub fn return_expired_claimable(ctx: Context<ReturnExpiredClaimable>) -> Result<()> {
+        require!(
+            ctx.accounts.claimable.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.claimable.expires_at,
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+        let claimable_info = ctx.accounts.claimable.to_account_info();
+        transfer_lamports(
+            &claimable_info,
+            &ctx.accounts.wallet.to_account_info(),
+            amount,
+        )?;
+        pay_cleanup_bounty(
+            &claimable_info,
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let claimable = &ctx.accounts.claimable;
+        emit!(ClaimableExpired {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: claimable.nonce,
+            recipient: claimable.recipient,
+            expires_at: claimable.expires_at,
+        });
+        emit!(ClaimableReturned {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: claimable.nonce,
+            recipient: claimable.recipient,
+            mint: None,
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Permissionless crank: return an expired token claimable to the sender wallet's token account
+    pub fn return_expired_claimable_token(ctx: Context<ReturnExpiredClaimableToken>) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.claimable.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.claimable.expires_at,
+            ClawWalletError::NotExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+
+        claimable_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.wallet_token_account.to_account_info(),
+            amount,
+        )?;
+        close_claimable_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+        pay_cleanup_bounty(
+            &ctx.accounts.claimable.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        let claimable = &ctx.accounts.claimable;
+        emit!(ClaimableExpired {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: claimable.nonce,
+            recipient: claimable.recipient,
+            expires_at: claimable.expires_at,
+        });
+        emit!(ClaimableReturned {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: claimable.nonce,
+            recipient: claimable.recipient,
+            mint: Some(mint),
+            amount,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Owner: issue an invoice for `amount` (SOL or `mint`) payable to this wallet
+    pub fn create_invoice(
+        ctx: Context<CreateInvoice>,
+        payer: Option<Pubkey>, // None = anyone may pay
+        mint: Option<Pubkey>,
+        amount: u64,
+        memo: String,
+        expires_at: Option<i64>, // None = never
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        require!(
+            memo.len() <= MAX_INVOICE_MEMO_LEN,
+            ClawWalletError::InvalidMemo
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = expires_at.unwrap_or(0);
+        require!(
+            expires_at == 0 || expires_at > now,
+            ClawWalletError::InvalidInvoice
+        );
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.invoice_nonce;
+        wallet.invoice_nonce += 1;
+
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.issuer = wallet.key();
+        invoice.nonce = nonce;
+        invoice.payer = payer.unwrap_or_default();
+        invoice.mint = mint;
+        invoice.amount = amount;
+        invoice.memo = memo;
+        invoice.expires_at = expires_at;
+        invoice.created_at = now;
+        invoice.paid_at = 0;
+        invoice.paid_by = Pubkey::default();
+        invoice.paid_amount = 0;
+        invoice.bump = *ctx.bumps.get("invoice").unwrap();
+
+        emit!(InvoiceCreated {
+            agent_id: wallet.agent_id.clone(),
+            invoice: invoice.key(),
+            nonce,
+            payer: invoice.payer,
+            mint,
+            amount,
+            memo: invoice.memo.clone(),
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: close an invoice, paid or not, and reclaim its rent
+    pub fn close_invoice(ctx: Context<CloseInvoice>) -> Result<()> {
+        emit!(InvoiceClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            invoice: ctx.accounts.invoice.key(),
+            paid: ctx.accounts.invoice.paid_at != 0,
+        });
+
+        Ok(())
+    }
+
+    /// Pay all or part of a SOL invoice from this wallet; the protocol fee is charged on top and
+    /// the payment is clamped to the outstanding balance, so any excess is never drawn from the wallet
+    pub fn pay_invoice<'info>(
+        ctx: Context<'_, '_, '_, 'info, PayInvoice<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.invoice.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        ctx.accounts
+            .invoice
+            .require_payable(ctx.accounts.wallet.key(), now)?;
+        let issuer = ctx.accounts.issuer.key();
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        // Only the outstanding balance is drawn; any excess never leaves the payer's wallet
+        let clamped = amount.saturating_sub(ctx.accounts.invoice.outstanding());
+        let amount = amount - clamped;
+        let (payees, cuts, reference_infos) = revenue_split(
+            &ctx.accounts.issuer,
+            &ctx.accounts.revenue_share,
+            ctx.remaining_accounts,
+            None,
+            amount,
+        )?;
+        let references = collect_references(reference_infos)?;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let outflow = amount
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            outflow,
+            now,
+        )?;
+        ctx.accounts.wallet.record_outflow(outflow, now)?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[issuer],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                outflow,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[issuer])?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, outflow)?;
+        if payees.is_empty() {
+            transfer_lamports(&wallet_info, &ctx.accounts.issuer.to_account_info(), amount)?;
+        } else {
+            for (payee, cut) in payees.iter().zip(cuts.iter()) {
+                transfer_lamports(&wallet_info, payee, *cut)?;
+            }
+            emit!(RevenueShared {
+                wallet: issuer,
+                source: ctx.accounts.invoice.key(),
+                mint: None,
+                amount,
+                recipients: payees.iter().map(|p| p.key()).collect(),
+                amounts: cuts,
+            });
+        }
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.sol_points_for(amount);
+        let points_earned = wallet.earn_points(
+            base_points,
+            amount,
+            config.min_earn_lamports,
+            issuer,
+            now,
+            config,
+        );
+
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.paid_amount = invoice
+            .paid_amount
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        invoice.paid_by = wallet.key();
+        let remaining = invoice.outstanding();
+
+        emit!(InvoicePaid {
+            agent_id: wallet.agent_id.clone(),
+            invoice: invoice.key(),
+            issuer,
+            nonce: invoice.nonce,
+            mint: None,
+            amount,
+            fee,
+            points_earned,
+            paid_amount: invoice.paid_amount,
+            remaining,
+            clamped,
+            references,
+        });
+
+        if remaining == 0 {
+            invoice.paid_at = now;
+            emit!(InvoiceSettled {
+                invoice: invoice.key(),
+                issuer,
+                nonce: invoice.nonce,
+                mint: invoice.mint,
+                amount: invoice.amount,
+                settled_by: wallet.key(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Pay all or part of an SPL token invoice from this wallet; the protocol fee is charged on top and
+    /// the payment is clamped to the outstanding balance, so any excess is never drawn from the wallet
+    pub fn pay_invoice_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, PayInvoiceToken<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.wallet_token_account.mint;
+        require!(
+            ctx.accounts.invoice.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        ctx.accounts
+            .invoice
+            .require_payable(ctx.accounts.wallet.key(), now)?;
+        let issuer = ctx.accounts.invoice.issuer;
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        // Only the outstanding balance is drawn; any excess never leaves the payer's wallet
+        let clamped = amount.saturating_sub(ctx.accounts.invoice.outstanding());
+        let amount = amount - clamped;
+        let (payees, cuts, reference_infos) = revenue_split(
+            &ctx.accounts.issuer,
+            &ctx.accounts.revenue_share,
+            ctx.remaining_accounts,
+            Some(mint),
+            amount,
+        )?;
+        let references = collect_references(reference_infos)?;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(amount, ctx.accounts.wallet.points);
+        let outflow = amount
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            outflow,
+            now,
+        )?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[issuer, ctx.accounts.issuer_token_account.key()],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                outflow,
+                now,
+                false,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &[issuer, ctx.accounts.issuer_token_account.key()],
+            )?;
+        }
+
+        if payees.is_empty() {
+            transfer_from_wallet(
+                &ctx.accounts.token_program,
+                &ctx.accounts.wallet_token_account,
+                &ctx.accounts.issuer_token_account,
+                &ctx.accounts.wallet,
+                amount,
+            )?;
+        } else {
+            let agent_id = ctx.accounts.wallet.agent_id.clone();
+            let seeds = &[
+                b"wallet".as_ref(),
+                agent_id.as_bytes(),
+                &[ctx.accounts.wallet.bump],
+            ];
+            for (payee, cut) in payees.iter().zip(cuts.iter()) {
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    SplTransfer {
+                        from: ctx.accounts.wallet_token_account.to_account_info(),
+                        to: payee.clone(),
+                        authority: ctx.accounts.wallet.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                );
+                token::transfer(cpi_ctx, *cut)?;
+            }
+            emit!(RevenueShared {
+                wallet: issuer,
+                source: ctx.accounts.invoice.key(),
+                mint: Some(mint),
+                amount,
+                recipients: payees.iter().map(|p| p.key()).collect(),
+                amounts: cuts,
+            });
+        }
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.wallet,
+            fee,
+        )?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.token_points_for(&mint, amount);
+        let points_earned = wallet.earn_points(
+            base_points,
+            amount,
+            config.min_earn_token,
+            issuer,
+            now,
+            config,
+        );
+
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.paid_amount = invoice
+            .paid_amount
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        invoice.paid_by = wallet.key();
+        let remaining = invoice.outstanding();
+
+        emit!(InvoicePaid {
+            agent_id: wallet.agent_id.clone(),
+            invoice: invoice.key(),
+            issuer,
+            nonce: invoice.nonce,
+            mint: Some(mint),
+            amount,
+            fee,
+            points_earned,
+            paid_amount: invoice.paid_amount,
+            remaining,
+            clamped,
+            references,
+        });
+
+        if remaining == 0 {
+            invoice.paid_at = now;
+            emit!(InvoiceSettled {
+                invoice: invoice.key(),
+                issuer,
+                nonce: invoice.nonce,
+                mint: invoice.mint,
+                amount: invoice.amount,
+                settled_by: wallet.key(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Return SOL to the payer of an earlier transfer to this wallet, fee-free. The payment must have a
+    /// receipt, and refunds never total more than it
+    pub fn refund(ctx: Context<Refund>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let recipient = ctx.accounts.recipient.key();
+        ctx.accounts.receipt.record_refund(amount)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            amount,
+            now,
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                amount,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[recipient])?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+
+        // Refunds unwind an earlier payment: no fee, no points, no tx count
+        let receipt = &ctx.accounts.receipt;
+        emit!(Refunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            recipient,
+            mint: None,
+            amount,
+            receipt: receipt.key(),
+            refunded: receipt.refunded,
+        });
+
+        Ok(())
+    }
+
+    /// Return SPL tokens to the payer of an earlier transfer to this wallet, fee-free; like `refund`, the
+    /// payment's receipt caps the refunds
+    pub fn refund_token(ctx: Context<RefundToken>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.receipt.record_refund(amount)?;
+        let mint = ctx.accounts.wallet_token_account.mint;
+        let parties = [
+            ctx.accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_token_account.owner,
+        ];
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            amount,
+            now,
+        )?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &parties,
+        )?;
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                amount,
+                now,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &parties)?;
+        }
+
+        transfer_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.recipient_token_account,
+            &ctx.accounts.wallet,
+            amount,
+        )?;
+
+        let receipt = &ctx.accounts.receipt;
+        emit!(Refunded {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            recipient: ctx.accounts.recipient_token_account.owner,
+            mint: Some(mint),
+            amount,
+            receipt: receipt.key(),
+            refunded: receipt.refunded,
+        });
+
+        Ok(())
+    }
+
+    /// Send one SOL payment split across recipients (remaining_accounts, in order of `shares_bps`);
+    /// shares must total 10_000 bps. The fee is charged on top. Any trailing accounts are references
+    pub fn send_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendSolBatch<'info>>,
+        amount: u64,
+        shares_bps: Vec<u16>,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        let amounts = split_by_shares(amount, &shares_bps)?;
+        let agent_id = ctx.accounts.wallet.agent_id.clone();
+        send_sol_batch(ctx, amounts, memo, category, idempotency_key)?;
+
+        emit!(PaymentSplit {
+            agent_id,
+            mint: None,
+            amount,
+            shares_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Send one SPL token payment split across recipient token accounts (remaining_accounts, in order of
+    /// `shares_bps`); shares must total 10_000 bps. The fee is charged on top
+    pub fn send_token_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendTokenBatch<'info>>,
+        amount: u64,
+        shares_bps: Vec<u16>,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        let amounts = split_by_shares(amount, &shares_bps)?;
+        let agent_id = ctx.accounts.wallet.agent_id.clone();
+        let mint = ctx.accounts.mint.key();
+        send_token_batch(ctx, amounts, memo, category, idempotency_key)?;
+
+        emit!(PaymentSplit {
+            agent_id,
+            mint: Some(mint),
+            amount,
+            shares_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: register a standing split (total 10_000 bps) applied to incoming invoice settlements
+    /// and claimable payments
+    pub fn create_revenue_share(
+        ctx: Context<CreateRevenueShare>,
+        shares: Vec<RevenueShareEntry>,
+    ) -> Result<()> {
+        let revenue_share = &mut ctx.accounts.revenue_share;
+        revenue_share.set_shares(shares)?;
+        revenue_share.wallet = ctx.accounts.wallet.key();
+        revenue_share.updated_at = Clock::get()?.unix_timestamp;
+        revenue_share.bump = *ctx.bumps.get("revenue_share").unwrap();
+        ctx.accounts.wallet.has_revenue_share = true;
+
+        emit!(RevenueShareSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            shares: revenue_share.shares.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Owner: replace the wallet's revenue share recipients
+    pub fn update_revenue_share(
+        ctx: Context<UpdateRevenueShare>,
+        shares: Vec<RevenueShareEntry>,
+    ) -> Result<()> {
+        let revenue_share = &mut ctx.accounts.revenue_share;
+        revenue_share.set_shares(shares)?;
+        revenue_share.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(RevenueShareSet {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            shares: revenue_share.shares.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Owner: drop the revenue share; incoming payments go to the wallet again
+    pub fn close_revenue_share(ctx: Context<CloseRevenueShare>) -> Result<()> {
+        ctx.accounts.wallet.has_revenue_share = false;
+
+        emit!(RevenueShareClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Receiving wallet's owner: collect a SOL claimable addressed to the wallet itself, divided by its
+    /// revenue share (payees as remaining_accounts, in share order) when one is registered
+    pub fn claim_transfer_to_wallet<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimTransferToWallet<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.claimable.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.claimable.expires_at,
+            ClawWalletError::ClaimExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+        let (payees, cuts, _) = revenue_split(
+            &ctx.accounts.recipient_wallet,
+            &ctx.accounts.revenue_share,
+            ctx.remaining_accounts,
+            None,
+            send_amount,
+        )?;
+
+        let claimable_info = ctx.accounts.claimable.to_account_info();
+        if payees.is_empty() {
+            transfer_lamports(
+                &claimable_info,
+                &ctx.accounts.recipient_wallet.to_account_info(),
+                send_amount,
+            )?;
+        } else {
+            for (payee, cut) in payees.iter().zip(cuts.iter()) {
+                transfer_lamports(&claimable_info, payee, *cut)?;
+            }
+            emit!(RevenueShared {
+                wallet: ctx.accounts.recipient_wallet.key(),
+                source: ctx.accounts.claimable.key(),
+                mint: None,
+                amount: send_amount,
+                recipients: payees.iter().map(|p| p.key()).collect(),
+                amounts: cuts,
+            });
+        }
+        transfer_lamports(
+            &claimable_info,
+            &ctx.accounts.treasury.to_account_info(),
+            fee,
+        )?;
+
+        emit!(ClaimableClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: None,
+            amount: send_amount,
+            fee,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Receiving wallet's owner: collect a token claimable addressed to the wallet itself, divided by its
+    /// revenue share (payee token accounts as remaining_accounts, in share order) when one is registered
+    pub fn claim_token_transfer_to_wallet<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimTokenTransferToWallet<'info>>,
+    ) -> Result<()> {
+        let mint = ctx.accounts.vault.mint;
+        require!(
+            ctx.accounts.claimable.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.claimable.expires_at,
+            ClawWalletError::ClaimExpired
+        );
+        let amount = ctx.accounts.claimable.amount;
+        let fee = ctx
+            .accounts
+            .config
+            .token_fee_for(amount, ctx.accounts.wallet.points);
+        let send_amount = amount - fee;
+        let (payees, cuts, _) = revenue_split(
+            &ctx.accounts.recipient_wallet,
+            &ctx.accounts.revenue_share,
+            ctx.remaining_accounts,
+            Some(mint),
+            send_amount,
+        )?;
+
+        if payees.is_empty() {
+            claimable_transfer_token(
+                &ctx.accounts.token_program,
+                &ctx.accounts.claimable,
+                &ctx.accounts.vault,
+                ctx.accounts.recipient_token_account.to_account_info(),
+                send_amount,
+            )?;
+        } else {
+            for (payee, cut) in payees.iter().zip(cuts.iter()) {
+                claimable_transfer_token(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.claimable,
+                    &ctx.accounts.vault,
+                    payee.clone(),
+                    *cut,
+                )?;
+            }
+            emit!(RevenueShared {
+                wallet: ctx.accounts.recipient_wallet.key(),
+                source: ctx.accounts.claimable.key(),
+                mint: Some(mint),
+                amount: send_amount,
+                recipients: payees.iter().map(|p| p.key()).collect(),
+                amounts: cuts,
+            });
+        }
+        claimable_transfer_token(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            fee,
+        )?;
+        close_claimable_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.claimable,
+            &ctx.accounts.vault,
+            ctx.accounts.rent_payer.to_account_info(),
+        )?;
+
+        emit!(ClaimableClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            nonce: ctx.accounts.claimable.nonce,
+            recipient: ctx.accounts.claimable.recipient,
+            mint: Some(mint),
+            amount: send_amount,
+            fee,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CLAIMABLE);
+
+        Ok(())
+    }
+
+    /// Tip another agent a small amount of SOL: the percentage fee without the minimum-fee floor (at
+    /// least the flat `tip_fee`) on top, so splitting a payment into tips saves nothing, and a fixed
+    /// points reward
+    pub fn tip(ctx: Context<Tip>, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= MAX_TIP_AMOUNT,
+            ClawWalletError::InvalidAmount
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let recipient = ctx.accounts.recipient.key();
+        let config = &ctx.accounts.config;
+        let fee = config
+            .fee_at(amount, config.fee_bps_for(ctx.accounts.wallet.points))
+            .max(config.tip_fee);
+        let outflow = amount
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            outflow,
+            now,
+        )?;
+        ctx.accounts.wallet.record_outflow(outflow, now)?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[recipient],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                outflow,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[recipient])?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, outflow)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        // Tips skip tx stats and streaks; points still go through the repeat-recipient and daily caps
+        let config = &ctx.accounts.config;
+        let points_earned = ctx
+            .accounts
+            .wallet
+            .earn_points(TIP_POINTS, amount, 0, recipient, now, config);
+
+        emit!(TipSent {
+            wallet: wallet_info.key(),
+            recipient,
+            amount,
+            points_earned,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: set the flat minimum fee charged on tips
+    pub fn set_tip_fee(ctx: Context<AdminConfig>, tip_fee: u64) -> Result<()> {
+        require!(tip_fee <= MAX_TIP_FEE, ClawWalletError::FeeTooHigh);
+        ctx.accounts.config.tip_fee = tip_fee;
+
+        emit!(TipFeeSet {
+            admin: ctx.accounts.admin.key(),
+            tip_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: open a crowdfund raising `goal` lamports by `deadline`
+    pub fn create_crowdfund(ctx: Context<CreateCrowdfund>, goal: u64, deadline: i64) -> Result<()> {
+        require!(goal > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(deadline > now, ClawWalletError::InvalidCrowdfund);
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.crowdfund_nonce;
+        wallet.crowdfund_nonce += 1;
+        wallet.open_object(OPEN_CROWDFUND)?;
+
+        let crowdfund = &mut ctx.accounts.crowdfund;
+        crowdfund.wallet = wallet.key();
+        crowdfund.nonce = nonce;
+        crowdfund.goal = goal;
+        crowdfund.deadline = deadline;
+        crowdfund.raised = 0;
+        crowdfund.refund_pool = 0;
+        crowdfund.open_contributions = 0;
+        crowdfund.claimed = false;
+        crowdfund.created_at = now;
+        crowdfund.bump = *ctx.bumps.get("crowdfund").unwrap();
+
+        emit!(CrowdfundCreated {
+            agent_id: wallet.agent_id.clone(),
+            crowdfund: crowdfund.key(),
+            nonce,
+            goal,
+            deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: contribute SOL from this wallet to another agent's crowdfund before its deadline
+    pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let crowdfund_wallet = ctx.accounts.crowdfund.wallet;
+        require!(
+            ctx.accounts.crowdfund.is_open(now),
+            ClawWalletError::CrowdfundEnded
+        );
+        require!(
+            crowdfund_wallet != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidCrowdfund
+        );
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[crowdfund_wallet],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.crowdfund.to_account_info(),
+            amount,
+        )?;
+
+        let crowdfund = &mut ctx.accounts.crowdfund;
+        let contribution = &mut ctx.accounts.contribution;
+        if contribution.amount == 0 {
+            contribution.crowdfund = crowdfund.key();
+            contribution.wallet = wallet_info.key();
+            contribution.rent_payer = ctx.accounts.owner.key();
+            contribution.bump = *ctx.bumps.get("contribution").unwrap();
+            crowdfund.open_contributions = crowdfund
+                .open_contributions
+                .checked_add(1)
+                .ok_or(ClawWalletError::MathOverflow)?;
+        }
+        contribution.amount = contribution
+            .amount
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        crowdfund.raised = crowdfund
+            .raised
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(CrowdfundContributed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            crowdfund: crowdfund.key(),
+            amount,
+            raised: crowdfund.raised,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: collect a crowdfund that reached its goal into the wallet (protocol fee deducted), at the
+    /// latest CROWDFUND_CLAIM_WINDOW after its deadline
+    pub fn claim_crowdfund(ctx: Context<ClaimCrowdfund>) -> Result<()> {
+        let crowdfund = &ctx.accounts.crowdfund;
+        require!(!crowdfund.claimed, ClawWalletError::CrowdfundEnded);
+        require!(
+            crowdfund.raised >= crowdfund.goal,
+            ClawWalletError::GoalNotReached
+        );
+        require!(
+            !crowdfund.has_failed(Clock::get()?.unix_timestamp),
+            ClawWalletError::CrowdfundEnded
+        );
+        let raised = crowdfund.raised;
+        let fee = ctx
+            .accounts
+            .config
+            .sol_fee_for(raised, ctx.accounts.wallet.points);
+
+        let crowdfund_info = ctx.accounts.crowdfund.to_account_info();
+        transfer_lamports(
+            &crowdfund_info,
+            &ctx.accounts.wallet.to_account_info(),
+            raised - fee,
+        )?;
+        transfer_lamports(
+            &crowdfund_info,
+            &ctx.accounts.treasury.to_account_info(),
+            fee,
+        )?;
+        ctx.accounts.crowdfund.claimed = true;
+
+        emit!(CrowdfundClaimed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            crowdfund: crowdfund_info.key(),
+            amount: raised - fee,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    /// Contributor's owner: close a contribution. If the crowdfund missed its goal by the deadline (or
+    /// went unclaimed through the claim window) the contributor's pro-rata share of the pool is returned first
+    pub fn close_contribution(ctx: Context<CloseContribution>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.contribution.amount;
+        let crowdfund = &mut ctx.accounts.crowdfund;
+
+        let refunded = if crowdfund.claimed {
+            0
+        } else {
+            require!(crowdfund.has_failed(now), ClawWalletError::CrowdfundActive);
+            if crowdfund.refund_pool == 0 {
+                let info = crowdfund.to_account_info();
+                let rent = Rent::get()?.minimum_balance(info.data_len());
+                crowdfund.refund_pool = info.lamports().saturating_sub(rent);
+            }
+            let refund =
+                (amount as u128 * crowdfund.refund_pool as u128 / crowdfund.raised as u128) as u64;
+            transfer_lamports(
+                &crowdfund.to_account_info(),
+                &ctx.accounts.wallet.to_account_info(),
+                refund,
+            )?;
+            refund
+        };
+        crowdfund.open_contributions -= 1;
+
+        emit!(ContributionClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            crowdfund: crowdfund.key(),
+            contributed: amount,
+            refunded,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: close a claimed or failed crowdfund once every contribution has been closed
+    pub fn close_crowdfund(ctx: Context<CloseCrowdfund>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let crowdfund = &ctx.accounts.crowdfund;
+        require!(
+            crowdfund.claimed || crowdfund.has_failed(now),
+            ClawWalletError::CrowdfundActive
+        );
+        require!(
+            crowdfund.open_contributions == 0,
+            ClawWalletError::CrowdfundActive
+        );
+
+        emit!(CrowdfundClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            crowdfund: crowdfund.key(),
+            claimed: crowdfund.claimed,
+        });
+        ctx.accounts.wallet.close_object(OPEN_CROWDFUND);
+
+        Ok(())
+    }
+
+    /// Owner: lock `reward` lamports against a task (identified by `task_hash`) for any agent to claim
+    pub fn post_bounty(
+        ctx: Context<PostBounty>,
+        reward: u64,
+        task_hash: [u8; 32],
+        expires_at: Option<i64>, // None = never
+    ) -> Result<()> {
+        require!(reward > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = expires_at.unwrap_or(0);
+        require!(
+            expires_at == 0 || expires_at > now,
+            ClawWalletError::InvalidBounty
+        );
+
+        ctx.accounts.wallet.record_outflow(reward, now)?;
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, reward)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.bounty.to_account_info(), reward)?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.bounty_nonce;
+        wallet.bounty_nonce = nonce.checked_add(1).ok_or(ClawWalletError::MathOverflow)?;
+        wallet.open_object(OPEN_BOUNTY)?;
+
+        let bounty = &mut ctx.accounts.bounty;
+        bounty.wallet = wallet.key();
+        bounty.nonce = nonce;
+        bounty.reward = reward;
+        bounty.task_hash = task_hash;
+        bounty.expires_at = expires_at;
+        bounty.status = BOUNTY_OPEN;
+        bounty.winner = Pubkey::default();
+        bounty.open_claims = 0;
+        bounty.created_at = now;
+        bounty.bump = *ctx.bumps.get("bounty").unwrap();
+
+        emit!(BountyPosted {
+            agent_id: wallet.agent_id.clone(),
+            bounty: bounty.key(),
+            nonce,
+            reward,
+            task_hash,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Claimant's owner: submit work against an open bounty
+    pub fn submit_bounty_claim(
+        ctx: Context<SubmitBountyClaim>,
+        submission_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let bounty = &mut ctx.accounts.bounty;
+        require!(bounty.status == BOUNTY_OPEN, ClawWalletError::BountyClosed);
+        require!(
+            bounty.expires_at == 0 || now < bounty.expires_at,
+            ClawWalletError::BountyClosed
+        );
+        require!(
+            bounty.wallet != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidBounty
+        );
+        bounty.open_claims = bounty
+            .open_claims
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let claim = &mut ctx.accounts.claim;
+        claim.bounty = bounty.key();
+        claim.wallet = ctx.accounts.wallet.key();
+        claim.submission_hash = submission_hash;
+        claim.submitted_at = now;
+        claim.bump = *ctx.bumps.get("claim").unwrap();
+
+        emit!(BountyClaimSubmitted {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            bounty: bounty.key(),
+            submission_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Poster's owner: award the bounty to a claimant; the reward (less the fee) goes to their wallet and
+    /// both sides earn points
+    pub fn award_bounty(ctx: Context<AwardBounty>) -> Result<()> {
+        require!(
+            ctx.accounts.bounty.status == BOUNTY_OPEN,
+            ClawWalletError::BountyClosed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let reward = ctx.accounts.bounty.reward;
+        let winner = ctx.accounts.claimant.key();
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), winner],
+            None,
+            reward,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+
+        let bounty_info = ctx.accounts.bounty.to_account_info();
+        transfer_lamports(
+            &bounty_info,
+            &ctx.accounts.claimant.to_account_info(),
+            reward - fee,
+        )?;
+        transfer_lamports(&bounty_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let config = &ctx.accounts.config;
+        let points = config.sol_points_for(reward);
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let points_earned = wallet.earn_points(
+            points,
+            reward,
+            config.min_earn_lamports,
+            winner,
+            now,
+            config,
+        );
+        ctx.accounts.claimant.add_points(points, config);
+
+        let bounty = &mut ctx.accounts.bounty;
+        bounty.status = BOUNTY_AWARDED;
+        bounty.winner = winner;
+
+        emit!(BountyAwarded {
+            agent_id: wallet.agent_id.clone(),
+            bounty: bounty.key(),
+            winner,
+            amount: reward - fee,
+            fee,
+            poster_points: points_earned,
+            winner_points: points,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: withdraw an unawarded bounty once it has no pending claims or has expired
+    pub fn cancel_bounty(ctx: Context<CancelBounty>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let bounty = &ctx.accounts.bounty;
+        require!(bounty.status == BOUNTY_OPEN, ClawWalletError::BountyClosed);
+        require!(
+            bounty.open_claims == 0 || (bounty.expires_at != 0 && now >= bounty.expires_at),
+            ClawWalletError::BountyHasClaims
+        );
+        let reward = bounty.reward;
+        transfer_lamports(
+            &ctx.accounts.bounty.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            reward,
+        )?;
+        ctx.accounts.bounty.status = BOUNTY_CANCELLED;
+
+        emit!(BountyCancelled {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            bounty: ctx.accounts.bounty.key(),
+            reward,
+        });
+
+        Ok(())
+    }
+
+    /// Claimant's owner: withdraw a claim, or clean it up after the bounty is settled
+    pub fn close_bounty_claim(ctx: Context<CloseBountyClaim>) -> Result<()> {
+        ctx.accounts.bounty.open_claims -= 1;
+
+        Ok(())
+    }
+
+    /// Owner: close a settled bounty once every claim has been closed
+    pub fn close_bounty(ctx: Context<CloseBounty>) -> Result<()> {
+        let bounty = &ctx.accounts.bounty;
+        require!(bounty.status != BOUNTY_OPEN, ClawWalletError::BountyClosed);
+        require!(bounty.open_claims == 0, ClawWalletError::BountyHasClaims);
+        ctx.accounts.wallet.close_object(OPEN_BOUNTY);
+
+        Ok(())
+    }
+
+    /// Owner: advertise a service at a fixed price in SOL or `mint`
+    pub fn create_listing(
+        ctx: Context<CreateListing>,
+        mint: Option<Pubkey>,
+        price: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(price > 0, ClawWalletError::InvalidAmount);
+        require!(
+            metadata_uri.len() <= MAX_METADATA_URI_LEN,
+            ClawWalletError::InvalidListing
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        let wallet = &mut ctx.accounts.wallet;
+        let nonce = wallet.listing_nonce;
+        wallet.listing_nonce += 1;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.wallet = wallet.key();
+        listing.nonce = nonce;
+        listing.mint = mint;
+        listing.price = price;
+        listing.metadata_uri = metadata_uri;
+        listing.active = true;
+        listing.orders = 0;
+        listing.created_at = now;
+        listing.bump = *ctx.bumps.get("listing").unwrap();
+
+        emit!(ListingUpdated {
+            agent_id: wallet.agent_id.clone(),
+            listing: listing.key(),
+            mint,
+            price,
+            metadata_uri: listing.metadata_uri.clone(),
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: change a listing's price or metadata, or take it off the market
+    pub fn update_listing(
+        ctx: Context<UpdateListing>,
+        price: Option<u64>,
+        metadata_uri: Option<String>,
+        active: Option<bool>,
+    ) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        if let Some(price) = price {
+            require!(price > 0, ClawWalletError::InvalidAmount);
+            listing.price = price;
+        }
+        if let Some(metadata_uri) = metadata_uri {
+            require!(
+                metadata_uri.len() <= MAX_METADATA_URI_LEN,
+                ClawWalletError::InvalidListing
+            );
+            listing.metadata_uri = metadata_uri;
+        }
+        if let Some(active) = active {
+            listing.active = active;
+        }
+
+        emit!(ListingUpdated {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            listing: listing.key(),
+            mint: listing.mint,
+            price: listing.price,
+            metadata_uri: listing.metadata_uri.clone(),
+            active: listing.active,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: delete a listing and reclaim its rent
+    pub fn close_listing(ctx: Context<CloseListing>) -> Result<()> {
+        emit!(ListingClosed {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            listing: ctx.accounts.listing.key(),
+            orders: ctx.accounts.listing.orders,
+        });
+
+        Ok(())
+    }
+
+    /// Buy a SOL-priced service from this wallet; the protocol fee is charged on top of the price
+    pub fn purchase_service(ctx: Context<PurchaseService>, max_price: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let listing = &ctx.accounts.listing;
+        require!(
+            listing.mint.is_none(),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(listing.active, ClawWalletError::ListingInactive);
+        require!(listing.price <= max_price, ClawWalletError::PriceChanged);
+        let seller = ctx.accounts.seller.key();
+        require!(
+            seller != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidListing
+        );
+        let price = listing.price;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), seller],
+            None,
+            price,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let outflow = price
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            outflow,
+            now,
+        )?;
+        ctx.accounts.wallet.record_outflow(outflow, now)?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[seller],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                outflow,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[seller])?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, outflow)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.seller.to_account_info(), price)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.sol_points_for(price);
+        let points_earned = wallet.earn_points(
+            base_points,
+            price,
+            config.min_earn_lamports,
+            seller,
+            now,
+            config,
+        );
+
+        let listing = &mut ctx.accounts.listing;
+        let order_id = listing.orders;
+        listing.orders += 1;
+
+        emit!(ServiceOrdered {
+            listing: listing.key(),
+            order_id,
+            seller,
+            buyer: wallet.key(),
+            mint: None,
+            price,
+            fee,
+            points_earned,
+            transfer_fee: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Buy an SPL Token or Token-2022 priced service from this wallet; the protocol fee is charged on top of the
+    /// price. remaining_accounts are the mint's transfer-hook accounts, if it has a hook. A transfer-fee mint
+    /// withholds its fee from what the seller receives
+    pub fn purchase_service_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseServiceToken<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.mint.key();
+        let (hook_accounts, _) =
+            transfer_hook_accounts(&ctx.accounts.mint.to_account_info(), ctx.remaining_accounts)?;
+        let listing = &ctx.accounts.listing;
+        require!(
+            listing.mint == Some(mint),
+            ClawWalletError::WrongTransactionKind
+        );
+        require!(listing.active, ClawWalletError::ListingInactive);
+        require!(listing.price <= max_price, ClawWalletError::PriceChanged);
+        let seller = listing.wallet;
+        require!(
+            seller != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidListing
+        );
+        let price = listing.price;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), seller],
+            Some(mint),
+            price,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let outflow = price
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let parties = [seller, ctx.accounts.seller_token_account.key()];
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            outflow,
+            now,
+        )?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &parties,
+        )?;
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                outflow,
+                now,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &parties)?;
+        }
+
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.seller_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            price,
+        )?;
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            fee,
+        )?;
+        let transfer_fee = mint_transfer_fee(&ctx.accounts.mint.to_account_info(), price)?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.token_points_for(&mint, price);
+        let points_earned = wallet.earn_points(
+            base_points,
+            price,
+            config.min_earn_token,
+            seller,
+            now,
+            config,
+        );
+
+        let listing = &mut ctx.accounts.listing;
+        let order_id = listing.orders;
+        listing.orders += 1;
+
+        emit!(ServiceOrdered {
+            listing: listing.key(),
+            order_id,
+            seller,
+            buyer: wallet.key(),
+            mint: Some(mint),
+            price,
+            fee,
+            points_earned,
+            transfer_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Consumer's owner: prepay SOL credits with a provider agent (creates the credit account on first use)
+    pub fn deposit_credits(ctx: Context<DepositCredits>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let provider = ctx.accounts.provider.key();
+        require!(
+            provider != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidCredits
+        );
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[provider],
+        )?;
+        ctx.accounts.wallet.record_outflow(amount, now)?;
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, amount)?;
+        transfer_lamports(
+            &wallet_info,
+            &ctx.accounts.credits.to_account_info(),
+            amount,
+        )?;
+
+        let credits = &mut ctx.accounts.credits;
+        if credits.created_at == 0 {
+            ctx.accounts.wallet.open_object(OPEN_CREDITS)?;
+            credits.consumer = wallet_info.key();
+            credits.provider = provider;
+            credits.rent_payer = ctx.accounts.owner.key();
+            credits.created_at = now;
+            credits.bump = *ctx.bumps.get("credits").unwrap();
+        }
+        credits.balance = credits
+            .balance
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(CreditsDeposited {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            provider,
+            amount,
+            balance: credits.balance,
+        });
+
+        Ok(())
+    }
+
+    /// Provider (owner or agent key): debit metered usage from a consumer's prepaid balance into the
+    /// provider wallet, protocol fee deducted at the consumer's tier
+    pub fn consume_credits(
+        ctx: Context<ConsumeCredits>,
+        amount: u64,
+        usage_ref: [u8; 16],
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        require!(
+            amount <= ctx.accounts.credits.balance,
+            ClawWalletError::InsufficientCredits
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.consumer.key(),
+            &[ctx.accounts.consumer.key(), ctx.accounts.credits.provider],
+            None,
+            amount,
+            ctx.accounts
+                .config
+                .fee_bps_for(ctx.accounts.consumer.points),
+            now,
+        )?;
+
+        let credits_info = ctx.accounts.credits.to_account_info();
+        transfer_lamports(
+            &credits_info,
+            &ctx.accounts.provider.to_account_info(),
+            amount - fee,
+        )?;
+        transfer_lamports(&credits_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let credits = &mut ctx.accounts.credits;
+        credits.balance -= amount;
+        credits.consumed = credits
+            .consumed
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(CreditsConsumed {
+            credits: credits.key(),
+            consumer: credits.consumer,
+            provider: credits.provider,
+            amount: amount - fee,
+            fee,
+            balance: credits.balance,
+            usage_ref,
+        });
+
+        Ok(())
+    }
+
+    /// Consumer's owner: give notice to withdraw `amount` of unused credits; withdraw_credits can take it
+    /// once CREDIT_WITHDRAW_NOTICE has passed. Replaces any pending request
+    pub fn request_withdraw_credits(ctx: Context<WithdrawCredits>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let withdraw_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(CREDIT_WITHDRAW_NOTICE)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let credits = &mut ctx.accounts.credits;
+        credits.withdraw_requested = amount;
+        credits.withdraw_at = withdraw_at;
+
+        emit!(CreditsWithdrawRequested {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            provider: credits.provider,
+            amount,
+            withdraw_at,
+        });
+
+        Ok(())
+    }
+
+    /// Consumer's owner: take the requested credits back into the wallet after the notice period;
+    /// unsettled usage stays covered
+    pub fn withdraw_credits(ctx: Context<WithdrawCredits>) -> Result<()> {
+        let credits = &ctx.accounts.credits;
+        require!(
+            credits.withdraw_at != 0,
+            ClawWalletError::NoWithdrawalRequested
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= credits.withdraw_at,
+            ClawWalletError::TimelockNotElapsed
+        );
+        let amount = credits.withdraw_requested;
+        require!(
+            amount <= credits.balance.saturating_sub(credits.owed()?),
+            ClawWalletError::InsufficientCredits
+        );
+        transfer_lamports(
+            &ctx.accounts.credits.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            amount,
+        )?;
+
+        let credits = &mut ctx.accounts.credits;
+        credits.balance -= amount;
+        credits.withdraw_requested = 0;
+        credits.withdraw_at = 0;
+
+        emit!(CreditsWithdrawn {
+            agent_id: ctx.accounts.wallet.agent_id.clone(),
+            provider: credits.provider,
+            amount,
+            balance: credits.balance,
+        });
+
+        Ok(())
+    }
+
+    /// Consumer's owner: close an empty credit account and reclaim its rent
+    pub fn close_credits(ctx: Context<CloseCredits>) -> Result<()> {
+        require!(
+            ctx.accounts.credits.balance == 0,
+            ClawWalletError::CreditsNotEmpty
+        );
+        require!(
+            ctx.accounts.credits.owed()? == 0,
+            ClawWalletError::UsageUnsettled
+        );
+        ctx.accounts.wallet.close_object(OPEN_CREDITS);
+
+        Ok(())
+    }
+
+    /// Consumer's owner: agree the postpaid unit price and the most the provider may bill per day;
+    /// only while nothing is owed
+    pub fn set_usage_terms(
+        ctx: Context<SetUsageTerms>,
+        unit_price: u64,
+        usage_cap: u64,
+    ) -> Result<()> {
+        let credits = &mut ctx.accounts.credits;
+        require!(credits.owed()? == 0, ClawWalletError::UsageUnsettled);
+        credits.unit_price = unit_price;
+        credits.usage_cap = usage_cap;
+
+        emit!(UsageTermsSet {
+            credits: credits.key(),
+            unit_price,
+            usage_cap,
+        });
+
+        Ok(())
+    }
+
+    /// Provider (owner or agent key): add `units` to the append-only usage counter, up to the agreed daily
+    /// cap and the prepaid balance
+    pub fn record_usage(ctx: Context<RecordUsage>, units: u64) -> Result<()> {
+        require!(units > 0, ClawWalletError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let credits = &mut ctx.accounts.credits;
+        require!(credits.unit_price > 0, ClawWalletError::InvalidCredits);
+        let billed = units
+            .checked_mul(credits.unit_price)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        // The cap counts everything billed in the period, so settling doesn't free it up again
+        let cap_used = roll_window(
+            &mut credits.period_start,
+            &mut credits.cap_used,
+            billed,
+            now,
+        )?;
+        require!(
+            cap_used <= credits.usage_cap,
+            ClawWalletError::UsageCapExceeded
+        );
+        credits.usage_units = credits
+            .usage_units
+            .checked_add(units)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        let owed = credits.owed()?;
+        require!(
+            owed <= credits.balance,
+            ClawWalletError::InsufficientCredits
+        );
+
+        emit!(UsageRecorded {
+            credits: credits.key(),
+            units,
+            usage_units: credits.usage_units,
+            owed,
+        });
+
+        Ok(())
+    }
+
+    /// Provider (owner or agent key): collect the amount owed for unsettled usage from the consumer's
+    /// prepaid balance, protocol fee deducted at the consumer's tier
+    pub fn settle_usage(ctx: Context<SettleUsage>) -> Result<()> {
+        let owed = ctx.accounts.credits.owed()?;
+        require!(owed > 0, ClawWalletError::NothingToWithdraw);
+        require!(
+            owed <= ctx.accounts.credits.balance,
+            ClawWalletError::InsufficientCredits
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.consumer.key(),
+            &[ctx.accounts.consumer.key(), ctx.accounts.credits.provider],
+            None,
+            owed,
+            ctx.accounts
+                .config
+                .fee_bps_for(ctx.accounts.consumer.points),
+            now,
+        )?;
+
+        let credits_info = ctx.accounts.credits.to_account_info();
+        transfer_lamports(
+            &credits_info,
+            &ctx.accounts.provider.to_account_info(),
+            owed - fee,
+        )?;
+        transfer_lamports(&credits_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let credits = &mut ctx.accounts.credits;
+        let units = credits.usage_units - credits.settled_units;
+        credits.settled_units = credits.usage_units;
+        credits.balance -= owed;
+        credits.consumed = credits
+            .consumed
+            .checked_add(owed)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        emit!(UsageSettled {
+            credits: credits.key(),
+            consumer: credits.consumer,
+            provider: credits.provider,
+            units,
+            amount: owed - fee,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: open a payment channel with `counterparty`, depositing `deposit` lamports. `signer` is the key
+    /// that signs this side's off-chain balance updates
+    pub fn open_channel(
+        ctx: Context<OpenChannel>,
+        signer: Pubkey,
+        deposit: u64,
+        challenge_period: i64,
+    ) -> Result<()> {
+        require!(
+            challenge_period > 0 && challenge_period <= MAX_CHANNEL_CHALLENGE_PERIOD,
+            ClawWalletError::InvalidChannel
+        );
+        require!(signer != Pubkey::default(), ClawWalletError::InvalidChannel);
+        let counterparty = ctx.accounts.counterparty.key();
+        require!(
+            counterparty != ctx.accounts.wallet.key(),
+            ClawWalletError::InvalidChannel
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[counterparty],
+        )?;
+        if deposit > 0 {
+            ctx.accounts.wallet.record_outflow(deposit, now)?;
+            let wallet_info = ctx.accounts.wallet.to_account_info();
+            require_spendable(&wallet_info, deposit)?;
+            transfer_lamports(
+                &wallet_info,
+                &ctx.accounts.channel.to_account_info(),
+                deposit,
+            )?;
+        }
+
+        let epoch = ctx.accounts.wallet.channel_nonce;
+        ctx.accounts.wallet.channel_nonce += 1;
+        ctx.accounts.wallet.open_object(OPEN_CHANNEL)?;
+
+        let channel = &mut ctx.accounts.channel;
+        channel.wallet_a = ctx.accounts.wallet.key();
+        channel.wallet_b = counterparty;
+        channel.signer_a = signer;
+        channel.signer_b = Pubkey::default();
+        channel.deposit_a = deposit;
+        channel.deposit_b = 0;
+        channel.state_nonce = 0;
+        channel.balance_a = deposit;
+        channel.balance_b = 0;
+        channel.challenge_period = challenge_period;
+        channel.closing_at = 0;
+        channel.rent_payer = ctx.accounts.owner.key();
+        channel.opened_at = now;
+        channel.bump = *ctx.bumps.get("channel").unwrap();
+        channel.epoch = epoch;
+
+        emit!(ChannelOpened {
+            channel: channel.key(),
+            wallet_a: channel.wallet_a,
+            wallet_b: counterparty,
+            deposit_a: deposit,
+            challenge_period,
+            epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Counterparty's owner: join a channel opened to this wallet, registering a signer and an optional deposit
+    pub fn join_channel(ctx: Context<JoinChannel>, signer: Pubkey, deposit: u64) -> Result<()> {
+        require!(signer != Pubkey::default(), ClawWalletError::InvalidChannel);
+        let channel = &ctx.accounts.channel;
+        require!(
+            channel.signer_b == Pubkey::default(),
+            ClawWalletError::InvalidChannel
+        );
+        require!(channel.closing_at == 0, ClawWalletError::ChannelClosing);
+        let now = Clock::get()?.unix_timestamp;
+
+        // The joining wallet's lists apply to the opener just as the opener's applied to it
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[channel.wallet_a],
+        )?;
+        if deposit > 0 {
+            ctx.accounts.wallet.record_outflow(deposit, now)?;
+            let wallet_info = ctx.accounts.wallet.to_account_info();
+            require_spendable(&wallet_info, deposit)?;
+            transfer_lamports(
+                &wallet_info,
+                &ctx.accounts.channel.to_account_info(),
+                deposit,
+            )?;
+        }
+
+        ctx.accounts.wallet.open_object(OPEN_CHANNEL)?;
+
+        let channel = &mut ctx.accounts.channel;
+        channel.signer_b = signer;
+        channel.deposit_b = deposit;
+        channel.balance_b = deposit;
+
+        emit!(ChannelJoined {
+            channel: channel.key(),
+            wallet_b: channel.wallet_b,
+            deposit_b: deposit,
+        });
+
+        Ok(())
+    }
+
+    /// Both parties (owner or agent key of each wallet): settle the agreed final balances at once and close
+    pub fn close_channel(ctx: Context<CloseChannel>, balance_a: u64, balance_b: u64) -> Result<()> {
+        let channel = &mut ctx.accounts.channel;
+        channel.require_balanced(balance_a, balance_b)?;
+        channel.balance_a = balance_a;
+        channel.balance_b = balance_b;
+        let fee = payout_channel(
+            channel,
+            &ctx.accounts.wallet_a,
+            &ctx.accounts.wallet_b,
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(ChannelSettled {
+            channel: channel.key(),
+            state_nonce: channel.state_nonce,
+            balance_a,
+            balance_b,
+            fee,
+            cooperative: true,
+        });
+        ctx.accounts.wallet_a.close_object(OPEN_CHANNEL);
+        if ctx.accounts.channel.signer_b != Pubkey::default() {
+            ctx.accounts.wallet_b.close_object(OPEN_CHANNEL);
+        }
+
+        Ok(())
+    }
+
+    /// Either party (owner or agent key): submit the latest balance update signed by the other side's
+    /// signer (an Ed25519 program instruction earlier in the transaction), starting or contesting the
+    /// challenge period. Nonce 0 is the opening deposits and needs no signature
+    pub fn settle_channel(
+        ctx: Context<SettleChannel>,
+        state_nonce: u64,
+        balance_a: u64,
+        balance_b: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let wallet = ctx.accounts.wallet.key();
+        let channel = &ctx.accounts.channel;
+        require!(
+            channel.closing_at == 0 || now < channel.closing_at,
+            ClawWalletError::ChannelClosing
+        );
+        channel.require_balanced(balance_a, balance_b)?;
+        let counterparty_signer = if wallet == channel.wallet_a {
+            channel.signer_b
+        } else {
+            channel.signer_a
+        };
+
+        if state_nonce == 0 {
+            require!(
+                channel.closing_at == 0
+                    && balance_a == channel.deposit_a
+                    && balance_b == channel.deposit_b,
+                ClawWalletError::InvalidChannelState
+            );
+        } else {
+            require!(
+                state_nonce > channel.state_nonce,
+                ClawWalletError::InvalidChannelState
+            );
+            require!(
+                counterparty_signer != Pubkey::default(),
+                ClawWalletError::InvalidChannelState
+            );
+            let message = channel_state_message(
+                &channel.key(),
+                channel.epoch,
+                state_nonce,
+                balance_a,
+                balance_b,
+            );
+            require_ed25519_signature(&ctx.accounts.instructions, &counterparty_signer, &message)?;
+        }
+
+        let channel = &mut ctx.accounts.channel;
+        channel.state_nonce = state_nonce;
+        channel.balance_a = balance_a;
+        channel.balance_b = balance_b;
+        if channel.closing_at == 0 {
+            channel.closing_at = now
+                .checked_add(channel.challenge_period)
+                .ok_or(ClawWalletError::MathOverflow)?;
+        }
+
+        emit!(ChannelStateSubmitted {
+            channel: channel.key(),
+            submitted_by: wallet,
+            state_nonce,
+            balance_a,
+            balance_b,
+            closing_at: channel.closing_at,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: pay out a channel's last submitted state once its challenge period is over
+    pub fn finalize_channel(ctx: Context<FinalizeChannel>) -> Result<()> {
+        let channel = &ctx.accounts.channel;
+        require!(
+            channel.closing_at != 0,
+            ClawWalletError::InvalidChannelState
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= channel.closing_at,
+            ClawWalletError::NotExpired
+        );
+        let fee = payout_channel(
+            channel,
+            &ctx.accounts.wallet_a,
+            &ctx.accounts.wallet_b,
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+        pay_cleanup_bounty(
+            &ctx.accounts.channel.to_account_info(),
+            &ctx.accounts.keeper.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+
+        emit!(ChannelSettled {
+            channel: channel.key(),
+            state_nonce: channel.state_nonce,
+            balance_a: channel.balance_a,
+            balance_b: channel.balance_b,
+            fee,
+            cooperative: false,
+        });
+        ctx.accounts.wallet_a.close_object(OPEN_CHANNEL);
+        if ctx.accounts.channel.signer_b != Pubkey::default() {
+            ctx.accounts.wallet_b.close_object(OPEN_CHANNEL);
+        }
+
+        Ok(())
+    }
+
+    /// Pay `amount` lamports to `payee` for the resource identified by `resource_hash` (e.g. the hash of an
+    /// HTTP resource URL), emitting a receipt with a per-wallet nonce; the protocol fee is charged on top
+    pub fn pay_for_resource(
+        ctx: Context<PayForResource>,
+        amount: u64,
+        resource_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let references = collect_references(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payee = ctx.accounts.payee.key();
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), payee],
+            None,
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let outflow = amount
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_SOL,
+            outflow,
+            now,
+        )?;
+        ctx.accounts.wallet.record_outflow(outflow, now)?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[payee],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_sol_policy(
+                &ctx.accounts.wallet,
+                &ctx.accounts.spend_policy,
+                outflow,
+                false,
+            )?;
+            enforce_allowlist(&ctx.accounts.wallet, &ctx.accounts.allowlist, &[payee])?;
+        }
+
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, outflow)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.payee.to_account_info(), amount)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.sol_points_for(amount);
+        let points_earned = wallet.earn_points(
+            base_points,
+            amount,
+            config.min_earn_lamports,
+            payee,
+            now,
+            config,
+        );
+        let nonce = wallet.resource_nonce;
+        wallet.resource_nonce += 1;
+
+        write_receipt(
+            &mut ctx.accounts.receipt,
+            ctx.bumps.get("receipt"),
+            &ctx.accounts.receipt_payer,
+            wallet,
+            payee,
+            None,
+            amount,
+            &references,
+            resource_hash,
+        )?;
+
+        emit!(ResourcePaid {
+            agent_id: wallet.agent_id.clone(),
+            wallet: wallet.key(),
+            payee,
+            resource_hash,
+            nonce,
+            mint: None,
+            amount,
+            fee,
+            points_earned,
+            paid_at: now,
+            references,
+            transfer_fee: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Pay `amount` of an SPL Token or Token-2022 mint to `payee_token_account`'s owner for the resource identified
+    /// by `resource_hash`, emitting a receipt with a per-wallet nonce; the protocol fee is charged on top.
+    /// Transfer-hook accounts (if the mint has a hook) come first in remaining_accounts, then any references.
+    /// The receipt records what the payee received after a transfer-fee mint withholds its fee
+    pub fn pay_for_resource_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, PayForResourceToken<'info>>,
+        amount: u64,
+        resource_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let (hook_accounts, reference_infos) =
+            transfer_hook_accounts(&ctx.accounts.mint.to_account_info(), ctx.remaining_accounts)?;
+        let references = collect_references(reference_infos)?;
+        let now = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.mint.key();
+        let payee = ctx.accounts.payee_token_account.owner;
+        let fee = protocol_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.fee_exemption,
+            &ctx.accounts.campaign,
+            ctx.accounts.wallet.key(),
+            &[ctx.accounts.wallet.key(), payee],
+            Some(mint),
+            amount,
+            ctx.accounts.config.fee_bps_for(ctx.accounts.wallet.points),
+            now,
+        )?;
+        let outflow = amount
+            .checked_add(fee)
+            .ok_or(ClawWalletError::MathOverflow)?;
+
+        let role = authorize_sender(
+            &ctx.accounts.wallet,
+            &ctx.accounts.authority.key(),
+            &mut ctx.accounts.session,
+            PERM_SEND_TOKEN,
+            outflow,
+            now,
+        )?;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[payee, ctx.accounts.payee_token_account.key()],
+        )?;
+        if role != SenderRole::Owner {
+            enforce_token_policy(
+                &ctx.accounts.wallet,
+                &mut ctx.accounts.spend_policy,
+                &mint,
+                outflow,
+                now,
+                false,
+            )?;
+            enforce_allowlist(
+                &ctx.accounts.wallet,
+                &ctx.accounts.allowlist,
+                &[payee, ctx.accounts.payee_token_account.key()],
+            )?;
+        }
+
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.payee_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            amount,
+        )?;
+        transfer_checked_from_wallet(
+            &ctx.accounts.token_program,
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            &ctx.accounts.wallet,
+            hook_accounts,
+            fee,
+        )?;
+        let transfer_fee = mint_transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+
+        let config = &ctx.accounts.config;
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, config);
+        let base_points = config.token_points_for(&mint, amount);
+        let points_earned = wallet.earn_points(
+            base_points,
+            amount,
+            config.min_earn_token,
+            payee,
+            now,
+            config,
+        );
+        let nonce = wallet.resource_nonce;
+        wallet.resource_nonce += 1;
+
+        write_receipt(
+            &mut ctx.accounts.receipt,
+            ctx.bumps.get("receipt"),
+            &ctx.accounts.receipt_payer,
+            wallet,
+            payee,
+            Some(mint),
+            amount - transfer_fee,
+            &references,
+            resource_hash,
+        )?;
+
+        emit!(ResourcePaid {
+            agent_id: wallet.agent_id.clone(),
+            wallet: wallet.key(),
+            payee,
+            resource_hash,
+            nonce,
+            mint: Some(mint),
+            amount,
+            fee,
+            points_earned,
+            paid_at: now,
+            references,
+            transfer_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Check that `receipt` records a payment of at least `min_amount` of `mint` (None = SOL) to `payee`
+    /// net of refunds (with `reference` and `resource_hash`, when given) and return the receipt via return
+    /// data with `amount` set to that net figure, for other programs to confirm an agent paid. A fully
+    /// refunded receipt never verifies. Receipts are not consumed: callers must track which
+    /// `(payer, nonce)` they have honored
+    pub fn verify_receipt(
+        ctx: Context<VerifyReceipt>,
+        payee: Pubkey,
+        mint: Option<Pubkey>,
+        min_amount: u64,
+        reference: Option<Pubkey>,
+        resource_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let mut receipt = ctx.accounts.receipt.clone().into_inner();
+        require!(
+            receipt.satisfies(payee, mint, min_amount, reference, resource_hash),
+            ClawWalletError::ReceiptMismatch
+        );
+        receipt.amount = receipt.net_amount();
+        set_return_data(&receipt.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Owner: close a payment receipt, returning its rent to whoever paid it
+    pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+        let receipt = &ctx.accounts.receipt;
+        emit!(ReceiptClosed {
+            receipt: receipt.key(),
+            payer: receipt.payer,
+            nonce: receipt.nonce,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: configure one of the wallet's Token-2022 token accounts for confidential transfers. The
+    /// ElGamal pubkey validity proof is verified by the ZK proof program (see `proof_account`)
+    pub fn configure_confidential_account(
+        ctx: Context<ConfigureConfidentialAccount>,
+        decryptable_zero_balance: [u8; 36],
+        maximum_pending_balance_credit_counter: u64,
+        proof_instruction_offset: i8,
+    ) -> Result<()> {
+        let mut data = decryptable_zero_balance.to_vec();
+        data.extend_from_slice(&maximum_pending_balance_credit_counter.to_le_bytes());
+        data.push(proof_instruction_offset as u8);
+        invoke_confidential(
+            &ctx.accounts.token_program,
+            CT_CONFIGURE_ACCOUNT,
+            &data,
+            vec![
+                AccountMeta::new(ctx.accounts.wallet_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.proof_account.key(), false),
+            ],
+            vec![
+                ctx.accounts.wallet_token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.proof_account.to_account_info(),
+            ],
+            &ctx.accounts.wallet,
+        )?;
+
+        emit!(ConfidentialAccountConfigured {
+            wallet: ctx.accounts.wallet.key(),
+            token_account: ctx.accounts.wallet_token_account.key(),
+            mint: ctx.accounts.mint.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Owner: move `amount` of a token account's public balance into its pending confidential balance
+    pub fn deposit_confidential(ctx: Context<DepositConfidential>, amount: u64) -> Result<()> {
+        require!(amount > 0, ClawWalletError::InvalidAmount);
+        let mut data = amount.to_le_bytes().to_vec();
+        data.push(ctx.accounts.mint.decimals);
+        invoke_confidential(
+            &ctx.accounts.token_program,
+            CT_DEPOSIT,
+            &data,
+            vec![
+                AccountMeta::new(ctx.accounts.wallet_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+            ],
+            vec![
+                ctx.accounts.wallet_token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+            ],
+            &ctx.accounts.wallet,
+        )?;
+
+        emit!(ConfidentialDeposited {
+            wallet: ctx.accounts.wallet.key(),
+            token_account: ctx.accounts.wallet_token_account.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Owner: fold received confidential transfers (the pending balance) into the available balance
+    pub fn apply_confidential_pending(
+        ctx: Context<ApplyConfidentialPending>,
+        expected_pending_balance_credit_counter: u64,
+        new_decryptable_available_balance: [u8; 36],
+    ) -> Result<()> {
+        let mut data = expected_pending_balance_credit_counter
+            .to_le_bytes()
+            .to_vec();
+        data.extend_from_slice(&new_decryptable_available_balance);
+        invoke_confidential(
+            &ctx.accounts.token_program,
+            CT_APPLY_PENDING_BALANCE,
+            &data,
+            vec![AccountMeta::new(
+                ctx.accounts.wallet_token_account.key(),
+                false,
+            )],
+            vec![ctx.accounts.wallet_token_account.to_account_info()],
+            &ctx.accounts.wallet,
+        )
+    }
+
+    /// Owner: send a confidential Token-2022 transfer; the amount is encrypted, so only the flat
+    /// `confidential_fee` (lamports) is charged and spend policies cannot apply. Owner-only because an
+    /// agent or session key could not be held to its limits; the flat fee means the owner pays less than
+    /// the percentage fee on large amounts, which is the accepted cost of hiding them
+    pub fn send_confidential(
+        ctx: Context<SendConfidential>,
+        new_source_decryptable_available_balance: [u8; 36],
+        proof_instruction_offset: i8,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let recipient = ctx.accounts.recipient_token_account.owner;
+        enforce_denylists(
+            &ctx.accounts.wallet,
+            &ctx.accounts.config,
+            &ctx.accounts.denylist,
+            &ctx.accounts.protocol_denylist,
+            &[ctx.accounts.recipient_token_account.key(), recipient],
+        )?;
+
+        let fee = ctx.accounts.config.confidential_fee;
+        let wallet_info = ctx.accounts.wallet.to_account_info();
+        require_spendable(&wallet_info, fee)?;
+        transfer_lamports(&wallet_info, &ctx.accounts.treasury.to_account_info(), fee)?;
+
+        let mut data = new_source_decryptable_available_balance.to_vec();
+        data.push(proof_instruction_offset as u8);
+        invoke_confidential(
+            &ctx.accounts.token_program,
+            CT_TRANSFER,
+            &data,
+            vec![
+                AccountMeta::new(ctx.accounts.wallet_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+                AccountMeta::new(ctx.accounts.recipient_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.proof_account.key(), false),
+            ],
+            vec![
+                ctx.accounts.wallet_token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.recipient_token_account.to_account_info(),
+                ctx.accounts.proof_account.to_account_info(),
+            ],
+            &ctx.accounts.wallet,
+        )?;
+
+        let wallet = &mut ctx.accounts.wallet;
+        wallet.record_tx(now, &ctx.accounts.config);
+
+        emit!(ConfidentialSent {
+            agent_id: wallet.agent_id.clone(),
+            mint: ctx.accounts.mint.key(),
+            recipient: ctx.accounts.recipient_token_account.key(),
+            fee,
+        });
+
+        Ok(())
+    }
+
+    /// Admin: set the flat fee charged on confidential sends (0 = free)
+    pub fn set_confidential_fee(ctx: Context<AdminConfig>, confidential_fee: u64) -> Result<()> {
+        require!(
+            confidential_fee <= MAX_CONFIDENTIAL_FEE,
+            ClawWalletError::FeeTooHigh
+        );
+        ctx.accounts.config.confidential_fee = confidential_fee;
+
+        emit!(ConfidentialFeeSet {
+            admin: ctx.accounts.admin.key(),
+            confidential_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Send tokens with `send_token`, creating the recipient's associated token account first if it does
+    /// not exist (rent charged to `payer`), so agents can pay fresh addresses. The remaining accounts are
+    /// `send_token`'s accounts followed by its own remaining accounts (hook accounts, references)
+    pub fn send_token_with_ata<'info>(
+        ctx: Context<'_, '_, '_, 'info, SendTokenWithAta<'info>>,
+        amount: u64,
+        memo: Option<String>,
+        category: u8,
+        idempotency_key: Option<[u8; 16]>,
+    ) -> Result<()> {
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: ctx.accounts.payer.to_account_info(),
+                associated_token: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.recipient.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+
+        // Now that the token account exists, resolve and run send_token against it
+        let mut accounts = ctx.remaining_accounts;
+        let mut bumps = BTreeMap::new();
+        let mut send = SendToken::try_accounts(
+            ctx.program_id,
+            &mut accounts,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        require_keys_eq!(
+            send.recipient_token_account.key(),
+            ctx.accounts.recipient_token_account.key(),
+            ClawWalletError::AtaMismatch
+        );
+        send_token(
+            Context::new(ctx.program_id, &mut send, accounts, bumps),
+            amount,
+            memo,
+            category,
+            idempotency_key,
+        )?;
+        send.exit(ctx.program_id)
+    }
+}
+
+/// Who signed a send instruction
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SenderRole {
+    Owner,
+    Agent,
+    Session,
+}
+
+/// Resolve the signer's role; anyone other than the owner or agent key needs a live session with `permission`
+fn authorize_sender(
+    wallet: &AgentWallet,
+    authority: &Pubkey,
+    session: &mut Option<Account<SessionKey>>,
+    permission: u8,
+    amount: u64,
+    now: i64,
+) -> Result<SenderRole> {
+    if *authority == wallet.owner {
+        return Ok(SenderRole::Owner);
+    }
+    if wallet.agent_authority != Pubkey::default() && *authority == wallet.agent_authority {
+        return Ok(SenderRole::Agent);
+    }
+
+    let session = session.as_mut().ok_or(ClawWalletError::Unauthorized)?;
+    session.authorize(permission, amount, now)?;
+    Ok(SenderRole::Session)
+}
+
+/// Accumulate `amount` into a 24h window starting at `window_start`, resetting it once elapsed
+fn roll_window(window_start: &mut i64, volume: &mut u64, amount: u64, now: i64) -> Result<u64> {
+    if now.saturating_sub(*window_start) >= SECONDS_PER_DAY {
+        *window_start = now;
+        *volume = 0;
+    }
+    *volume = volume
+        .checked_add(amount)
+        .ok_or(ClawWalletError::MathOverflow)?;
+    Ok(*volume)
+}
+
+/// Check a SOL outflow (already recorded in the wallet's rolling volume) against the spend policy;
+/// `timelocked` is set when executing a queued transfer, which may exceed the timelock threshold
+fn enforce_sol_policy(
+    wallet: &AgentWallet,
+    spend_policy: &Option<Account<SpendPolicy>>,
+    amount: u64,
+    timelocked: bool,
+) -> Result<()> {
+    if !wallet.has_spend_policy {
+        return Ok(());
+    }
+    let policy = spend_policy
+        .as_ref()
+        .ok_or(ClawWalletError::SpendPolicyRequired)?;
+    require!(
+        policy.max_per_tx == 0 || amount <= policy.max_per_tx,
+        ClawWalletError::MaxPerTxExceeded
+    );
+    require!(
+        policy.daily_limit == 0 || wallet.volume_24h <= policy.daily_limit,
+        ClawWalletError::DailyLimitExceeded
+    );
+    require!(
+        timelocked || policy.timelock_threshold == 0 || amount <= policy.timelock_threshold,
+        ClawWalletError::TimelockRequired
+    );
+
+    Ok(())
+}
+
+fn enforce_token_policy(
+    wallet: &AgentWallet,
+    spend_policy: &mut Option<Account<SpendPolicy>>,
+    mint: &Pubkey,
+    amount: u64,
+    now: i64,
+    timelocked: bool,
+) -> Result<()> {
+    if !wallet.has_spend_policy {
+        return Ok(());
+    }
+    let policy = spend_policy
+        .as_mut()
+        .ok_or(ClawWalletError::SpendPolicyRequired)?;
+    match policy.token_limits.iter_mut().find(|l| l.mint == *mint) {
+        Some(l) => {
+            require!(
+                l.max_per_tx == 0 || amount <= l.max_per_tx,
+                ClawWalletError::MaxPerTxExceeded
+            );
+            let volume = roll_window(&mut l.window_start, &mut l.volume_24h, amount, now)?;
+            require!(
+                l.daily_limit == 0 || volume <= l.daily_limit,
+                ClawWalletError::DailyLimitExceeded
+            );
+            require!(
+                timelocked || l.timelock_threshold == 0 || amount <= l.timelock_threshold,
+                ClawWalletError::TimelockRequired
+            );
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Check a program-owned account can pay out `amount` while staying rent-exempt
+fn require_spendable(info: &AccountInfo, amount: u64) -> Result<()> {
+    let min_balance = Rent::get()?.minimum_balance(info.data_len());
+    let required = amount
+        .checked_add(min_balance)
+        .ok_or(ClawWalletError::MathOverflow)?;
+    require!(
+        info.lamports() >= required,
+        ClawWalletError::InsufficientFunds
+    );
+    Ok(())
+}
+
+/// Move lamports out of a program-owned account (direct manipulation, no CPI)
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// When the allowlist is enabled, at least one of `destinations` (aliases of a single destination) must be on it
+fn enforce_allowlist(
+    wallet: &AgentWallet,
+    allowlist: &Option<Account<RecipientAllowlist>>,
+    destinations: &[Pubkey],
+) -> Result<()> {
+    if !wallet.allowlist_enabled {
+        return Ok(());
+    }
+    let allowlist = allowlist
+        .as_ref()
+        .ok_or(ClawWalletError::AllowlistRequired)?;
+    require!(
+        destinations
+            .iter()
+            .any(|d| allowlist.recipients.contains(d)),
+        ClawWalletError::RecipientNotAllowed
+    );
+    Ok(())
+}
+
+/// `enforce_allowlist` for several destinations at once: `destinations` is taken in groups of `group`
+/// aliases of one destination (e.g. a token account and its owner), and every group must be allowlisted
+fn enforce_allowlist_each(
+    wallet: &AgentWallet,
+    allowlist: &Option<Account<RecipientAllowlist>>,
+    destinations: &[Pubkey],
+    group: usize,
+) -> Result<()> {
+    if !wallet.allowlist_enabled {
+        return Ok(());
+    }
+    let allowlist = allowlist
+        .as_ref()
+        .ok_or(ClawWalletError::AllowlistRequired)?;
+    require!(
+        allowlist_admits(&allowlist.recipients, destinations, group),
+        ClawWalletError::RecipientNotAllowed
+    );
+    Ok(())
+}
+
+fn allowlist_admits(allowed: &[Pubkey], destinations: &[Pubkey], group: usize) -> bool {
+    destinations
+        .chunks(group)
+        .all(|aliases| aliases.iter().any(|d| allowed.contains(d)))
+}
+
+/// Reject `destinations` found on the wallet's or the protocol's denylist
+fn enforce_denylists(
+    wallet: &AgentWallet,
+    config: &ProtocolConfig,
+    denylist: &Option<Account<Denylist>>,
+    protocol_denylist: &Option<Account<Denylist>>,
+    destinations: &[Pubkey],
+) -> Result<()> {
+    if wallet.denylist_enabled {
+        let denylist = denylist.as_ref().ok_or(ClawWalletError::DenylistRequired)?;
+        denylist.check(destinations)?;
+    }
+    if config.denylist_enabled {
+        let denylist = protocol_denylist
+            .as_ref()
+            .ok_or(ClawWalletError::DenylistRequired)?;
+        denylist.check(destinations)?;
+    }
+    Ok(())
+}
+
+/// Transfer SPL tokens out of a wallet-owned token account, signing as the wallet PDA
+fn transfer_from_wallet<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    wallet: &Account<'info, AgentWallet>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"wallet".as_ref(),
+        wallet.agent_id.as_bytes(),
+        &[wallet.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        SplTransfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: wallet.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Limit `fee` to `cap` (0 = uncapped)
+fn apply_fee_cap(fee: u64, cap: u64) -> u64 {
+    if cap == 0 {
+        fee
+    } else {
+        fee.min(cap)
+    }
+}
+
+/// True if `exemption` covers one of `parties`; emits FeeExemptionUsed when applied
+fn apply_fee_exemption(
+    exemption: &Option<Account<FeeExemption>>,
+    wallet: Pubkey,
+    parties: &[Pubkey],
+    amount: u64,
+) -> Result<bool> {
+    let exemption = match exemption {
+        Some(exemption) => exemption,
+        None => return Ok(false),
+    };
+    require!(
+        parties.contains(&exemption.subject),
+        ClawWalletError::InvalidFeeExemption
+    );
+
+    emit!(FeeExemptionUsed {
+        wallet,
+        subject: exemption.subject,
+        reason: exemption.reason,
+        amount,
+    });
+
+    Ok(true)
+}
+
+/// Fee rate for one leg of a multi-asset send: like `campaign_fee_bps`, but a campaign for another asset
+/// leaves the leg at `base_bps` instead of failing
+fn leg_fee_bps(
+    campaign: &Option<Account<FeeCampaign>>,
+    base_bps: u16,
+    mint: Option<Pubkey>,
+    now: i64,
+) -> u16 {
+    match campaign {
+        Some(campaign) if campaign.applies(mint, now) => base_bps.min(campaign.fee_bps),
+        _ => base_bps,
+    }
+}
+
+/// Fee rate after applying `campaign` (if supplied) to `base_bps` for a transfer of `mint`
+fn campaign_fee_bps(
+    campaign: &Option<Account<FeeCampaign>>,
+    base_bps: u16,
+    mint: Option<Pubkey>,
+    now: i64,
+) -> Result<u16> {
+    let campaign = match campaign {
+        Some(campaign) => campaign,
+        None => return Ok(base_bps),
+    };
+    require!(
+        campaign.applies(mint, now),
+        ClawWalletError::CampaignNotActive
+    );
+    Ok(base_bps.min(campaign.fee_bps))
+}
+
+/// Protocol fee on `amount` of `mint` (None = SOL) at the sender's `base_bps`: waived when `exemption` covers
+/// one of `parties`, else lowered by `campaign`, then held to the minimum fee and caps for the asset
+#[allow(clippy::too_many_arguments)]
+fn protocol_fee(
+    config: &ProtocolConfig,
+    exemption: &Option<Account<FeeExemption>>,
+    campaign: &Option<Account<FeeCampaign>>,
+    wallet: Pubkey,
+    parties: &[Pubkey],
+    mint: Option<Pubkey>,
+    amount: u64,
+    base_bps: u16,
+    now: i64,
+) -> Result<u64> {
+    if apply_fee_exemption(exemption, wallet, parties, amount)? {
+        return Ok(0);
+    }
+    let bps = campaign_fee_bps(campaign, base_bps, mint, now)?;
+    Ok(match mint {
+        Some(_) => config.token_fee_at(amount, bps),
+        None => config.sol_fee_at(amount, bps),
+    })
+}
+
+/// Load the per-mint fee PDA if it has been created (it lives at a fixed address, so it can't be skipped)
+fn load_mint_fee(info: &AccountInfo) -> Result<Option<MintFee>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let mint_fee = Account::<MintFee>::try_from(info)?;
+    Ok(Some(mint_fee.into_inner()))
+}
+
+/// Fee rate for a token send: the mint's rate (if set) scaled by the sender's points tier
+fn token_fee_bps(config: &ProtocolConfig, mint_fee: &Option<MintFee>, points: u64) -> u16 {
+    let tier_bps = config.fee_bps_for(points);
+    match mint_fee {
+        Some(m) if config.fee_bps > 0 => {
+            (m.fee_bps as u32 * tier_bps as u32 / config.fee_bps as u32) as u16
+        }
+        Some(m) => m.fee_bps,
+        None => tier_bps,
+    }
+}
+
+/// Portion of `fee` owed to the wallet's referrer, checking the supplied referrer account
+fn referral_cut(
+    wallet: &AgentWallet,
+    referrer: Option<Pubkey>,
+    config: &ProtocolConfig,
+    fee: u64,
+) -> Result<u64> {
+    if wallet.referrer == Pubkey::default() {
+        return Ok(0);
+    }
+    let referrer = referrer.ok_or(ClawWalletError::ReferrerRequired)?;
+    require!(
+        referrer == wallet.referrer,
+        ClawWalletError::InvalidReferrer
+    );
+    Ok((fee as u128 * config.referral_share_bps as u128 / 10_000) as u64)
+}
+
+/// Attribute a send to the code the wallet signed up with (`lamports` is 0 for token sends)
+fn record_referred_send(
+    wallet: &AgentWallet,
+    referral_code: &mut Option<Account<ReferralCode>>,
+    lamports: u64,
+) -> Result<()> {
+    if wallet.referral_code == Pubkey::default() {
+        return Ok(());
+    }
+    let referral_code = referral_code
+        .as_mut()
+        .ok_or(ClawWalletError::ReferrerRequired)?;
+    require!(
+        referral_code.key() == wallet.referral_code,
+        ClawWalletError::InvalidReferrer
+    );
+    referral_code.referred_tx_count = referral_code
+        .referred_tx_count
+        .checked_add(1)
+        .ok_or(ClawWalletError::MathOverflow)?;
+    referral_code.referred_volume = referral_code
+        .referred_volume
+        .checked_add(lamports)
+        .ok_or(ClawWalletError::MathOverflow)?;
+    Ok(())
+}
+
+/// Split `amount` by beneficiary share; rounding dust goes to the first beneficiary
+fn fee_shares(beneficiaries: &[FeeBeneficiary], amount: u64) -> Vec<u64> {
+    let bps: Vec<u16> = beneficiaries.iter().map(|b| b.share_bps).collect();
+    bps_shares(amount, &bps)
+}
+
+/// Split `amount` by basis-point shares; rounding dust goes to the first share
+fn bps_shares(amount: u64, shares_bps: &[u16]) -> Vec<u64> {
+    let mut shares: Vec<u64> = shares_bps
+        .iter()
+        .map(|bps| (amount as u128 * *bps as u128 / 10_000) as u64)
+        .collect();
+    let dust = amount - shares.iter().sum::<u64>();
+    shares[0] += dust;
+    shares
+}
+
+/// Shares must be non-zero and total 10_000 bps
+fn validate_shares(shares_bps: &[u16]) -> Result<()> {
+    let total: u32 = shares_bps.iter().map(|bps| *bps as u32).sum();
+    require!(
+        total == 10_000 && shares_bps.iter().all(|bps| *bps > 0),
+        ClawWalletError::InvalidShares
+    );
+    Ok(())
+}
+
+/// Validate caller-supplied shares and split `amount` by them
+fn split_by_shares(amount: u64, shares_bps: &[u16]) -> Result<Vec<u64>> {
+    validate_shares(shares_bps)?;
+    Ok(bps_shares(amount, shares_bps))
+}
+
+/// Spend one of the wallet's discount credits on a non-zero fee, returning the discounted fee
+fn apply_fee_discount(wallet: &mut AgentWallet, config: &ProtocolConfig, fee: u64) -> u64 {
+    if fee == 0 || wallet.fee_discount_credits == 0 {
+        return fee;
+    }
+    wallet.fee_discount_credits -= 1;
+    config.discounted_fee(fee)
+}
+
+/// Refresh `wallet`'s leaderboard entry when the leaderboard is supplied
+fn update_leaderboard(
+    leaderboard: &Option<AccountLoader<Leaderboard>>,
+    wallet: Pubkey,
+    points: u64,
+) -> Result<()> {
+    if let Some(leaderboard) = leaderboard {
+        leaderboard.load_mut()?.submit(wallet, points);
+    }
+    Ok(())
+}
+
+/// Points for one leg of a multi-asset send: `campaign` boosts only the legs in its asset
+fn leg_campaign_points(
+    campaign: &Option<Account<PointsCampaign>>,
+    points: u64,
+    mint: Option<Pubkey>,
+    now: i64,
+    agent_id: &str,
+) -> Result<u64> {
+    match campaign {
+        Some(c) if c.applies(mint, now) => campaign_points(campaign, points, mint, now, agent_id),
+        _ => Ok(points),
+    }
+}
+
+/// Apply `campaign` (if supplied) to `points` earned by a send of `mint` (None = SOL)
+fn campaign_points(
+    campaign: &Option<Account<PointsCampaign>>,
+    points: u64,
+    mint: Option<Pubkey>,
+    now: i64,
+    agent_id: &str,
+) -> Result<u64> {
+    let campaign = match campaign {
+        Some(campaign) => campaign,
+        None => return Ok(points),
+    };
+    require!(
+        campaign.applies(mint, now),
+        ClawWalletError::CampaignNotActive
+    );
+    let boosted = (points as u128 * campaign.multiplier_bps as u128 / 10_000) as u64;
+
+    emit!(PointsCampaignApplied {
+        id: campaign.id,
+        agent_id: agent_id.to_string(),
+        base_points: points,
+        points: boosted,
+    });
+
+    Ok(boosted)
+}
+
+/// Advance the sender's quest progress, if a quest and its progress account were passed
+fn advance_quest(
+    quest: &Option<Account<Quest>>,
+    progress: &mut Option<Account<QuestProgress>>,
+    wallet: &Account<AgentWallet>,
+    mint: Option<Pubkey>,
+    amount: u64,
+    agent: Option<Pubkey>,
+    now: i64,
+) -> Result<()> {
+    let (quest, progress) = match (quest, progress) {
+        (Some(quest), Some(progress)) => (quest, progress),
+        (None, None) => return Ok(()),
+        _ => return err!(ClawWalletError::InvalidQuest),
+    };
+    require!(progress.quest == quest.key(), ClawWalletError::InvalidQuest);
+    require!(
+        progress.wallet == wallet.key(),
+        ClawWalletError::InvalidQuest
+    );
+    require!(now < quest.end_at, ClawWalletError::CampaignNotActive);
+
+    if progress.advance(quest, mint, amount, agent, now) {
+        emit!(QuestCompleted {
+            id: quest.id,
+            agent_id: wallet.agent_id.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+/// USD value of `amount` raw units (with `decimals`) in micro-USD, from a fresh Pyth price (None if stale)
+fn usd_value(
+    feed: &AccountInfo,
+    amount: u64,
+    decimals: u8,
+    now: i64,
+    max_age: u64,
+) -> Result<Option<u64>> {
+    let price_feed = load_price_feed_from_account_info(feed)
+        .map_err(|_| error!(ClawWalletError::InvalidPriceFeed))?;
+    let price = match price_feed.get_price_no_older_than(now, max_age) {
+        Some(price) => price,
+        None => return Ok(None),
+    };
+    require!(price.price > 0, ClawWalletError::InvalidPriceFeed);
+
+    let value = amount as u128 * price.price as u128;
+    let exp = price.expo + 6 - decimals as i32;
+    let scale = 10u128
+        .checked_pow(exp.unsigned_abs())
+        .ok_or(ClawWalletError::MathOverflow)?;
+    let value = if exp >= 0 {
+        value
+            .checked_mul(scale)
+            .ok_or(ClawWalletError::MathOverflow)?
+    } else {
+        value / scale
+    };
+    u64::try_from(value)
+        .map(Some)
+        .map_err(|_| error!(ClawWalletError::MathOverflow))
+}
+
+/// Base points for a send: USD value through `usd_points` when the asset has a price feed, else the raw
+/// formula. Falls back to the raw formula when the feed account is omitted or its price is stale
+fn send_points(
+    config: &ProtocolConfig,
+    price_feed: &Option<UncheckedAccount>,
+    mint: Option<Pubkey>,
+    amount: u64,
+    now: i64,
+) -> Result<u64> {
+    let raw_points = match mint {
+        Some(mint) => config.token_points_for(&mint, amount),
+        None => config.sol_points_for(amount),
+    };
+    let (feed, decimals) = match config.price_feed_for(mint) {
+        Some(feed) => feed,
+        None => return Ok(raw_points),
+    };
+    let info = match price_feed {
+        Some(info) => info,
+        None => return Ok(raw_points),
+    };
+    require_keys_eq!(info.key(), feed, ClawWalletError::InvalidPriceFeed);
+
+    match usd_value(
+        &info.to_account_info(),
+        amount,
+        decimals,
+        now,
+        config.max_price_age,
+    )? {
+        Some(value) => Ok(config.usd_points.points_for(value)),
+        None => Ok(raw_points),
+    }
+}
+
+/// Forward an optional memo to the Memo program so it is attributed to this transaction. Memos are taken
+/// by the direct sends (send_sol, send_to_agent, send_token and its ATA variant, the batch, split and multi
+/// sends); queued, scheduled, escrow, HTLC, stream, tip and refund payments carry none
+fn forward_memo(memo_program: &Option<Program<Memo>>, memo: &Option<String>) -> Result<()> {
+    let memo = match memo {
+        Some(memo) => memo,
+        None => return Ok(()),
+    };
+    require!(
+        !memo.is_empty() && memo.len() <= MAX_MEMO_LEN,
+        ClawWalletError::InvalidMemo
+    );
+    let memo_program = memo_program
+        .as_ref()
+        .ok_or(ClawWalletError::MemoProgramRequired)?;
+    memo::build_memo(
+        CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+        memo.as_bytes(),
+    )
+}
+
+/// Solana Pay-style reference keys: trailing read-only, non-signer accounts echoed into the send event.
+/// Taken by the direct sends, claimable sends, invoice payments and pay_for_resource(_token); queued,
+/// scheduled, escrow, HTLC, stream, tip and refund payments carry none
+fn collect_references(accounts: &[AccountInfo]) -> Result<Vec<Pubkey>> {
+    require!(
+        accounts.len() <= MAX_REFERENCES,
+        ClawWalletError::InvalidReference
+    );
+    require!(
+        accounts.iter().all(|a| !a.is_writable && !a.is_signer),
+        ClawWalletError::InvalidReference
+    );
+    Ok(accounts.iter().map(|a| a.key()).collect())
+}
+
+/// Pay the configured keeper tip from a wallet, as far as its spendable balance allows
+fn pay_keeper_tip(
+    wallet: &AccountInfo,
+    keeper: &AccountInfo,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    if config.keeper_tip == 0 || wallet.key() == keeper.key() {
+        return Ok(());
+    }
+    let min_balance = Rent::get()?.minimum_balance(wallet.data_len());
+    let tip = config
+        .keeper_tip
+        .min(wallet.lamports().saturating_sub(min_balance));
+    if tip == 0 {
+        return Ok(());
+    }
+    transfer_lamports(wallet, keeper, tip)?;
+
+    emit!(KeeperPaid {
+        keeper: keeper.key(),
+        account: wallet.key(),
+        amount: tip,
+    });
+
+    Ok(())
+}
+
+/// Close a program-owned companion PDA (if it exists), sending its rent to `destination`; returns
+/// whether there was one
+fn close_companion(companion: &AccountInfo, destination: &AccountInfo) -> Result<bool> {
+    if companion.owner != &crate::ID || companion.data_is_empty() {
+        return Ok(false);
+    }
+    transfer_lamports(companion, destination, companion.lamports())?;
+    companion.assign(&system_program::ID);
+    companion.realloc(0, false)?;
+    Ok(true)
+}
+
+/// Realloc a program account of the type with `discriminator` up to `space`, zero-filling the tail so
+/// fields appended to the struct read as their defaults, and top up its rent from `payer`
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    discriminator: [u8; 8],
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(
+        account.owner == &crate::ID
+            && account.data_len() >= 8
+            && account.try_borrow_data()?[..8] == discriminator,
+        ClawWalletError::NotMigratable
+    );
+    let old_len = account.data_len();
+    require!(old_len < space, ClawWalletError::AlreadyMigrated);
+
+    let rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if rent > 0 {
+        let cpi_context = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(cpi_context, rent)?;
+    }
+    account.realloc(space, true)?;
+
+    emit!(AccountMigrated {
+        account: account.key(),
+        old_len: old_len as u32,
+        new_len: space as u32,
+        rent_paid: rent,
+    });
+
+    Ok(())
+}
+
+/// Pay the keeper its share of the rent reclaimed from an account about to be closed
+fn pay_cleanup_bounty(
+    closing: &AccountInfo,
+    keeper: &AccountInfo,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    let bounty = (closing.lamports() as u128 * config.cleanup_bounty_bps as u128 / 10_000) as u64;
+    if bounty == 0 {
+        return Ok(());
+    }
+    transfer_lamports(closing, keeper, bounty)?;
+
+    emit!(KeeperPaid {
+        keeper: keeper.key(),
+        account: closing.key(),
+        amount: bounty,
+    });
+
+    Ok(())
+}
+
+/// Move tokens out of a program-owned vault, signed by the PDA with `seeds`
+fn vault_transfer<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        SplTransfer {
+            from: vault.to_account_info(),
+            to,
+            authority,
+        },
+        &[seeds],
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Close an emptied program-owned vault, returning its rent to `destination`
+fn close_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token::CloseAccount {
+            account: vault.to_account_info(),
+            destination,
+            authority,
+        },
+        &[seeds],
+    );
+    token::close_account(cpi_ctx)
+}
+
+/// Move tokens out of an escrow vault, signed by the escrow PDA
+fn escrow_transfer_token<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &Account<'info, Escrow>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let nonce = escrow.nonce.to_le_bytes();
+    let seeds = &[
+        b"escrow".as_ref(),
+        escrow.wallet.as_ref(),
+        nonce.as_ref(),
+        &[escrow.bump],
+    ];
+    vault_transfer(
+        token_program,
+        vault,
+        to,
+        escrow.to_account_info(),
+        seeds,
+        amount,
+    )
+}
+
+/// Close an emptied escrow vault, returning its rent to `destination`
+fn close_escrow_vault<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &Account<'info, Escrow>,
+    vault: &Account<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    let nonce = escrow.nonce.to_le_bytes();
+    let seeds = &[
+        b"escrow".as_ref(),
+        escrow.wallet.as_ref(),
+        nonce.as_ref(),
+        &[escrow.bump],
+    ];
+    close_vault(
+        token_program,
+        vault,
+        destination,
+        escrow.to_account_info(),
+        seeds,
+    )
+}
+
+/// Move tokens out of an HTLC vault, signed by the HTLC PDA
+fn htlc_transfer_token<'info>(
+    token_program: &Program<'info, Token>,
+    htlc: &Account<'info, Htlc>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let nonce = htlc.nonce.to_le_bytes();
+    let seeds = &[
+        b"htlc".as_ref(),
+        htlc.wallet.as_ref(),
+        nonce.as_ref(),
+        &[htlc.bump],
+    ];
+    vault_transfer(
+        token_program,
+        vault,
+        to,
+        htlc.to_account_info(),
+        seeds,
+        amount,
+    )
+}
+
+/// Close an emptied HTLC vault, returning its rent to `destination`
+fn close_htlc_vault<'info>(
+    token_program: &Program<'info, Token>,
+    htlc: &Account<'info, Htlc>,
+    vault: &Account<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    let nonce = htlc.nonce.to_le_bytes();
+    let seeds = &[
+        b"htlc".as_ref(),
+        htlc.wallet.as_ref(),
+        nonce.as_ref(),
+        &[htlc.bump],
+    ];
+    close_vault(
+        token_program,
+        vault,
+        destination,
+        htlc.to_account_info(),
+        seeds,
+    )
+}
+
+/// A designated escrow arbiter must hold an eligible bond
+fn require_bonded_arbiter(
+    arbiter: Pubkey,
+    arbiter_bond: &Option<Account<ArbiterBond>>,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    if arbiter == Pubkey::default() {
+        return Ok(());
+    }
+    let arbiter_bond = arbiter_bond
+        .as_ref()
+        .ok_or(ClawWalletError::InsufficientBond)?;
+    require!(
+        arbiter_bond.authority == arbiter,
+        ClawWalletError::InvalidArbiter
+    );
+    require!(
+        arbiter_bond.is_eligible(config),
+        ClawWalletError::InsufficientBond
+    );
+    Ok(())
+}
+
+/// Move tokens out of a claimable transfer's vault, signed by the claimable PDA
+fn claimable_transfer_token<'info>(
+    token_program: &Program<'info, Token>,
+    claimable: &Account<'info, Claimable>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let nonce = claimable.nonce.to_le_bytes();
+    let seeds = &[
+        b"claimable".as_ref(),
+        claimable.recipient.as_ref(),
+        claimable.wallet.as_ref(),
+        nonce.as_ref(),
+        &[claimable.bump],
+    ];
+    vault_transfer(
+        token_program,
+        vault,
+        to,
+        claimable.to_account_info(),
+        seeds,
+        amount,
+    )
+}
+
+/// Close an emptied claimable vault, returning its rent to `destination`
+fn close_claimable_vault<'info>(
+    token_program: &Program<'info, Token>,
+    claimable: &Account<'info, Claimable>,
+    vault: &Account<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    let nonce = claimable.nonce.to_le_bytes();
+    let seeds = &[
+        b"claimable".as_ref(),
+        claimable.recipient.as_ref(),
+        claimable.wallet.as_ref(),
+        nonce.as_ref(),
+        &[claimable.bump],
+    ];
+    close_vault(
+        token_program,
+        vault,
+        destination,
+        claimable.to_account_info(),
+        seeds,
+    )
+}
+
+/// Split an incoming payment by the receiving wallet's revenue share. The payees are the leading
+/// `accounts` in share order (token accounts for `mint`); returns them, their cuts, and the accounts after
+fn revenue_split<'a, 'info>(
+    receiver: &AgentWallet,
+    revenue_share: &Option<Account<RevenueShare>>,
+    accounts: &'a [AccountInfo<'info>],
+    mint: Option<Pubkey>,
+    amount: u64,
+) -> Result<(&'a [AccountInfo<'info>], Vec<u64>, &'a [AccountInfo<'info>])> {
+    if !receiver.has_revenue_share {
+        return Ok((&accounts[..0], Vec::new(), accounts));
+    }
+    let revenue_share = revenue_share
+        .as_ref()
+        .ok_or(ClawWalletError::RevenueShareRequired)?;
+    let shares = &revenue_share.shares;
+    require!(
+        accounts.len() >= shares.len(),
+        ClawWalletError::InvalidShares
+    );
+    let (payees, rest) = accounts.split_at(shares.len());
+    for (payee, share) in payees.iter().zip(shares.iter()) {
+        require!(payee.is_writable, ClawWalletError::InvalidShares);
+        let owner = match mint {
+            Some(mint) => {
+                let token_account = Account::<TokenAccount>::try_from(payee)?;
+                require!(token_account.mint == mint, ClawWalletError::MintMismatch);
+                token_account.owner
+            }
+            None => payee.key(),
+        };
+        require!(owner == share.recipient, ClawWalletError::InvalidShares);
+    }
+    let bps: Vec<u16> = shares.iter().map(|s| s.share_bps).collect();
+    Ok((payees, bps_shares(amount, &bps), rest))
+}
+
+/// Bytes each side signs off-chain for a channel balance update. `epoch` tells apart channels re-opened
+/// at the same address, so states signed for an earlier one can't be replayed
+fn channel_state_message(
+    channel: &Pubkey,
+    epoch: u64,
+    state_nonce: u64,
+    balance_a: u64,
+    balance_b: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(15 + 32 + 32);
+    message.extend_from_slice(b"clawwallet-chan");
+    message.extend_from_slice(channel.as_ref());
+    message.extend_from_slice(&epoch.to_le_bytes());
+    message.extend_from_slice(&state_nonce.to_le_bytes());
+    message.extend_from_slice(&balance_a.to_le_bytes());
+    message.extend_from_slice(&balance_b.to_le_bytes());
+    message
+}
+
+/// Require an Ed25519 program instruction earlier in this transaction that verified `signer`'s
+/// signature over `message` (all offsets pointing into that instruction's own data)
+fn require_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = ix_sysvar::load_current_index_checked(instructions)? as usize;
+    for index in 0..current {
+        let ix = ix_sysvar::load_instruction_at_checked(index, instructions)?;
+        if ix.program_id == ed25519_program::ID && ed25519_verifies(&ix.data, signer, message) {
+            return Ok(());
+        }
+    }
+    err!(ClawWalletError::InvalidChannelState)
+}
+
+fn ed25519_verifies(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    let count = match data.first() {
+        Some(count) => *count as usize,
+        None => return false,
+    };
+    (0..count).any(|i| {
+        let offsets = match data.get(2 + i * 14..2 + (i + 1) * 14) {
+            Some(offsets) => offsets,
+            None => return false,
+        };
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+        let (signature_ix, pubkey_offset, pubkey_ix) = (read(2), read(4) as usize, read(6));
+        let (message_offset, message_len, message_ix) =
+            (read(8) as usize, read(10) as usize, read(12));
+        signature_ix == u16::MAX
+            && pubkey_ix == u16::MAX
+            && message_ix == u16::MAX
+            && data.get(pubkey_offset..pubkey_offset + 32) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_len) == Some(message)
+    })
+}
+
+/// Pay each side its channel balance; the protocol fee on the net flow is taken from the receiving side
+/// at the paying side's tier. Returns the fee
+fn payout_channel(
+    channel: &Account<Channel>,
+    wallet_a: &Account<AgentWallet>,
+    wallet_b: &Account<AgentWallet>,
+    treasury: &AccountInfo,
+    config: &ProtocolConfig,
+) -> Result<u64> {
+    let (payout_a, payout_b, fee) = if channel.balance_b > channel.deposit_b {
+        let fee = config.sol_fee_for(channel.balance_b - channel.deposit_b, wallet_a.points);
+        (channel.balance_a, channel.balance_b - fee, fee)
+    } else {
+        let fee = config.sol_fee_for(
+            channel.balance_a.saturating_sub(channel.deposit_a),
+            wallet_b.points,
+        );
+        (channel.balance_a - fee, channel.balance_b, fee)
+    };
+    let channel_info = channel.to_account_info();
+    transfer_lamports(&channel_info, &wallet_a.to_account_info(), payout_a)?;
+    transfer_lamports(&channel_info, &wallet_b.to_account_info(), payout_b)?;
+    transfer_lamports(&channel_info, treasury, fee)?;
+    Ok(fee)
+}
+
+/// Fill in the optional payment receipt for a transfer just made and advance the wallet's receipt nonce
+#[allow(clippy::too_many_arguments)]
+fn write_receipt(
+    receipt: &mut Option<Account<PaymentReceipt>>,
+    bump: Option<&u8>,
+    rent_payer: &Option<Signer>,
+    wallet: &mut Account<AgentWallet>,
+    payee: Pubkey,
+    mint: Option<Pubkey>,
+    amount: u64,
+    references: &[Pubkey],
+    resource_hash: [u8; 32],
+) -> Result<()> {
+    let receipt = match receipt.as_mut() {
+        Some(receipt) => receipt,
+        None => return Ok(()),
+    };
+    let clock = Clock::get()?;
+    receipt.payer = wallet.key();
+    receipt.nonce = wallet.receipt_nonce;
+    receipt.payee = payee;
+    receipt.mint = mint;
+    receipt.amount = amount;
+    // Pubkey::default() = no reference attached
+    receipt.reference = references.first().copied().unwrap_or_default();
+    receipt.slot = clock.slot;
+    receipt.created_at = clock.unix_timestamp;
+    receipt.rent_payer = rent_payer
+        .as_ref()
+        .map(|p| p.key())
+        .ok_or(ClawWalletError::ReceiptPayerRequired)?;
+    receipt.bump = *bump.ok_or(ClawWalletError::ReceiptPayerRequired)?;
+    receipt.refunded = 0;
+    receipt.resource_hash = resource_hash;
+    wallet.receipt_nonce += 1;
+
+    emit!(ReceiptCreated {
+        receipt: receipt.key(),
+        payer: receipt.payer,
+        nonce: receipt.nonce,
+        payee,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Fee the Token-2022 transfer-fee extension withholds from a transfer of `amount` (0 for legacy SPL
+/// Token mints and mints without the extension)
+fn mint_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => Ok(transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ClawWalletError::MathOverflow)?),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Split off the accounts a Token-2022 transfer hook needs: when `mint` has a hook, the leading
+/// `accounts` are the hook program, its extra-account-metas PDA and the extra accounts it lists.
+/// Returns them and the accounts after. One group serves every leg of a send (recipient, treasury,
+/// referrer), so hooks whose extra accounts depend on the destination are not supported
+fn transfer_hook_accounts<'a, 'info>(
+    mint: &AccountInfo<'info>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok((&[], accounts));
+    }
+    let hook_program = {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+        match state
+            .get_extension::<TransferHook>()
+            .ok()
+            .and_then(|hook| Option::<Pubkey>::from(hook.program_id))
+        {
+            Some(hook_program) => hook_program,
+            None => return Ok((&[], accounts)),
+        }
+    };
+    split_hook_accounts(mint.key, &hook_program, accounts)
+}
+
+/// Split `accounts` after `hook_program`, its extra-account-metas PDA for `mint` and the extra accounts
+/// the PDA lists
+fn split_hook_accounts<'a, 'info>(
+    mint: &Pubkey,
+    hook_program: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let (validation, _) =
+        Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], hook_program);
+    require!(
+        accounts.len() >= 2
+            && accounts[0].key() == *hook_program
+            && accounts[1].key() == validation,
+        ClawWalletError::InvalidTransferHook
+    );
+    // The metas PDA holds a TLV entry (8-byte discriminator, 4-byte length) whose value starts with the
+    // u32 count of extra accounts
+    let extra = {
+        let data = accounts[1].try_borrow_data()?;
+        match data.get(12..16) {
+            Some(count) => u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize,
+            None => 0,
+        }
+    };
+    require!(
+        accounts.len() >= 2 + extra,
+        ClawWalletError::InvalidTransferHook
+    );
+    Ok(accounts.split_at(2 + extra))
+}
+
+/// `transfer_checked` from one of the wallet's token accounts, forwarding any transfer-hook accounts
+fn transfer_checked_from_wallet<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, InterfaceTokenAccount>,
+    mint: &InterfaceAccount<'info, InterfaceMint>,
+    to: AccountInfo<'info>,
+    wallet: &Account<'info, AgentWallet>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"wallet".as_ref(),
+        wallet.agent_id.as_bytes(),
+        &[wallet.bump],
+    ];
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        &token_program.key(),
+        &from.key(),
+        &mint.key(),
+        to.key,
+        &wallet.key(),
+        &[],
+        amount,
+        mint.decimals,
+    )?;
+    ix.accounts
+        .extend(hook_accounts.iter().map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: false,
+            is_writable: account.is_writable,
+        }));
+    let mut infos = vec![
+        from.to_account_info(),
+        mint.to_account_info(),
+        to,
+        wallet.to_account_info(),
+    ];
+    infos.extend_from_slice(hook_accounts);
+    invoke_signed(&ix, &infos, &[&seeds[..]])?;
+    Ok(())
+}
+
+/// Invoke a Token-2022 confidential transfer extension instruction with the wallet PDA as the token
+/// account owner, appended after `metas`
+fn invoke_confidential<'info>(
+    token_program: &Program<'info, Token2022>,
+    instruction: u8,
+    payload: &[u8],
+    mut metas: Vec<AccountMeta>,
+    mut infos: Vec<AccountInfo<'info>>,
+    wallet: &Account<'info, AgentWallet>,
+) -> Result<()> {
+    let seeds = &[
+        b"wallet".as_ref(),
+        wallet.agent_id.as_bytes(),
+        &[wallet.bump],
+    ];
+    metas.push(AccountMeta::new_readonly(wallet.key(), true));
+    infos.push(wallet.to_account_info());
+    let mut data = vec![CT_EXTENSION_IX, instruction];
+    data.extend_from_slice(payload);
+    let ix = Instruction {
+        program_id: token_program.key(),
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&ix, &infos, &[&seeds[..]])?;
+    Ok(())
+}
+
+/// `amount` as holders see it, in raw units: Token-2022 interest-bearing mints display balances with
+/// accrued interest, so scale by the mint's interest to date (unchanged for every other mint)
+fn interest_adjusted_amount(
+    mint: &InterfaceAccount<InterfaceMint>,
+    amount: u64,
+    now: i64,
+) -> Result<u64> {
+    let info = mint.to_account_info();
+    if info.owner != &spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<Token2022Mint>::unpack(&data)?;
+    let interest = match state.get_extension::<InterestBearingConfig>() {
+        Ok(interest) => interest,
+        Err(_) => return Ok(amount),
+    };
+    // Continuously compounded: the average rate up to the last rate change, then the current rate since
+    let initialized_at = i64::from(interest.initialization_timestamp);
+    let updated_at = i64::from(interest.last_update_timestamp);
+    let rate_seconds = i16::from(interest.pre_update_average_rate) as i128
+        * (updated_at - initialized_at) as i128
+        + i16::from(interest.current_rate) as i128 * (now - updated_at) as i128;
+    compound_interest(amount, rate_seconds)
+}
+
+/// `amount` grown by e^(r·t) in integer fixed point, where `rate_seconds` is the sum of rate (bps) × elapsed
+/// seconds over the periods at each rate; a negative sum shrinks it
+fn compound_interest(amount: u64, rate_seconds: i128) -> Result<u64> {
+    let exponent =
+        rate_seconds.unsigned_abs() * INTEREST_SCALE / (INTEREST_SECONDS_PER_YEAR * 10_000);
+    // e^x = e^whole · e^fraction; the fraction's Taylor series converges quickly on [0, 1)
+    let fraction = exponent % INTEREST_SCALE;
+    let mut growth = INTEREST_SCALE;
+    let mut term = INTEREST_SCALE;
+    for k in 1..=24u128 {
+        term = term * fraction / INTEREST_SCALE / k;
+        if term == 0 {
+            break;
+        }
+        growth += term;
+    }
+    for _ in 0..exponent / INTEREST_SCALE {
+        growth = growth
+            .checked_mul(E_SCALED)
+            .ok_or(ClawWalletError::MathOverflow)?
+            / INTEREST_SCALE;
+    }
+
+    let scaled = if rate_seconds >= 0 {
+        (amount as u128)
+            .checked_mul(growth)
+            .ok_or(ClawWalletError::MathOverflow)?
+            / INTEREST_SCALE
+    } else {
+        amount as u128 * INTEREST_SCALE / growth
+    };
+    u64::try_from(scaled).map_err(|_| error!(ClawWalletError::MathOverflow))
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: String)]
+pub struct CreateWallet<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AgentWallet::INIT_SPACE,
+        seeds = [b"wallet", agent_id.as_bytes()],
+        bump
+    )]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREATE_WALLET) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SendSol<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: Recipient can be any account
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// Required when the sender was referred; receives the referral share of the fee
+    #[account(mut)]
+    pub referrer: Option<Account<'info, AgentWallet>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Active points campaign to apply, if any
+    #[account(seeds = [b"points_campaign", points_campaign.id.to_le_bytes().as_ref()], bump = points_campaign.bump)]
+    pub points_campaign: Option<Account<'info, PointsCampaign>>,
+    /// Quest to advance, together with the sender's progress account
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Option<Account<'info, Quest>>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest_progress.quest.as_ref(), wallet.key().as_ref()],
+        bump = quest_progress.bump
+    )]
+    pub quest_progress: Option<Account<'info, QuestProgress>>,
+    /// CHECK: Pyth price account for USD-value points when the sent asset has a configured feed; raw points if omitted
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+    /// Payment receipt to create for this transfer, if wanted
+    #[account(
+        init,
+        payer = receipt_payer,
+        space = 8 + PaymentReceipt::INIT_SPACE,
+        seeds = [b"receipt", wallet.key().as_ref(), wallet.receipt_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Required with `receipt`; pays its rent
+    #[account(mut)]
+    pub receipt_payer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SendToAgent<'info> {
+    #[account(mut, constraint = !from_wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub from_wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub to_wallet: Account<'info, AgentWallet>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TO_AGENT) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", from_wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", from_wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", from_wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", from_wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// Required when the sender was referred; receives the referral share of the fee
+    #[account(mut)]
+    pub referrer: Option<Account<'info, AgentWallet>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Active points campaign to apply, if any
+    #[account(seeds = [b"points_campaign", points_campaign.id.to_le_bytes().as_ref()], bump = points_campaign.bump)]
+    pub points_campaign: Option<Account<'info, PointsCampaign>>,
+    /// Quest to advance, together with the sender's progress account
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Option<Account<'info, Quest>>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest_progress.quest.as_ref(), from_wallet.key().as_ref()],
+        bump = quest_progress.bump
+    )]
+    pub quest_progress: Option<Account<'info, QuestProgress>>,
+    /// CHECK: Pyth price account for USD-value points when the sent asset has a configured feed; raw points if omitted
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+    /// Payment receipt to create for this transfer, if wanted
+    #[account(
+        init,
+        payer = receipt_payer,
+        space = 8 + PaymentReceipt::INIT_SPACE,
+        seeds = [b"receipt", from_wallet.key().as_ref(), from_wallet.receipt_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Required with `receipt`; pays its rent
+    #[account(mut)]
+    pub receipt_payer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SendToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+
+    /// Token mint (USDC or another SPL Token or Token-2022 mint)
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+
+    /// Wallet's token account
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// Recipient's token account
+    #[account(mut, constraint = recipient_token_account.mint == mint.key() @ ClawWalletError::MintMismatch)]
+    pub recipient_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// Treasury's token account for fees (ATA of the treasury, or of the fee vault while accruing)
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address_with_program_id(&config.fee_recipient(), &mint.key(), &token_program.key())
+            @ ClawWalletError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// CHECK: Per-mint fee PDA; may be uninitialized when the mint uses the default rate
+    #[account(seeds = [b"mint_fee", mint.key().as_ref()], bump)]
+    pub mint_fee: UncheckedAccount<'info>,
+    /// Required when the sender was referred: the referrer wallet's token account for this mint
+    #[account(mut, constraint = referrer_token_account.mint == mint.key() @ ClawWalletError::MintMismatch)]
+    pub referrer_token_account: Option<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Active points campaign to apply, if any
+    #[account(seeds = [b"points_campaign", points_campaign.id.to_le_bytes().as_ref()], bump = points_campaign.bump)]
+    pub points_campaign: Option<Account<'info, PointsCampaign>>,
+    /// Quest to advance, together with the sender's progress account
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Option<Account<'info, Quest>>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest_progress.quest.as_ref(), wallet.key().as_ref()],
+        bump = quest_progress.bump
+    )]
+    pub quest_progress: Option<Account<'info, QuestProgress>>,
+    /// CHECK: Pyth price account for USD-value points when the sent asset has a configured feed; raw points if omitted
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+    /// Payment receipt to create for this transfer, if wanted
+    #[account(
+        init,
+        payer = receipt_payer,
+        space = 8 + PaymentReceipt::INIT_SPACE,
+        seeds = [b"receipt", wallet.key().as_ref(), wallet.receipt_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Required with `receipt`; pays its rent
+    #[account(mut)]
+    pub receipt_payer: Option<Signer<'info>>,
+    /// Required with `receipt`
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSpendPolicy<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SpendPolicy::INIT_SPACE,
+        seeds = [b"policy", wallet.key().as_ref()],
+        bump
+    )]
+    pub spend_policy: Account<'info, SpendPolicy>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpendPolicy<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", wallet.key().as_ref()],
+        bump = spend_policy.bump
+    )]
+    pub spend_policy: Account<'info, SpendPolicy>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateWallet<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session", wallet.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"session", wallet.key().as_ref(), session.signer.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionKey>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: String)]
+pub struct CreateMultisigWallet<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AgentWallet::INIT_SPACE,
+        seeds = [b"wallet", agent_id.as_bytes()],
+        bump
+    )]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [b"multisig", wallet.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREATE_WALLET) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTransaction<'info> {
+    #[account(mut, seeds = [b"multisig", multisig.wallet.as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + PendingTransaction::INIT_SPACE,
+        seeds = [b"pending_tx", multisig.wallet.as_ref(), multisig.tx_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_tx: Account<'info, PendingTransaction>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MULTISIG) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveTransaction<'info> {
+    #[account(seeds = [b"multisig", multisig.wallet.as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, constraint = pending_tx.wallet == multisig.wallet)]
+    pub pending_tx: Account<'info, PendingTransaction>,
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MULTISIG) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTransaction<'info> {
+    #[account(has_one = wallet, seeds = [b"multisig", wallet.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = proposer, has_one = recipient, close = proposer)]
+    pub pending_tx: Account<'info, PendingTransaction>,
+    /// CHECK: Receives the pending transaction's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+    /// CHECK: Checked against the pending transaction
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MULTISIG | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTokenTransaction<'info> {
+    #[account(has_one = wallet, seeds = [b"multisig", wallet.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = proposer, close = proposer)]
+    pub pending_tx: Account<'info, PendingTransaction>,
+    /// CHECK: Receives the pending transaction's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = pending_tx.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MULTISIG | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(
+        mut,
+        constraint = wallet.pending_owner != Pubkey::default() @ ClawWalletError::NoPendingOwner,
+        constraint = wallet.pending_owner == new_owner.key() @ ClawWalletError::Unauthorized
+    )]
+    pub wallet: Account<'info, AgentWallet>,
+    pub new_owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RecoveryConfig::INIT_SPACE,
+        seeds = [b"recovery", wallet.key().as_ref()],
+        bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_RECOVERY) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianAction<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"recovery", wallet.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+    pub guardian: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_RECOVERY) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverWallet<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"recovery", wallet.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_RECOVERY) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"recovery", wallet.key().as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, RecoveryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInheritedWallet<'info> {
+    #[account(
+        mut,
+        constraint = wallet.beneficiary != Pubkey::default() @ ClawWalletError::Unauthorized,
+        constraint = wallet.beneficiary == beneficiary.key() @ ClawWalletError::Unauthorized
+    )]
+    pub wallet: Account<'info, AgentWallet>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_RECOVERY) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyAction<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Clawwallet>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ClawWalletError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RollSeason<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+    /// Required once the leaderboard exists
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAllowlist<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + RecipientAllowlist::INIT_SPACE,
+        seeds = [b"allowlist", wallet.key().as_ref()],
+        bump
+    )]
+    pub allowlist: Account<'info, RecipientAllowlist>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAllowlist<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"allowlist", wallet.key().as_ref()],
+        bump = allowlist.bump
+    )]
+    pub allowlist: Account<'info, RecipientAllowlist>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDenylist<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Denylist::INIT_SPACE,
+        seeds = [b"denylist", wallet.key().as_ref()],
+        bump
+    )]
+    pub denylist: Account<'info, Denylist>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ManageDenylist<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"denylist", wallet.key().as_ref()],
+        bump = denylist.bump
+    )]
+    pub denylist: Account<'info, Denylist>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProtocolDenylist<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Denylist::INIT_SPACE,
+        seeds = [b"denylist"],
+        bump
+    )]
+    pub denylist: Account<'info, Denylist>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageProtocolDenylist<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"denylist"], bump = denylist.bump)]
+    pub denylist: Account<'info, Denylist>,
+}
+
+#[derive(Accounts)]
+pub struct QueueTransfer<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key; pays the queue entry's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QueuedTransfer::INIT_SPACE,
+        seeds = [b"queued", wallet.key().as_ref(), wallet.queue_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_transfer: Account<'info, QueuedTransfer>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when a delegated signer queues on a wallet with a spend policy
+    #[account(seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteQueuedTransfer<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = queued_by, has_one = recipient, close = queued_by)]
+    pub queued_transfer: Account<'info, QueuedTransfer>,
+    /// CHECK: Receives the queue entry's rent
+    #[account(mut)]
+    pub queued_by: AccountInfo<'info>,
+    /// CHECK: Checked against the queued transfer
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteQueuedTokenTransfer<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = queued_by, close = queued_by)]
+    pub queued_transfer: Account<'info, QueuedTransfer>,
+    /// CHECK: Receives the queue entry's rent
+    #[account(mut)]
+    pub queued_by: AccountInfo<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = queued_transfer.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelQueuedTransfer<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = wallet, has_one = queued_by, close = queued_by)]
+    pub queued_transfer: Account<'info, QueuedTransfer>,
+    /// CHECK: Receives the queue entry's rent
+    #[account(mut)]
+    pub queued_by: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovers<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ApproverSet::INIT_SPACE,
+        seeds = [b"approvers", wallet.key().as_ref()],
+        bump
+    )]
+    pub approver_set: Account<'info, ApproverSet>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposePayment<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key; pays the proposal's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PaymentProposal::INIT_SPACE,
+        seeds = [b"proposal", wallet.key().as_ref(), wallet.proposal_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, PaymentProposal>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+pub struct ApprovePayment<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet)]
+    pub proposal: Account<'info, PaymentProposal>,
+    /// Wallet owner or a registered approver
+    pub approver: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when `approver` is not the owner
+    #[account(seeds = [b"approvers", wallet.key().as_ref()], bump = approver_set.bump)]
+    pub approver_set: Option<Account<'info, ApproverSet>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecutePayment<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = proposer, has_one = recipient, close = proposer)]
+    pub proposal: Account<'info, PaymentProposal>,
+    /// CHECK: Receives the proposal's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+    /// CHECK: Checked against the proposal
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Required when the proposal was approved by approver quorum
+    #[account(seeds = [b"approvers", wallet.key().as_ref()], bump = approver_set.bump)]
+    pub approver_set: Option<Account<'info, ApproverSet>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTokenPayment<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = proposer, close = proposer)]
+    pub proposal: Account<'info, PaymentProposal>,
+    /// CHECK: Receives the proposal's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = proposal.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Required when the proposal was approved by approver quorum
+    #[account(seeds = [b"approvers", wallet.key().as_ref()], bump = approver_set.bump)]
+    pub approver_set: Option<Account<'info, ApproverSet>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct RejectPayment<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = wallet, has_one = proposer, close = proposer)]
+    pub proposal: Account<'info, PaymentProposal>,
+    /// CHECK: Receives the proposal's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteQueuedBatch<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_QUEUE | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// Fee waiver for the wallet, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredSession<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// CHECK: Receives the session's rent
+    #[account(mut, address = wallet.owner)]
+    pub owner: AccountInfo<'info>,
+    #[account(mut, has_one = wallet, close = owner)]
+    pub session: Account<'info, SessionKey>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredQueuedTransfer<'info> {
+    #[account(mut, has_one = queued_by, close = queued_by)]
+    pub queued_transfer: Account<'info, QueuedTransfer>,
+    /// CHECK: Receives the queue entry's rent
+    #[account(mut)]
+    pub queued_by: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut, address = queued_transfer.wallet)]
+    pub wallet: Account<'info, AgentWallet>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredProposal<'info> {
+    #[account(mut, has_one = proposer, close = proposer)]
+    pub proposal: Account<'info, PaymentProposal>,
+    /// CHECK: Receives the proposal's rent
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseWallet<'info> {
+    #[account(mut, has_one = owner, close = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// CHECK: Spend policy PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump)]
+    pub spend_policy: UncheckedAccount<'info>,
+    /// CHECK: Allowlist PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"allowlist", wallet.key().as_ref()], bump)]
+    pub allowlist: UncheckedAccount<'info>,
+    /// CHECK: Denylist PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"denylist", wallet.key().as_ref()], bump)]
+    pub denylist: UncheckedAccount<'info>,
+    /// CHECK: Recovery (guardians) PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"recovery", wallet.key().as_ref()], bump)]
+    pub recovery: UncheckedAccount<'info>,
+    /// CHECK: Approver set PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"approvers", wallet.key().as_ref()], bump)]
+    pub approver_set: UncheckedAccount<'info>,
+    /// CHECK: Metadata PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"metadata", wallet.key().as_ref()], bump)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: Multisig PDA; closed with the wallet if it exists
+    #[account(mut, seeds = [b"multisig", wallet.key().as_ref()], bump)]
+    pub multisig: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Owner and discriminator are checked by the instruction; realloc'd in place
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+    /// Pays the rent for the added space
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + AgentMetadata::INIT_SPACE,
+        seeds = [b"metadata", wallet.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, AgentMetadata>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct AddFeeExemption<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeeExemption::INIT_SPACE,
+        seeds = [b"fee_exempt", subject.as_ref()],
+        bump
+    )]
+    pub exemption: Account<'info, FeeExemption>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"fee_exempt", exemption.subject.as_ref()],
+        bump = exemption.bump
+    )]
+    pub exemption: Account<'info, FeeExemption>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteFee<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// Per-mint fee PDA for token quotes, if the mint has one
+    #[account(seeds = [b"mint_fee", mint_fee.mint.as_ref()], bump = mint_fee.bump)]
+    pub mint_fee: Option<Account<'info, MintFee>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFeeCampaign<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeeCampaign::INIT_SPACE,
+        seeds = [b"campaign", config.campaign_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Account<'info, FeeCampaign>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeCampaign<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, FeeCampaign>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintFee<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: Mint the rate applies to
+    pub mint: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintFee::INIT_SPACE,
+        seeds = [b"mint_fee", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_fee: Account<'info, MintFee>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer_agent_id: String)]
+pub struct RegisterReferral<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"wallet", referrer_agent_id.as_bytes()], bump = referrer.bump)]
+    pub referrer: Account<'info, AgentWallet>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreateReferralCode<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ReferralCode::INIT_SPACE,
+        seeds = [b"referral_code", code.as_bytes()],
+        bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct RegisterReferralCode<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"referral_code", code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Account<'info, ReferralCode>,
+    /// The code's wallet; may not share the registering wallet's owner
+    #[account(address = referral_code.wallet @ ClawWalletError::InvalidReferrer)]
+    pub referrer: Account<'info, AgentWallet>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_WALLET_ADMIN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFeeVault<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeeVault::INIT_SPACE,
+        seeds = [b"fee_vault"],
+        bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = treasury)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub treasury: Signer<'info>,
+    #[account(mut, seeds = [b"fee_vault"], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = treasury)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub treasury: Signer<'info>,
+    #[account(seeds = [b"fee_vault"], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(mut, constraint = vault_token_account.owner == fee_vault.key())]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, constraint = treasury_token_account.mint == vault_token_account.mint @ ClawWalletError::MintMismatch)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut, seeds = [b"fee_vault"], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeTokenFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(seeds = [b"fee_vault"], bump = fee_vault.bump)]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(mut, constraint = vault_token_account.owner == fee_vault.key())]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPoints<'info> {
+    #[account(mut, has_one = owner, constraint = !from_wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub from_wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, constraint = to_wallet.key() != from_wallet.key() @ ClawWalletError::SameWallet)]
+    pub to_wallet: Account<'info, AgentWallet>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLeaderboard<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Leaderboard>(),
+        seeds = [b"leaderboard"],
+        bump
+    )]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitScore<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRewardVault<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RewardVault::INIT_SPACE,
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EpochReward::INIT_SPACE,
+        seeds = [b"epoch", config.epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub epoch_reward: Account<'info, EpochReward>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEpochReward<'info> {
+    #[account(mut, seeds = [b"epoch", epoch_reward.epoch.to_le_bytes().as_ref()], bump = epoch_reward.bump)]
+    pub epoch_reward: Account<'info, EpochReward>,
+    #[account(mut, seeds = [b"reward_vault"], bump = reward_vault.bump)]
+    pub reward_vault: Account<'info, RewardVault>,
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEpochTokenReward<'info> {
+    #[account(mut, seeds = [b"epoch", epoch_reward.epoch.to_le_bytes().as_ref()], bump = epoch_reward.bump)]
+    pub epoch_reward: Account<'info, EpochReward>,
+    #[account(seeds = [b"reward_vault"], bump = reward_vault.bump)]
+    pub reward_vault: Account<'info, RewardVault>,
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, constraint = vault_token_account.owner == reward_vault.key())]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault_token_account.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BurnPoints<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+#[instruction(badge: u8)]
+pub struct MintBadge<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Badge::INIT_SPACE,
+        seeds = [b"badge", wallet.key().as_ref(), &[badge]],
+        bump
+    )]
+    pub badge: Account<'info, Badge>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRewardMint<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"reward_mint"],
+        bump,
+        mint::decimals = REWARD_MINT_DECIMALS,
+        mint::authority = config
+    )]
+    pub reward_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = reward_mint,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = reward_mint,
+        associated_token::authority = wallet
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDistributor<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Distributor::INIT_SPACE,
+        seeds = [b"distributor", config.distributor_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub distributor: Account<'info, Distributor>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDistributor<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"distributor", distributor.id.to_le_bytes().as_ref()], bump = distributor.bump)]
+    pub distributor: Account<'info, Distributor>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDistribution<'info> {
+    #[account(mut, seeds = [b"distributor", distributor.id.to_le_bytes().as_ref()], bump = distributor.bump)]
+    pub distributor: Account<'info, Distributor>,
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimStatus::INIT_SPACE,
+        seeds = [b"claim", distributor.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub claim_status: Account<'info, ClaimStatus>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenDistribution<'info> {
+    #[account(mut, seeds = [b"distributor", distributor.id.to_le_bytes().as_ref()], bump = distributor.bump)]
+    pub distributor: Account<'info, Distributor>,
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimStatus::INIT_SPACE,
+        seeds = [b"claim", distributor.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub claim_status: Account<'info, ClaimStatus>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, constraint = vault_token_account.owner == distributor.key())]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault_token_account.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePointsCampaign<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PointsCampaign::INIT_SPACE,
+        seeds = [b"points_campaign", config.points_campaign_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Account<'info, PointsCampaign>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePointsCampaign<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"points_campaign", campaign.id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, PointsCampaign>,
+}
+
+#[derive(Accounts)]
+pub struct CreateQuest<'info> {
+    #[account(mut, has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Quest::INIT_SPACE,
+        seeds = [b"quest", config.quest_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub quest: Account<'info, Quest>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseQuest<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, close = admin, seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+}
+
+#[derive(Accounts)]
+pub struct StartQuest<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + QuestProgress::INIT_SPACE,
+        seeds = [b"quest_progress", quest.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub progress: Account<'info, QuestProgress>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimQuestReward<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Account<'info, Quest>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest.key().as_ref(), wallet.key().as_ref()],
+        bump = progress.bump
+    )]
+    pub progress: Account<'info, QuestProgress>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_POINTS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SendSolBatch<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// Required when the sender was referred; receives the referral share of the fee
+    #[account(mut)]
+    pub referrer: Option<Account<'info, AgentWallet>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+#[derive(Accounts)]
+pub struct SendTokenBatch<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// Token mint sent to every recipient (SPL Token or Token-2022)
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// Treasury's token account for fees (ATA of the treasury, or of the fee vault while accruing)
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address_with_program_id(&config.fee_recipient(), &mint.key(), &token_program.key())
+            @ ClawWalletError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// CHECK: Per-mint fee PDA; may be uninitialized when the mint uses the default rate
+    #[account(seeds = [b"mint_fee", mint.key().as_ref()], bump)]
+    pub mint_fee: UncheckedAccount<'info>,
+    /// Required when the sender was referred: the referrer wallet's token account for this mint
+    #[account(mut, constraint = referrer_token_account.mint == mint.key() @ ClawWalletError::MintMismatch)]
+    pub referrer_token_account: Option<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+#[derive(Accounts)]
+pub struct SendMulti<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: Recipient can be any account; token legs must be owned by it
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// SPL Token or Token-2022; every token leg's mint must belong to it
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) && config.is_active(PAUSE_SEND_TOKEN)
+            @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Required when a memo is attached
+    pub memo_program: Option<Program<'info, Memo>>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply to the legs in its asset, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+    /// Required when the sender was referred and the send has a SOL leg; receives the referral share
+    #[account(mut)]
+    pub referrer: Option<Account<'info, AgentWallet>>,
+    /// Required when the sender signed up through a referral code
+    #[account(mut, seeds = [b"referral_code", referral_code.code.as_bytes()], bump = referral_code.bump)]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+    /// Points leaderboard to refresh, if the caller wants to keep it current
+    #[account(mut, seeds = [b"leaderboard"], bump = leaderboard.load()?.bump)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    /// Active points campaign to apply to the legs in its asset, if any
+    #[account(seeds = [b"points_campaign", points_campaign.id.to_le_bytes().as_ref()], bump = points_campaign.bump)]
+    pub points_campaign: Option<Account<'info, PointsCampaign>>,
+    /// Quest to advance, together with the sender's progress account
+    #[account(seeds = [b"quest", quest.id.to_le_bytes().as_ref()], bump = quest.bump)]
+    pub quest: Option<Account<'info, Quest>>,
+    #[account(
+        mut,
+        seeds = [b"quest_progress", quest_progress.quest.as_ref(), wallet.key().as_ref()],
+        bump = quest_progress.bump
+    )]
+    pub quest_progress: Option<Account<'info, QuestProgress>>,
+}
+
+#[derive(Accounts)]
+pub struct SchedulePayment<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the scheduled payment's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ScheduledPayment::INIT_SPACE,
+        seeds = [b"scheduled", wallet.key().as_ref(), wallet.schedule_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub scheduled_payment: Account<'info, ScheduledPayment>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SCHEDULE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteScheduledPayment<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = payer, has_one = recipient, close = payer)]
+    pub scheduled_payment: Account<'info, ScheduledPayment>,
+    /// CHECK: Receives the scheduled payment's rent
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: Checked against the scheduled payment
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SCHEDULE | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteScheduledTokenPayment<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, has_one = payer, close = payer)]
+    pub scheduled_payment: Account<'info, ScheduledPayment>,
+    /// CHECK: Receives the scheduled payment's rent
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = scheduled_payment.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SCHEDULE | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// Fee waiver for the wallet or the recipient, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelScheduledPayment<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = wallet, has_one = payer, close = payer)]
+    pub scheduled_payment: Account<'info, ScheduledPayment>,
+    /// CHECK: Receives the scheduled payment's rent
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ChargeSubscription<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = merchant,
+        seeds = [b"subscription", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    /// CHECK: The merchant's settlement account; receives the charge
+    #[account(mut, address = merchant.settlement @ ClawWalletError::InvalidMerchant)]
+    pub settlement: AccountInfo<'info>,
+    /// The merchant authority or a keeper; keepers receive the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the merchant, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ChargeSubscriptionToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = merchant,
+        seeds = [b"subscription", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// The merchant's settlement token account
+    #[account(mut, address = merchant.settlement_token_account @ ClawWalletError::InvalidMerchant)]
+    pub merchant_token_account: Account<'info, TokenAccount>,
+    /// The merchant authority or a keeper; keepers receive the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the merchant, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSubscription<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"subscription", wallet.key().as_ref(), subscription.merchant.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"subscription", wallet.key().as_ref(), subscription.merchant.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterMerchant<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Merchant::INIT_SPACE,
+        seeds = [b"merchant", authority.key().as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, Merchant>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMerchant<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority, seeds = [b"merchant", authority.key().as_ref()], bump = merchant.bump)]
+    pub merchant: Account<'info, Merchant>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetMerchantTerms<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"merchant", merchant.authority.as_ref()], bump = merchant.bump)]
+    pub merchant: Account<'info, Merchant>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMandate<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Mandate::INIT_SPACE,
+        seeds = [b"mandate", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump
+    )]
+    pub mandate: Account<'info, Mandate>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMandate<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"mandate", wallet.key().as_ref(), mandate.merchant.as_ref()],
+        bump = mandate.bump
+    )]
+    pub mandate: Account<'info, Mandate>,
+}
+
+#[derive(Accounts)]
+pub struct ChargeMandate<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = merchant,
+        seeds = [b"mandate", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump = mandate.bump
+    )]
+    pub mandate: Account<'info, Mandate>,
+    #[account(
+        has_one = authority,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    pub authority: Signer<'info>,
+    /// CHECK: The merchant's settlement account; receives the charge
+    #[account(mut, address = merchant.settlement @ ClawWalletError::InvalidMerchant)]
+    pub settlement: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the merchant, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct ChargeMandateToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = merchant,
+        seeds = [b"mandate", wallet.key().as_ref(), merchant.key().as_ref()],
+        bump = mandate.bump
+    )]
+    pub mandate: Account<'info, Mandate>,
+    #[account(
+        has_one = authority,
+        seeds = [b"merchant", merchant.authority.as_ref()],
+        bump = merchant.bump,
+        constraint = merchant.active @ ClawWalletError::MerchantInactive
+    )]
+    pub merchant: Account<'info, Merchant>,
+    pub authority: Signer<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// The merchant's settlement token account
+    #[account(mut, address = merchant.settlement_token_account @ ClawWalletError::InvalidMerchant)]
+    pub merchant_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SUBSCRIPTIONS | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the wallet or the merchant, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the stream's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Stream::INIT_SPACE,
+        seeds = [b"stream", wallet.key().as_ref(), wallet.stream_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub stream: Account<'info, Stream>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_STREAMS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromStream<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = recipient,
+        seeds = [b"stream", wallet.key().as_ref(), stream.nonce.to_le_bytes().as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, Stream>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_STREAMS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Receives the stream's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        has_one = recipient,
+        seeds = [b"stream", wallet.key().as_ref(), stream.nonce.to_le_bytes().as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, Stream>,
+    /// CHECK: Checked against the stream; receives the accrued balance
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_STREAMS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStream<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"stream", wallet.key().as_ref(), stream.nonce.to_le_bytes().as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, Stream>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_STREAMS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TransferStream<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = recipient,
+        seeds = [b"stream", wallet.key().as_ref(), stream.nonce.to_le_bytes().as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, Stream>,
+    pub recipient: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_STREAMS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStream<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// CHECK: Receives the stream's rent
+    #[account(mut, address = wallet.owner)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"stream", wallet.key().as_ref(), stream.nonce.to_le_bytes().as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, Stream>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVestingGrant<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the grant's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VestingGrant::INIT_SPACE,
+        seeds = [b"vesting", wallet.key().as_ref(), wallet.vesting_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub grant: Account<'info, VestingGrant>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_VESTING) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = beneficiary,
+        seeds = [b"vesting", wallet.key().as_ref(), grant.nonce.to_le_bytes().as_ref()],
+        bump = grant.bump
+    )]
+    pub grant: Account<'info, VestingGrant>,
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_VESTING) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeUnvested<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"vesting", wallet.key().as_ref(), grant.nonce.to_le_bytes().as_ref()],
+        bump = grant.bump
+    )]
+    pub grant: Account<'info, VestingGrant>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_VESTING) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVestingGrant<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// CHECK: Receives the grant's rent
+    #[account(mut, address = wallet.owner)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"vesting", wallet.key().as_ref(), grant.nonce.to_le_bytes().as_ref()],
+        bump = grant.bump
+    )]
+    pub grant: Account<'info, VestingGrant>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePayroll<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the payroll's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Payroll::INIT_SPACE,
+        seeds = [b"payroll", wallet.key().as_ref()],
+        bump
+    )]
+    pub payroll: Account<'info, Payroll>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SCHEDULE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePayroll<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, has_one = wallet, seeds = [b"payroll", wallet.key().as_ref()], bump = payroll.bump)]
+    pub payroll: Account<'info, Payroll>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePayroll<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"payroll", wallet.key().as_ref()],
+        bump = payroll.bump
+    )]
+    pub payroll: Account<'info, Payroll>,
+}
+
+#[derive(Accounts)]
+pub struct RunPayroll<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut, has_one = wallet, seeds = [b"payroll", wallet.key().as_ref()], bump = payroll.bump)]
+    pub payroll: Account<'info, Payroll>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SCHEDULE | PAUSE_SEND_SOL | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Whoever runs the crank; receives the keeper tip
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateEscrow<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the escrow's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(constraint = payee_wallet.key() != wallet.key() @ ClawWalletError::InvalidEscrow)]
+    pub payee_wallet: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", wallet.key().as_ref(), wallet.escrow_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Required when an arbiter is designated
+    pub arbiter_bond: Option<Account<'info, ArbiterBond>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTokenEscrow<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the escrow's and vault's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(constraint = payee_wallet.key() != wallet.key() @ ClawWalletError::InvalidEscrow)]
+    pub payee_wallet: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", wallet.key().as_ref(), wallet.escrow_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"escrow_vault", escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Required when an arbiter is designated
+    pub arbiter_bond: Option<Account<'info, ArbiterBond>>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = payee,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: The payee agent wallet, checked against the escrow
+    #[account(mut)]
+    pub payee: AccountInfo<'info>,
+    /// CHECK: Receives the escrow's rent once the last milestone is released
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseTokenEscrow<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = payee_token_account.owner == escrow.payee @ ClawWalletError::InvalidEscrow,
+        constraint = payee_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub payee_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &vault.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the escrow's and vault's rent once the last milestone is released
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundEscrow<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(has_one = owner, address = escrow.payee @ ClawWalletError::InvalidEscrow)]
+    pub payee_wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    /// CHECK: Receives the escrow's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundTokenEscrow<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(has_one = owner, address = escrow.payee @ ClawWalletError::InvalidEscrow)]
+    pub payee_wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    /// CHECK: Receives the escrow's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpiredEscrow<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    /// CHECK: Receives the escrow's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpiredTokenEscrow<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the escrow's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeEscrow<'info> {
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(address = escrow.payee @ ClawWalletError::InvalidEscrow)]
+    pub payee_wallet: Account<'info, AgentWallet>,
+    /// Owner of the payer or payee wallet
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveEscrowDispute<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = payee,
+        has_one = rent_payer,
+        has_one = arbiter,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.disputed_at != 0 @ ClawWalletError::EscrowNotDisputed
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub arbiter: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter.key().as_ref()],
+        bump = arbiter_bond.bump,
+        constraint = arbiter_bond.is_eligible(&config) @ ClawWalletError::InsufficientBond
+    )]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+    /// CHECK: The payee agent wallet, checked against the escrow
+    #[account(mut)]
+    pub payee: AccountInfo<'info>,
+    /// CHECK: Receives the escrow's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveTokenEscrowDispute<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        has_one = arbiter,
+        seeds = [b"escrow", wallet.key().as_ref(), escrow.nonce.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.disputed_at != 0 @ ClawWalletError::EscrowNotDisputed
+    )]
+    pub escrow: Account<'info, Escrow>,
+    pub arbiter: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter.key().as_ref()],
+        bump = arbiter_bond.bump,
+        constraint = arbiter_bond.is_eligible(&config) @ ClawWalletError::InsufficientBond
+    )]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = payee_token_account.owner == escrow.payee @ ClawWalletError::InvalidEscrow,
+        constraint = payee_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub payee_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &vault.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the escrow's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterArbiter<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ArbiterBond::INIT_SPACE,
+        seeds = [b"arbiter", authority.key().as_ref()],
+        bump
+    )]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_ESCROW) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateArbiterBond<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"arbiter", authority.key().as_ref()],
+        bump = arbiter_bond.bump
+    )]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawArbiterBond<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [b"arbiter", authority.key().as_ref()],
+        bump = arbiter_bond.bump
+    )]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+}
+
+#[derive(Accounts)]
+pub struct SlashArbiter<'info> {
+    #[account(has_one = admin, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"arbiter", arbiter_bond.authority.as_ref()], bump = arbiter_bond.bump)]
+    pub arbiter_bond: Account<'info, ArbiterBond>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockHtlc<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the HTLC's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Htlc::INIT_SPACE,
+        seeds = [b"htlc", wallet.key().as_ref(), wallet.htlc_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct LockTokenHtlc<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the HTLC's and vault's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Htlc::INIT_SPACE,
+        seeds = [b"htlc", wallet.key().as_ref(), wallet.htlc_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"htlc_vault", htlc.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = htlc
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimHtlc<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = recipient,
+        has_one = rent_payer,
+        seeds = [b"htlc", wallet.key().as_ref(), htlc.nonce.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    /// CHECK: Checked against the HTLC
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Receives the HTLC's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenHtlc<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"htlc", wallet.key().as_ref(), htlc.nonce.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    #[account(mut, seeds = [b"htlc_vault", htlc.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == htlc.recipient @ ClawWalletError::InvalidHtlc,
+        constraint = recipient_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &vault.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the HTLC's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundHtlc<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"htlc", wallet.key().as_ref(), htlc.nonce.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    /// CHECK: Receives the HTLC's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundTokenHtlc<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"htlc", wallet.key().as_ref(), htlc.nonce.to_le_bytes().as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, Htlc>,
+    #[account(mut, seeds = [b"htlc_vault", htlc.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the HTLC's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_HTLC) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct SendClaimable<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the claimable's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Claimable::INIT_SPACE,
+        seeds = [
+            b"claimable",
+            recipient.as_ref(),
+            wallet.key().as_ref(),
+            wallet.claimable_nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct SendClaimableToken<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the claimable's and vault's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Claimable::INIT_SPACE,
+        seeds = [
+            b"claimable",
+            recipient.as_ref(),
+            wallet.key().as_ref(),
+            wallet.claimable_nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"claimable_vault", claimable.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = claimable
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTransfer<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = recipient,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            recipient.key().as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    /// CHECK: Receives the claimable's rent (the sender's owner)
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenTransfer<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = recipient,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            recipient.key().as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    /// Pays for their token account if it does not exist yet
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    #[account(mut, seeds = [b"claimable_vault", claimable.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = vault.mint @ ClawWalletError::MintMismatch)]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        associated_token::mint = mint,
+        associated_token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &mint.key())
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the claimable's and vault's rent (the sender's owner)
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelClaimable<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            claimable.recipient.as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    /// CHECK: Receives the claimable's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelClaimableToken<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            claimable.recipient.as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    #[account(mut, seeds = [b"claimable_vault", claimable.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the claimable's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReturnExpiredClaimable<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            claimable.recipient.as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    /// CHECK: Receives the claimable's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReturnExpiredClaimableToken<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [
+            b"claimable",
+            claimable.recipient.as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    #[account(mut, seeds = [b"claimable_vault", claimable.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the claimable's and vault's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInvoice<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the invoice's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Invoice::INIT_SPACE,
+        seeds = [b"invoice", wallet.key().as_ref(), wallet.invoice_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_INVOICES) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseInvoice<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        constraint = invoice.issuer == wallet.key() @ ClawWalletError::InvalidInvoice,
+        seeds = [b"invoice", wallet.key().as_ref(), invoice.nonce.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+}
+
+#[derive(Accounts)]
+pub struct PayInvoice<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = issuer,
+        seeds = [b"invoice", issuer.key().as_ref(), invoice.nonce.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    /// The issuing agent wallet
+    #[account(mut)]
+    pub issuer: Account<'info, AgentWallet>,
+    /// Required when the issuer has a revenue share
+    #[account(seeds = [b"revenue_share", issuer.key().as_ref()], bump = revenue_share.bump)]
+    pub revenue_share: Option<Account<'info, RevenueShare>>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_INVOICES | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct PayInvoiceToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = issuer,
+        seeds = [b"invoice", issuer.key().as_ref(), invoice.nonce.to_le_bytes().as_ref()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+    /// The issuing agent wallet
+    pub issuer: Account<'info, AgentWallet>,
+    /// Required when the issuer has a revenue share
+    #[account(seeds = [b"revenue_share", issuer.key().as_ref()], bump = revenue_share.bump)]
+    pub revenue_share: Option<Account<'info, RevenueShare>>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// Receives the payment unless the issuer has a revenue share
+    #[account(
+        mut,
+        constraint = issuer_token_account.owner == invoice.issuer @ ClawWalletError::InvalidInvoice,
+        constraint = issuer_token_account.mint == wallet_token_account.mint @ ClawWalletError::MintMismatch
+    )]
+    pub issuer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &wallet_token_account.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_INVOICES | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: The original payer; receives the refund
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// Receipt of the SOL payment `recipient` made to this wallet
+    #[account(
+        mut,
+        constraint = receipt.payee == wallet.key() @ ClawWalletError::ReceiptMismatch,
+        constraint = receipt.payer == recipient.key() @ ClawWalletError::ReceiptMismatch,
+        constraint = receipt.mint.is_none() @ ClawWalletError::ReceiptMismatch,
+        seeds = [b"receipt", receipt.payer.as_ref(), receipt.nonce.to_le_bytes().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, PaymentReceipt>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct RefundToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+    /// The original payer's token account
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == wallet_token_account.mint @ ClawWalletError::MintMismatch
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    /// Receipt of the token payment the recipient's owner made to this wallet
+    #[account(
+        mut,
+        constraint = receipt.payee == wallet.key() @ ClawWalletError::ReceiptMismatch,
+        constraint = receipt.payer == recipient_token_account.owner @ ClawWalletError::ReceiptMismatch,
+        constraint = receipt.mint == Some(wallet_token_account.mint) @ ClawWalletError::ReceiptMismatch,
+        seeds = [b"receipt", receipt.payer.as_ref(), receipt.nonce.to_le_bytes().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, PaymentReceipt>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRevenueShare<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the revenue share's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + RevenueShare::INIT_SPACE,
+        seeds = [b"revenue_share", wallet.key().as_ref()],
+        bump
+    )]
+    pub revenue_share: Account<'info, RevenueShare>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRevenueShare<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"revenue_share", wallet.key().as_ref()],
+        bump = revenue_share.bump
+    )]
+    pub revenue_share: Account<'info, RevenueShare>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRevenueShare<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"revenue_share", wallet.key().as_ref()],
+        bump = revenue_share.bump
+    )]
+    pub revenue_share: Account<'info, RevenueShare>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTransferToWallet<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        constraint = claimable.recipient == recipient_wallet.key() @ ClawWalletError::Unauthorized,
+        seeds = [
+            b"claimable",
+            recipient_wallet.key().as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    /// The agent wallet the claimable is addressed to
+    #[account(mut, has_one = owner)]
+    pub recipient_wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    /// Required when the receiving wallet has a revenue share
+    #[account(seeds = [b"revenue_share", recipient_wallet.key().as_ref()], bump = revenue_share.bump)]
+    pub revenue_share: Option<Account<'info, RevenueShare>>,
+    /// CHECK: Receives the claimable's rent (the sender's owner)
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenTransferToWallet<'info> {
+    #[account(mut)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet,
+        has_one = rent_payer,
+        constraint = claimable.recipient == recipient_wallet.key() @ ClawWalletError::Unauthorized,
+        seeds = [
+            b"claimable",
+            recipient_wallet.key().as_ref(),
+            wallet.key().as_ref(),
+            claimable.nonce.to_le_bytes().as_ref()
+        ],
+        bump = claimable.bump
+    )]
+    pub claimable: Account<'info, Claimable>,
+    /// The agent wallet the claimable is addressed to
+    #[account(has_one = owner)]
+    pub recipient_wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    /// Required when the receiving wallet has a revenue share
+    #[account(seeds = [b"revenue_share", recipient_wallet.key().as_ref()], bump = revenue_share.bump)]
+    pub revenue_share: Option<Account<'info, RevenueShare>>,
+    #[account(mut, seeds = [b"claimable_vault", claimable.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    /// Receives the tokens unless the wallet has a revenue share
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == recipient_wallet.key() @ ClawWalletError::Unauthorized,
+        constraint = recipient_token_account.mint == vault.mint @ ClawWalletError::MintMismatch
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address(&config.fee_recipient(), &vault.mint)
+            @ ClawWalletError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Receives the claimable's and vault's rent (the sender's owner)
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CLAIMABLE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Tip<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: Any SOL recipient
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCrowdfund<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the crowdfund's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Crowdfund::INIT_SPACE,
+        seeds = [b"crowdfund", wallet.key().as_ref(), wallet.crowdfund_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub crowdfund: Account<'info, Crowdfund>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CROWDFUND) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Contribute<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the contribution's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"crowdfund", crowdfund.wallet.as_ref(), crowdfund.nonce.to_le_bytes().as_ref()],
+        bump = crowdfund.bump
+    )]
+    pub crowdfund: Account<'info, Crowdfund>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", crowdfund.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CROWDFUND) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCrowdfund<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"crowdfund", wallet.key().as_ref(), crowdfund.nonce.to_le_bytes().as_ref()],
+        bump = crowdfund.bump
+    )]
+    pub crowdfund: Account<'info, Crowdfund>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CROWDFUND) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseContribution<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"crowdfund", crowdfund.wallet.as_ref(), crowdfund.nonce.to_le_bytes().as_ref()],
+        bump = crowdfund.bump
+    )]
+    pub crowdfund: Account<'info, Crowdfund>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = crowdfund,
+        has_one = wallet,
+        has_one = rent_payer,
+        seeds = [b"contribution", crowdfund.key().as_ref(), wallet.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+    /// CHECK: Receives the contribution's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCrowdfund<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"crowdfund", wallet.key().as_ref(), crowdfund.nonce.to_le_bytes().as_ref()],
+        bump = crowdfund.bump
+    )]
+    pub crowdfund: Account<'info, Crowdfund>,
+}
+
+#[derive(Accounts)]
+pub struct PostBounty<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the bounty's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Bounty::INIT_SPACE,
+        seeds = [b"bounty", wallet.key().as_ref(), wallet.bounty_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_BOUNTIES) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitBountyClaim<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the claim's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.wallet.as_ref(), bounty.nonce.to_le_bytes().as_ref()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + BountyClaim::INIT_SPACE,
+        seeds = [b"bounty_claim", bounty.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, BountyClaim>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_BOUNTIES) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AwardBounty<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"bounty", wallet.key().as_ref(), bounty.nonce.to_le_bytes().as_ref()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        has_one = bounty,
+        constraint = claim.wallet == claimant.key() @ ClawWalletError::InvalidBounty,
+        seeds = [b"bounty_claim", bounty.key().as_ref(), claimant.key().as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Account<'info, BountyClaim>,
+    /// The winning agent wallet
+    #[account(mut)]
+    pub claimant: Account<'info, AgentWallet>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_BOUNTIES) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Fee waiver for the wallet or the winner, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBounty<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"bounty", wallet.key().as_ref(), bounty.nonce.to_le_bytes().as_ref()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBountyClaim<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.wallet.as_ref(), bounty.nonce.to_le_bytes().as_ref()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = bounty,
+        has_one = wallet,
+        seeds = [b"bounty_claim", bounty.key().as_ref(), wallet.key().as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Account<'info, BountyClaim>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBounty<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"bounty", wallet.key().as_ref(), bounty.nonce.to_le_bytes().as_ref()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+}
+
+#[derive(Accounts)]
+pub struct CreateListing<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the listing's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Listing::INIT_SPACE,
+        seeds = [b"listing", wallet.key().as_ref(), wallet.listing_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MARKETPLACE) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = wallet,
+        seeds = [b"listing", wallet.key().as_ref(), listing.nonce.to_le_bytes().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+}
+
+#[derive(Accounts)]
+pub struct CloseListing<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = wallet,
+        seeds = [b"listing", wallet.key().as_ref(), listing.nonce.to_le_bytes().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseService<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = listing.wallet == seller.key() @ ClawWalletError::InvalidListing,
+        seeds = [b"listing", seller.key().as_ref(), listing.nonce.to_le_bytes().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// CHECK: The selling agent wallet, checked against the listing
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MARKETPLACE | PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the buyer or the seller, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseServiceToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"listing", listing.wallet.as_ref(), listing.nonce.to_le_bytes().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// The listing's price mint (SPL Token or Token-2022)
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == listing.wallet @ ClawWalletError::InvalidListing,
+        constraint = seller_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub seller_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address_with_program_id(&config.fee_recipient(), &mint.key(), &token_program.key())
+            @ ClawWalletError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_MARKETPLACE | PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Fee waiver for the buyer or the seller, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositCredits<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the credit account's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The provider agent wallet
+    pub provider: Account<'info, AgentWallet>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + CreditAccount::INIT_SPACE,
+        seeds = [b"credits", wallet.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREDITS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeCredits<'info> {
+    /// The consumer agent wallet; sets the fee tier
+    pub consumer: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        constraint = provider.is_operator(&authority.key()) @ ClawWalletError::Unauthorized
+    )]
+    pub provider: Account<'info, AgentWallet>,
+    /// Provider wallet owner or agent key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = consumer,
+        has_one = provider,
+        seeds = [b"credits", consumer.key().as_ref(), provider.key().as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREDITS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Fee waiver for the consumer or the provider, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCredits<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        constraint = credits.consumer == wallet.key() @ ClawWalletError::InvalidCredits,
+        seeds = [b"credits", wallet.key().as_ref(), credits.provider.as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCredits<'info> {
+    #[account(mut, has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer,
+        constraint = credits.consumer == wallet.key() @ ClawWalletError::InvalidCredits,
+        seeds = [b"credits", wallet.key().as_ref(), credits.provider.as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+    /// CHECK: Receives the credit account's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetUsageTerms<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        constraint = credits.consumer == wallet.key() @ ClawWalletError::InvalidCredits,
+        seeds = [b"credits", wallet.key().as_ref(), credits.provider.as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RecordUsage<'info> {
+    #[account(constraint = provider.is_operator(&authority.key()) @ ClawWalletError::Unauthorized)]
+    pub provider: Account<'info, AgentWallet>,
+    /// Provider wallet owner or agent key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = provider,
+        seeds = [b"credits", credits.consumer.as_ref(), provider.key().as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREDITS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleUsage<'info> {
+    /// The consumer agent wallet (its points set the fee tier)
+    pub consumer: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        constraint = provider.is_operator(&authority.key()) @ ClawWalletError::Unauthorized
+    )]
+    pub provider: Account<'info, AgentWallet>,
+    /// Provider wallet owner or agent key
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = consumer,
+        has_one = provider,
+        seeds = [b"credits", consumer.key().as_ref(), provider.key().as_ref()],
+        bump = credits.bump
+    )]
+    pub credits: Account<'info, CreditAccount>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CREDITS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Fee waiver for the consumer or the provider, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct OpenChannel<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Pays the channel's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The other agent wallet
+    pub counterparty: Account<'info, AgentWallet>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Channel::INIT_SPACE,
+        seeds = [b"channel", wallet.key().as_ref(), counterparty.key().as_ref()],
+        bump
+    )]
+    pub channel: Account<'info, Channel>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CHANNELS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct JoinChannel<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        constraint = channel.wallet_b == wallet.key() @ ClawWalletError::InvalidChannel,
+        seeds = [b"channel", channel.wallet_a.as_ref(), wallet.key().as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, Channel>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_CHANNELS) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChannel<'info> {
+    #[account(mut, constraint = wallet_a.is_operator(&authority_a.key()) @ ClawWalletError::Unauthorized)]
+    pub wallet_a: Account<'info, AgentWallet>,
+    #[account(mut, constraint = wallet_b.is_operator(&authority_b.key()) @ ClawWalletError::Unauthorized)]
+    pub wallet_b: Account<'info, AgentWallet>,
+    pub authority_a: Signer<'info>,
+    pub authority_b: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet_a,
+        has_one = wallet_b,
+        has_one = rent_payer,
+        seeds = [b"channel", wallet_a.key().as_ref(), wallet_b.key().as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, Channel>,
+    /// CHECK: Receives the channel's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleChannel<'info> {
+    /// The submitting side's agent wallet
+    #[account(
+        constraint = wallet.is_operator(&authority.key()) @ ClawWalletError::Unauthorized,
+        constraint = wallet.key() == channel.wallet_a || wallet.key() == channel.wallet_b
+            @ ClawWalletError::InvalidChannel
+    )]
+    pub wallet: Account<'info, AgentWallet>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"channel", channel.wallet_a.as_ref(), channel.wallet_b.as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, Channel>,
+    /// CHECK: The instructions sysvar, read for the Ed25519 signature check
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeChannel<'info> {
+    #[account(mut)]
+    pub wallet_a: Account<'info, AgentWallet>,
+    #[account(mut)]
+    pub wallet_b: Account<'info, AgentWallet>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = wallet_a,
+        has_one = wallet_b,
+        has_one = rent_payer,
+        seeds = [b"channel", wallet_a.key().as_ref(), wallet_b.key().as_ref()],
+        bump = channel.bump
+    )]
+    pub channel: Account<'info, Channel>,
+    /// CHECK: Receives the channel's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    /// Whoever runs the cleanup; receives the cleanup bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PayForResource<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// CHECK: The resource's seller; any SOL recipient
+    #[account(mut)]
+    pub payee: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_SOL) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Payment receipt to create for this transfer, if wanted
+    #[account(
+        init,
+        payer = receipt_payer,
+        space = 8 + PaymentReceipt::INIT_SPACE,
+        seeds = [b"receipt", wallet.key().as_ref(), wallet.receipt_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Required with `receipt`; pays its rent
+    #[account(mut)]
+    pub receipt_payer: Option<Signer<'info>>,
+    /// Required with `receipt`
+    pub system_program: Option<Program<'info, System>>,
+    /// Fee waiver for the wallet or the payee, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct PayForResourceToken<'info> {
+    #[account(mut, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    /// Wallet owner, agent key, or a registered session key
+    pub authority: Signer<'info>,
+    /// Payment mint (SPL Token or Token-2022)
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// The resource's seller's token account
+    #[account(mut, constraint = payee_token_account.mint == mint.key() @ ClawWalletError::MintMismatch)]
+    pub payee_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token_account.key()
+            == get_associated_token_address_with_program_id(&config.fee_recipient(), &mint.key(), &token_program.key())
+            @ ClawWalletError::InvalidTreasury,
+        constraint = treasury_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a spend policy
+    #[account(mut, seeds = [b"policy", wallet.key().as_ref()], bump = spend_policy.bump)]
+    pub spend_policy: Option<Account<'info, SpendPolicy>>,
+    /// Required when `authority` is a session key
+    #[account(
+        mut,
+        seeds = [b"session", wallet.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionKey>>,
+    /// Required when the wallet's allowlist is enabled
+    #[account(seeds = [b"allowlist", wallet.key().as_ref()], bump = allowlist.bump)]
+    pub allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+    /// Payment receipt to create for this transfer, if wanted
+    #[account(
+        init,
+        payer = receipt_payer,
+        space = 8 + PaymentReceipt::INIT_SPACE,
+        seeds = [b"receipt", wallet.key().as_ref(), wallet.receipt_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, PaymentReceipt>>,
+    /// Required with `receipt`; pays its rent
+    #[account(mut)]
+    pub receipt_payer: Option<Signer<'info>>,
+    /// Required with `receipt`
+    pub system_program: Option<Program<'info, System>>,
+    /// Fee waiver for the wallet or the payee, if one exists
+    #[account(seeds = [b"fee_exempt", fee_exemption.subject.as_ref()], bump = fee_exemption.bump)]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    /// Active fee campaign to apply, if any
+    #[account(seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()], bump = campaign.bump)]
+    pub campaign: Option<Account<'info, FeeCampaign>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyReceipt<'info> {
+    #[account(
+        seeds = [b"receipt", receipt.payer.as_ref(), receipt.nonce.to_le_bytes().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, PaymentReceipt>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReceipt<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer,
+        constraint = receipt.payer == wallet.key() @ ClawWalletError::Unauthorized,
+        seeds = [b"receipt", wallet.key().as_ref(), receipt.nonce.to_le_bytes().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, PaymentReceipt>,
+    /// CHECK: Receives the receipt's rent
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureConfidentialAccount<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// CHECK: The instructions sysvar when the proof is verified in this transaction, otherwise the
+    /// proof context-state account; checked by Token-2022
+    pub proof_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct DepositConfidential<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyConfidentialPending<'info> {
+    #[account(has_one = owner)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    #[account(mut, constraint = wallet_token_account.owner == wallet.key())]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SendConfidential<'info> {
+    #[account(mut, has_one = owner, constraint = !wallet.frozen @ ClawWalletError::WalletFrozen)]
+    pub wallet: Account<'info, AgentWallet>,
+    pub owner: Signer<'info>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key(),
+        constraint = wallet_token_account.mint == mint.key() @ ClawWalletError::MintMismatch
+    )]
+    pub wallet_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    #[account(mut, constraint = recipient_token_account.mint == mint.key() @ ClawWalletError::MintMismatch)]
+    pub recipient_token_account: InterfaceAccount<'info, InterfaceTokenAccount>,
+    /// CHECK: The instructions sysvar when the proof is verified in this transaction, otherwise the
+    /// proof context-state account; checked by Token-2022
+    pub proof_account: AccountInfo<'info>,
+    /// CHECK: Must be the config's treasury (or the fee vault while accruing)
+    #[account(mut, address = config.fee_recipient() @ ClawWalletError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    pub token_program: Program<'info, Token2022>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.is_active(PAUSE_SEND_TOKEN) @ ClawWalletError::ProtocolPaused
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// Required when the wallet has a denylist
+    #[account(seeds = [b"denylist", wallet.key().as_ref()], bump = denylist.bump)]
+    pub denylist: Option<Account<'info, Denylist>>,
+    /// Required when the protocol denylist exists
+    #[account(seeds = [b"denylist"], bump = protocol_denylist.bump)]
+    pub protocol_denylist: Option<Account<'info, Denylist>>,
+}
+
+#[derive(Accounts)]
+pub struct SendTokenWithAta<'info> {
+    /// Pays the recipient ATA's rent when it has to be created
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Any token recipient; owns `recipient_token_account`
+    pub recipient: AccountInfo<'info>,
+    /// CHECK: The recipient's associated token account, created if missing; must also be send_token's
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&recipient.key(), &mint.key(), &token_program.key())
+            @ ClawWalletError::AtaMismatch
+    )]
+    pub recipient_token_account: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, InterfaceMint>,
+    /// SPL Token or Token-2022, whichever owns the mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AgentWallet {
+    #[max_len(64)]
+    pub agent_id: String,
+    pub owner: Pubkey,
+    pub points: u64,
+    pub created_at: i64,
+    pub tx_count: u64,
+    pub bump: u8,
+    pub has_spend_policy: bool,
+    pub volume_window_start: i64,
+    pub volume_24h: u64,         // outgoing lamports since volume_window_start
+    pub agent_authority: Pubkey, // default = no agent key
+    pub pending_owner: Pubkey,   // default = no transfer in progress
+    pub beneficiary: Pubkey,     // default = no dead-man switch
+    pub inactivity_period: i64,
+    pub last_activity_at: i64,
+    pub frozen: bool,
+    pub allowlist_enabled: bool,
+    pub denylist_enabled: bool,
+    pub queue_nonce: u64,
+    pub proposal_nonce: u64,
+    pub referrer: Pubkey,          // default = not referred
+    pub referral_earnings: u64,    // lamports earned from referred wallets' SOL fees
+    pub referral_code: Pubkey,     // default = not signed up through a code
+    pub fee_discount_credits: u64, // sends left at the points-discounted fee
+    pub last_tx_at: i64,
+    pub prestige: u16,
+    pub lifetime_volume: u64, // outgoing lamports, all time
+    pub badges: u32,          // bit per BADGE_* index
+    pub level: u8,            // highest level reached, see LEVEL_REQUIREMENTS
+    pub last_tx_day: i64,     // UTC day index (unix_timestamp / SECONDS_PER_DAY)
+    pub streak_days: u32,
+    #[max_len(4)]
+    pub recent_recipients: Vec<Pubkey>,
+    pub earn_day: i64,
+    pub points_earned_today: u64,
+    pub season: u32,
+    #[max_len(4)]
+    pub season_history: Vec<SeasonRecord>,
+    pub category_tx_counts: [u64; 8],
+    pub category_sol_volume: [u64; 8], // lamports; token sends only bump the count
+    #[max_len(16)]
+    pub idempotency_keys: Vec<[u8; 16]>,
+    pub schedule_nonce: u64,
+    pub stream_nonce: u64,
+    pub vesting_nonce: u64,
+    pub escrow_nonce: u64,
+    pub htlc_nonce: u64,
+    pub claimable_nonce: u64,
+    pub invoice_nonce: u64,
+    pub has_revenue_share: bool,
+    pub crowdfund_nonce: u64,
+    pub bounty_nonce: u64,
+    pub listing_nonce: u64,
+    pub resource_nonce: u64,
+    pub receipt_nonce: u64,
+    pub channel_nonce: u64,
+    pub session_count: u16, // open session keys; must be 0 to close the wallet
+    pub points_decayed_at: i64, // end of the last inactivity period already decayed
+    pub open_objects: [u16; OPEN_KINDS], // per OPEN_* kind; all must be 0 to close the wallet
+}
+
+impl AgentWallet {
+    /// The owner or the agent key
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        *key == self.owner
+            || (self.agent_authority != Pubkey::default() && *key == self.agent_authority)
+    }
+
+    /// Add an outgoing SOL amount to the rolling 24h volume, returning the new total
+    pub fn record_outflow(&mut self, amount: u64, now: i64) -> Result<u64> {
+        self.lifetime_volume = self.lifetime_volume.saturating_add(amount);
+        self.check_badges();
+        roll_window(
+            &mut self.volume_window_start,
+            &mut self.volume_24h,
+            amount,
+            now,
+        )
+    }
+
+    /// Count a newly created object of `kind` (OPEN_*) against the wallet
+    pub fn open_object(&mut self, kind: usize) -> Result<()> {
+        self.open_objects[kind] = self.open_objects[kind]
+            .checked_add(1)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Release an object of `kind` once it is closed; saturating, as objects created before the counts
+    /// existed were never counted
+    pub fn close_object(&mut self, kind: usize) {
+        self.open_objects[kind] = self.open_objects[kind].saturating_sub(1);
+    }
+
+    /// Set the `badge` bit, emitting AchievementUnlocked the first time
+    pub fn unlock_badge(&mut self, badge: u8) {
+        if self.badges & (1 << badge) != 0 {
+            return;
+        }
+        self.badges |= 1 << badge;
+
+        emit!(AchievementUnlocked {
+            agent_id: self.agent_id.clone(),
+            badge,
+        });
+    }
+
+    /// Unlock any activity badges whose thresholds have been crossed
+    fn check_badges(&mut self) {
+        if self.tx_count >= 1 {
+            self.unlock_badge(BADGE_FIRST_TRANSFER);
+        }
+        if self.tx_count >= 100 {
+            self.unlock_badge(BADGE_100_TRANSFERS);
+        }
+        if self.lifetime_volume >= 1_000_000_000 {
+            self.unlock_badge(BADGE_1_SOL_VOLUME);
+        }
+    }
+
+    /// Credit points, saturating at the configured cap
+    pub fn add_points(&mut self, amount: u64, config: &ProtocolConfig) {
+        self.sync_season(config);
+        let points = self.points.saturating_add(amount);
+        self.points = if config.points_cap == 0 {
+            points
+        } else {
+            points.min(config.points_cap)
+        };
+        self.refresh_level();
+    }
+
+    /// On the first touch after a season roll, archive the old season's points and start from zero
+    pub fn sync_season(&mut self, config: &ProtocolConfig) {
+        if self.season == config.season {
+            return;
+        }
+        if self.season_history.len() == MAX_SEASON_HISTORY {
+            self.season_history.remove(0);
+        }
+        self.season_history.push(SeasonRecord {
+            season: self.season,
+            points: self.points,
+        });
+
+        emit!(SeasonSnapshotted {
+            agent_id: self.agent_id.clone(),
+            season: self.season,
+            points: self.points,
+        });
+
+        self.season = config.season;
+        self.points = 0;
+    }
+
+    /// Points counting toward the current season, without requiring a sync
+    pub fn season_points(&self, config: &ProtocolConfig) -> u64 {
+        if self.season == config.season {
+            self.points
+        } else {
+            0
+        }
+    }
+
+    /// Reject a client-supplied idempotency key seen in the recent window, then remember it
+    pub fn use_idempotency_key(&mut self, key: Option<[u8; 16]>) -> Result<()> {
+        let key = match key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        require!(
+            !self.idempotency_keys.contains(&key),
+            ClawWalletError::DuplicateSend
+        );
+        if self.idempotency_keys.len() == MAX_IDEMPOTENCY_KEYS {
+            self.idempotency_keys.remove(0);
+        }
+        self.idempotency_keys.push(key);
+        Ok(())
+    }
+
+    /// Count a transfer (and its SOL volume) toward its category's totals
+    pub fn record_category(&mut self, category: u8, lamports: u64) -> Result<()> {
+        require!(
+            (category as usize) < NUM_CATEGORIES,
+            ClawWalletError::InvalidCategory
+        );
+        let i = category as usize;
+        self.category_tx_counts[i] = self.category_tx_counts[i].saturating_add(1);
+        self.category_sol_volume[i] = self.category_sol_volume[i].saturating_add(lamports);
+        Ok(())
+    }
+
+    /// Award send points under the anti-farming rules (minimum amount, repeat-recipient
+    /// reduction, daily cap) plus the level bonus, and return what was actually credited
+    pub fn earn_points(
+        &mut self,
+        points: u64,
+        amount: u64,
+        min_amount: u64,
+        recipient: Pubkey,
+        now: i64,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        if amount < min_amount {
+            return 0;
+        }
+
+        let mut points = points;
+        if self.recent_recipients.contains(&recipient) {
+            points = (points as u128 * config.repeat_points_bps as u128 / 10_000) as u64;
+        } else {
+            if self.recent_recipients.len() == RECENT_RECIPIENTS {
+                self.recent_recipients.remove(0);
+            }
+            self.recent_recipients.push(recipient);
+        }
+        let bonus_bps = 10_000 + self.level as u128 * LEVEL_POINTS_BONUS_BPS as u128;
+        points = (points as u128 * bonus_bps / 10_000) as u64;
+
+        let day = now.div_euclid(SECONDS_PER_DAY);
+        if day != self.earn_day {
+            self.earn_day = day;
+            self.points_earned_today = 0;
+        }
+        if config.daily_points_cap > 0 {
+            points = points.min(
+                config
+                    .daily_points_cap
+                    .saturating_sub(self.points_earned_today),
+            );
+        }
+        self.points_earned_today = self.points_earned_today.saturating_add(points);
+
+        self.add_points(points, config);
+        points
+    }
+
+    /// Level implied by current activity (points, tx count and lifetime volume)
+    pub fn activity_level(&self) -> u8 {
+        LEVEL_REQUIREMENTS
+            .iter()
+            .take_while(|(points, txs, volume)| {
+                self.points >= *points && self.tx_count >= *txs && self.lifetime_volume >= *volume
+            })
+            .count() as u8
+    }
+
+    /// Raise the stored level to the activity level, emitting LevelUp; levels never drop
+    fn refresh_level(&mut self) {
+        let level = self.activity_level();
+        if level <= self.level {
+            return;
+        }
+        self.level = level;
+
+        emit!(LevelUp {
+            agent_id: self.agent_id.clone(),
+            level,
+        });
+    }
+
+    /// Bring the points balance up to date: archive a finished season and apply any decay owed. Call
+    /// before reading or spending points
+    pub fn settle_points(&mut self, config: &ProtocolConfig, now: i64) {
+        self.sync_season(config);
+        self.decay_points(config.points_decay_bps, now);
+    }
+
+    /// Count a completed outgoing transfer, first applying any points decay owed since the last one
+    pub fn record_tx(&mut self, now: i64, config: &ProtocolConfig) {
+        self.settle_points(config, now);
+        self.tx_count += 1;
+        self.last_activity_at = now;
+        self.last_tx_at = now;
+        self.update_streak(now, config);
+        self.check_badges();
+        self.refresh_level();
+    }
+
+    /// Extend (or restart) the daily streak on the first transfer of a UTC day and pay its bonus
+    fn update_streak(&mut self, now: i64, config: &ProtocolConfig) {
+        let day = now.div_euclid(SECONDS_PER_DAY);
+        if day == self.last_tx_day {
+            return;
+        }
+        self.streak_days = if day == self.last_tx_day + 1 {
+            self.streak_days.saturating_add(1)
+        } else {
+            1
+        };
+        self.last_tx_day = day;
+
+        let bonus = (self.streak_days as u64 * STREAK_BONUS_PER_DAY).min(MAX_STREAK_BONUS);
+        self.add_points(bonus, config);
+
+        emit!(StreakBonus {
+            agent_id: self.agent_id.clone(),
+            streak_days: self.streak_days,
+            bonus,
+        });
+    }
+
+    /// Lazily decay points by `decay_bps` per full POINTS_DECAY_PERIOD since the last transfer (or the
+    /// last period already decayed)
+    fn decay_points(&mut self, decay_bps: u16, now: i64) {
+        let since = self
+            .last_tx_at
+            .max(self.created_at)
+            .max(self.points_decayed_at);
+        let periods = (now - since) / POINTS_DECAY_PERIOD;
+        if periods <= 0 {
+            return;
+        }
+        self.points_decayed_at = since + periods * POINTS_DECAY_PERIOD;
+        if decay_bps == 0 || self.points == 0 {
+            return;
+        }
+
+        let before = self.points;
+        let mut points = before as u128;
+        for _ in 0..periods {
+            points = points * (10_000 - decay_bps as u128) / 10_000;
+            if points == 0 {
+                break;
+            }
+        }
+        self.points = points as u64;
+
+        emit!(PointsDecayed {
+            agent_id: self.agent_id.clone(),
+            periods: periods as u64,
+            points_lost: before - self.points,
+        });
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SpendPolicy {
+    pub wallet: Pubkey,
+    pub daily_limit: u64, // lamports per rolling 24h, 0 = unlimited
+    #[max_len(4)]
+    pub token_limits: Vec<TokenLimit>,
+    pub bump: u8,
+    pub max_per_tx: u64,         // lamports, 0 = unlimited
+    pub timelock_threshold: u64, // lamports, 0 = no timelock
+    pub timelock_delay: i64,     // seconds, 0 = DEFAULT_TIMELOCK_DELAY
+}
+
+impl SpendPolicy {
+    /// Timelock threshold for SOL (`None`) or an SPL mint; 0 = no timelock
+    fn timelock_threshold_for(&self, mint: Option<Pubkey>) -> u64 {
+        match mint {
+            None => self.timelock_threshold,
+            Some(mint) => self
+                .token_limits
+                .iter()
+                .find(|l| l.mint == mint)
+                .map_or(0, |l| l.timelock_threshold),
+        }
+    }
+
+    /// Apply `update` to the entry for `mint`, creating it if missing and dropping it once all caps are 0
+    fn update_token_limit(
+        &mut self,
+        mint: Pubkey,
+        update: impl FnOnce(&mut TokenLimit),
+    ) -> Result<()> {
+        let i = match self.token_limits.iter().position(|l| l.mint == mint) {
+            Some(i) => i,
+            None => {
+                require!(
+                    self.token_limits.len() < MAX_TOKEN_LIMITS,
+                    ClawWalletError::TooManyTokenLimits
+                );
+                self.token_limits.push(TokenLimit {
+                    mint,
+                    daily_limit: 0,
+                    window_start: 0,
+                    volume_24h: 0,
+                    max_per_tx: 0,
+                    timelock_threshold: 0,
+                });
+                self.token_limits.len() - 1
+            }
+        };
+
+        update(&mut self.token_limits[i]);
+        let l = &self.token_limits[i];
+        if l.daily_limit == 0 && l.max_per_tx == 0 && l.timelock_threshold == 0 {
+            self.token_limits.remove(i);
+        }
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    pub wallet: Pubkey,
+    pub signer: Pubkey,
+    pub expires_at: i64,
+    pub permissions: u8, // PERM_* bits
+    pub spend_cap: u64,  // lamports over the session lifetime, 0 = unlimited
+    pub spent: u64,
+    pub token_spend_cap: u64, // raw token units over the session lifetime, 0 = unlimited
+    pub token_spent: u64,
+    pub bump: u8,
+}
+
+impl SessionKey {
+    /// Check the session is live and allows `permission`, charging `amount` against its cap
+    fn authorize(&mut self, permission: u8, amount: u64, now: i64) -> Result<()> {
+        require!(now < self.expires_at, ClawWalletError::SessionExpired);
+        require!(
+            self.permissions & permission != 0,
+            ClawWalletError::SessionPermissionDenied
+        );
+
+        let (cap, spent) = if permission == PERM_SEND_TOKEN {
+            (self.token_spend_cap, &mut self.token_spent)
+        } else {
+            (self.spend_cap, &mut self.spent)
+        };
+        let total = spent
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            cap == 0 || total <= cap,
+            ClawWalletError::SessionCapExceeded
+        );
+        *spent = total;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TokenLimit {
+    pub mint: Pubkey,
+    pub daily_limit: u64, // raw token units per rolling 24h
+    pub window_start: i64,
+    pub volume_24h: u64,
+    pub max_per_tx: u64,
+    pub timelock_threshold: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    pub wallet: Pubkey,
+    #[max_len(10)]
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub tx_nonce: u64,
+    pub bump: u8,
+}
+
+impl Multisig {
+    fn owner_index(&self, key: &Pubkey) -> Result<usize> {
+        self.owners
+            .iter()
+            .position(|o| o == key)
+            .ok_or_else(|| error!(ClawWalletError::NotMultisigOwner))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingTransaction {
+    pub wallet: Pubkey,
+    pub proposer: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,    // system account for SOL, token account for SPL
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub approvals: u16, // bit i = owners[i] approved
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryConfig {
+    pub wallet: Pubkey,
+    #[max_len(5)]
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub new_owner: Pubkey,
+    pub approvals: u8,     // bit i = guardians[i] approved
+    pub initiated_at: i64, // 0 = no recovery in progress
+    pub bump: u8,
+}
+
+impl RecoveryConfig {
+    fn guardian_index(&self, key: &Pubkey) -> Result<usize> {
+        self.guardians
+            .iter()
+            .position(|g| g == key)
+            .ok_or_else(|| error!(ClawWalletError::NotGuardian))
+    }
+
+    fn clear(&mut self) {
+        self.new_owner = Pubkey::default();
+        self.approvals = 0;
+        self.initiated_at = 0;
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+    pub paused_instructions: u64, // PAUSE_* bits
+    pub denylist_enabled: bool,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub pending_treasury: Pubkey, // default = no change pending
+    pub treasury_change_at: i64,
+    #[max_len(4)]
+    pub fee_tiers: Vec<FeeTier>,
+    pub min_fee: u64,       // lamports, SOL transfers only
+    pub fee_rounding: u8,   // FEE_ROUND_*
+    pub max_fee: u64,       // lamports, 0 = uncapped
+    pub max_token_fee: u64, // raw token units, 0 = uncapped
+    pub campaign_nonce: u64,
+    pub referral_share_bps: u16,
+    pub fee_vault: Pubkey, // default = no vault
+    pub accrue_fees: bool,
+    #[max_len(4)]
+    pub fee_beneficiaries: Vec<FeeBeneficiary>,
+    pub discount_cost: u64, // points per discount credit, 0 = disabled
+    pub discount_bps: u16,  // discount off the fee per credit
+    pub points_transfer_tax_bps: u16,
+    pub epoch: u64,
+    pub epoch_started_at: i64,
+    pub epoch_duration: i64, // 0 = epochs disabled
+    pub points_decay_bps: u16,
+    pub points_cap: u64,     // 0 = uncapped
+    pub reward_mint: Pubkey, // default = no reward token
+    pub reward_rate: u64,    // raw reward-token units per point
+    pub distributor_nonce: u64,
+    pub points_campaign_nonce: u64,
+    pub min_earn_lamports: u64,
+    pub min_earn_token: u64,
+    pub daily_points_cap: u64, // 0 = uncapped
+    pub repeat_points_bps: u16,
+    pub quest_nonce: u64,
+    pub season: u32,
+    pub season_started_at: i64,
+    pub sol_points: PointsFormula,
+    pub token_points: PointsFormula, // default for mints without an override
+    #[max_len(4)]
+    pub mint_points: Vec<MintPointsFormula>,
+    pub sol_price_feed: Pubkey, // default = lamport-based points
+    #[max_len(4)]
+    pub mint_price_feeds: Vec<MintPriceFeed>,
+    pub usd_points: PointsFormula, // applied to micro-USD values
+    pub max_price_age: u64,
+    pub keeper_tip: u64,         // lamports from the wallet per crank execution
+    pub cleanup_bounty_bps: u16, // share of reclaimed rent paid to cleanup keepers
+    pub min_arbiter_bond: u64,
+    pub claimable_ttl: i64,
+    pub tip_fee: u64,          // flat lamports per tip
+    pub confidential_fee: u64, // flat lamports per confidential send
+}
+
+impl ProtocolConfig {
+    /// False if the protocol is globally paused or any of `flags` is paused
+    pub fn is_active(&self, flags: u64) -> bool {
+        !self.paused && self.paused_instructions & flags == 0
+    }
+
+    /// `fee` after one points-discount credit
+    pub fn discounted_fee(&self, fee: u64) -> u64 {
+        fee - (fee as u128 * self.discount_bps as u128 / 10_000) as u64
+    }
+
+    /// Where fees are paid: the fee vault while accruing, else the treasury
+    pub fn fee_recipient(&self) -> Pubkey {
+        if self.accrue_fees {
+            self.fee_vault
+        } else {
+            self.treasury
+        }
+    }
+
+    /// Fee rate for a sender with `points`: the lowest tier reached, else the base rate
+    pub fn fee_bps_for(&self, points: u64) -> u16 {
+        self.fee_tiers
+            .iter()
+            .filter(|t| points >= t.min_points)
+            .map(|t| t.fee_bps)
+            .fold(self.fee_bps, u16::min)
+    }
+
+    /// Fee on `amount` at `bps`, per the rounding policy
+    pub fn fee_at(&self, amount: u64, bps: u16) -> u64 {
+        let scaled = amount as u128 * bps as u128;
+        let fee = if self.fee_rounding == FEE_ROUND_UP {
+            (scaled + 9_999) / 10_000
+        } else {
+            scaled / 10_000
+        };
+        (fee as u64).min(amount)
+    }
+
+    /// SOL fee at `bps`: raised to the minimum fee, then capped, and never more than `amount`;
+    /// a zero rate waives it entirely
+    pub fn sol_fee_at(&self, amount: u64, bps: u16) -> u64 {
+        if bps == 0 {
+            return 0;
+        }
+        let fee = self.fee_at(amount, bps).max(self.min_fee);
+        apply_fee_cap(fee, self.max_fee).min(amount)
+    }
+
+    /// Token fee at `bps`, capped at max_token_fee
+    pub fn token_fee_at(&self, amount: u64, bps: u16) -> u64 {
+        apply_fee_cap(self.fee_at(amount, bps), self.max_token_fee)
+    }
+
+    /// SOL fee for a sender with `points`
+    pub fn sol_fee_for(&self, amount: u64, points: u64) -> u64 {
+        self.sol_fee_at(amount, self.fee_bps_for(points))
+    }
+
+    /// Token fee for a sender with `points`
+    pub fn token_fee_for(&self, amount: u64, points: u64) -> u64 {
+        self.token_fee_at(amount, self.fee_bps_for(points))
+    }
+
+    /// Base points for a SOL send
+    pub fn sol_points_for(&self, amount: u64) -> u64 {
+        self.sol_points.points_for(amount)
+    }
+
+    /// Base points for a token send, using the mint's override if one is configured
+    pub fn token_points_for(&self, mint: &Pubkey, amount: u64) -> u64 {
+        match self.mint_points.iter().find(|e| &e.mint == mint) {
+            Some(entry) => entry.formula.points_for(amount),
+            None => self.token_points.points_for(amount),
+        }
+    }
+
+    /// Pyth feed and decimals for an asset (None = SOL), if USD-value points are enabled for it
+    pub fn price_feed_for(&self, mint: Option<Pubkey>) -> Option<(Pubkey, u8)> {
+        match mint {
+            None if self.sol_price_feed != Pubkey::default() => Some((self.sol_price_feed, 9)),
+            None => None,
+            Some(mint) => self
+                .mint_price_feeds
+                .iter()
+                .find(|e| e.mint == mint)
+                .map(|e| (e.feed, e.decimals)),
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientAllowlist {
+    pub wallet: Pubkey,
+    #[max_len(16)]
+    pub recipients: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// Blocked recipients, either per wallet (["denylist", wallet]) or protocol-wide (["denylist"])
+#[account]
+#[derive(InitSpace)]
+pub struct Denylist {
+    #[max_len(32)]
+    pub entries: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Denylist {
+    fn insert(&mut self, recipient: Pubkey) -> Result<()> {
+        if !self.entries.contains(&recipient) {
+            require!(
+                self.entries.len() < MAX_DENYLIST,
+                ClawWalletError::DenylistFull
+            );
+            self.entries.push(recipient);
+        }
+        Ok(())
+    }
+
+    fn check(&self, destinations: &[Pubkey]) -> Result<()> {
+        require!(
+            !destinations.iter().any(|d| self.entries.contains(d)),
+            ClawWalletError::RecipientDenied
+        );
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct QueuedTransfer {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub queued_by: Pubkey,
+    pub delegated: bool, // queued by a non-owner signer; policy applies at execution
+    pub recipient: Pubkey, // system account for SOL, token account for SPL
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+    pub expires_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ApproverSet {
+    pub wallet: Pubkey,
+    #[max_len(5)]
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+    pub version: u64, // bumped on every set_approvers
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentProposal {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,    // system account for SOL, token account for SPL
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub approvals: u8, // bit i = approvers[i] approved
+    pub approved: bool,
+    pub created_at: i64,
+    pub bump: u8,
+    pub expires_at: i64,
+    pub approvers_version: u64, // approver set version the approval bits refer to
+}
+
+impl PaymentProposal {
+    /// A quorum approval only stands while the approver set it was gathered under is unchanged
+    pub fn check_quorum_current(&self, approver_set: &Option<Account<ApproverSet>>) -> Result<()> {
+        if self.approvals == 0 {
+            return Ok(()); // approved by the owner
+        }
+        match approver_set {
+            Some(set) if set.version == self.approvers_version => Ok(()),
+            _ => err!(ClawWalletError::ApproversChanged),
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AgentMetadata {
+    pub wallet: Pubkey,
+    #[max_len(32)]
+    pub display_name: String,
+    #[max_len(200)]
+    pub uri: String,
+    #[max_len(256)]
+    pub description: String,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeTier {
+    pub min_points: u64,
+    pub fee_bps: u16,
+}
+
+/// Admin-granted fee waiver for a wallet or recipient (["fee_exempt", subject])
+#[account]
+#[derive(InitSpace)]
+pub struct FeeExemption {
+    pub subject: Pubkey,
+    pub reason: u8, // EXEMPT_* code
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Result of quote_fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeQuote {
+    pub fee_bps: u16,
+    pub fee: u64,
+    pub send_amount: u64,
+}
+
+/// Time-boxed fee reduction (["campaign", id])
+#[account]
+#[derive(InitSpace)]
+pub struct FeeCampaign {
+    pub id: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub mint: Option<Pubkey>, // None = all transfers, Some = only this token
+    pub fee_bps: u16,
+    pub bump: u8,
+}
+
+impl FeeCampaign {
+    /// True while the campaign window is open and it covers `mint` (None = SOL)
+    pub fn applies(&self, mint: Option<Pubkey>, now: i64) -> bool {
+        now >= self.start_at && now < self.end_at && (self.mint.is_none() || self.mint == mint)
+    }
+}
+
+/// Per-mint fee rate for token sends (["mint_fee", mint])
+#[account]
+#[derive(InitSpace)]
+pub struct MintFee {
+    pub mint: Pubkey,
+    pub fee_bps: u16,
+    pub max_fee: u64, // raw token units, 0 = config cap only
+    pub bump: u8,
+}
+
+/// Human-readable referral code with attribution totals (["referral_code", code])
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralCode {
+    #[max_len(16)]
+    pub code: String,
+    pub wallet: Pubkey, // referrer wallet PDA
+    pub signups: u64,
+    pub referred_tx_count: u64,
+    pub referred_volume: u64, // lamports sent by referred wallets
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Protocol fee vault (["fee_vault"]); holds accrued SOL and owns the token fee accounts
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    pub total_claimed: u64, // lamports
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeBeneficiary {
+    pub recipient: Pubkey,
+    pub share_bps: u16,
+}
+
+/// Top wallets by points (["leaderboard"]), sorted descending
+#[account(zero_copy)]
+pub struct Leaderboard {
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE],
+    pub count: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+#[zero_copy]
+pub struct LeaderboardEntry {
+    pub wallet: Pubkey,
+    pub points: u64,
+}
+
+impl Leaderboard {
+    /// Insert or refresh `wallet`, evicting the lowest entry when full; returns its rank if on the board
+    pub fn submit(&mut self, wallet: Pubkey, points: u64) -> Option<usize> {
+        let count = self.count as usize;
+        if let Some(i) = self.entries[..count]
+            .iter()
+            .position(|e| e.wallet == wallet)
+        {
+            self.entries[i].points = points;
+        } else if count < LEADERBOARD_SIZE {
+            self.entries[count] = LeaderboardEntry { wallet, points };
+            self.count += 1;
+        } else if points > self.entries[count - 1].points {
+            self.entries[count - 1] = LeaderboardEntry { wallet, points };
+        } else {
+            return None;
+        }
+
+        let count = self.count as usize;
+        self.entries[..count].sort_unstable_by(|a, b| b.points.cmp(&a.points));
+        self.entries[..count]
+            .iter()
+            .position(|e| e.wallet == wallet)
+    }
+
+    /// Drop every entry (at an epoch or season boundary)
+    pub fn clear(&mut self) {
+        self.entries = [LeaderboardEntry {
+            wallet: Pubkey::default(),
+            points: 0,
+        }; LEADERBOARD_SIZE];
+        self.count = 0;
+    }
+}
+
+/// Funds epoch rewards (["reward_vault"]); holds SOL and owns the reward token accounts
+#[account]
+#[derive(InitSpace)]
+pub struct RewardVault {
+    pub bump: u8,
+}
+
+/// Snapshot of one epoch's winners and their rewards (["epoch", epoch])
+#[account]
+#[derive(InitSpace)]
+pub struct EpochReward {
+    pub epoch: u64,
+    pub mint: Option<Pubkey>, // None = SOL
+    #[max_len(10)]
+    pub winners: Vec<Pubkey>,
+    #[max_len(10)]
+    pub amounts: Vec<u64>,
+    pub claimed: u16, // bit i = winners[i] has claimed
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+impl EpochReward {
+    /// Mark `wallet`'s reward claimed and return its amount
+    fn claim(&mut self, wallet: &Pubkey) -> Result<u64> {
+        let index = self
+            .winners
+            .iter()
+            .position(|w| w == wallet)
+            .ok_or(ClawWalletError::NotEpochWinner)?;
+        let bit = 1u16 << index;
+        require!(
+            self.claimed & bit == 0,
+            ClawWalletError::RewardAlreadyClaimed
+        );
+        self.claimed |= bit;
+        Ok(self.amounts[index])
+    }
+}
+
+/// Achievement badge (["badge", wallet, badge])
+#[account]
+#[derive(InitSpace)]
+pub struct Badge {
+    pub wallet: Pubkey,
+    pub badge: u8, // BADGE_* index
+    pub minted_at: i64,
+    pub bump: u8,
+}
+
+/// Merkle-root airdrop of points or tokens (["distributor", id]); leaves are
+/// keccak(wallet || amount_le) and proofs hash sorted pairs
+#[account]
+#[derive(InitSpace)]
+pub struct Distributor {
+    pub id: u64,
+    pub root: [u8; 32],
+    pub mint: Option<Pubkey>, // None = points
+    pub total_claimed: u64,
+    pub claims: u64,
+    pub bump: u8,
+}
+
+impl Distributor {
+    /// Check `proof` for (wallet, amount) against the root and update the totals
+    fn verify_and_record(
+        &mut self,
+        wallet: &Pubkey,
+        amount: u64,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let mut node = keccak::hashv(&[wallet.as_ref(), &amount.to_le_bytes()]).0;
+        for sibling in proof {
+            node = if node <= *sibling {
+                keccak::hashv(&[&node, sibling]).0
+            } else {
+                keccak::hashv(&[sibling, &node]).0
+            };
+        }
+        require!(node == self.root, ClawWalletError::InvalidProof);
+
+        self.total_claimed = self
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        self.claims += 1;
+        Ok(())
+    }
+}
+
+/// Marks a wallet's distributor claim as spent (["claim", distributor, wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimStatus {
+    pub amount: u64,
+    pub claimed_at: i64,
+}
+
+/// Time-boxed points multiplier (["points_campaign", id])
+#[account]
+#[derive(InitSpace)]
+pub struct PointsCampaign {
+    pub id: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub mint: Option<Pubkey>, // None = all sends, Some = only this token
+    pub multiplier_bps: u16,
+    pub bump: u8,
+}
+
+impl PointsCampaign {
+    /// True while the campaign window is open and it covers `mint` (None = SOL)
+    pub fn applies(&self, mint: Option<Pubkey>, now: i64) -> bool {
+        now >= self.start_at && now < self.end_at && (self.mint.is_none() || self.mint == mint)
+    }
+}
+
+/// Admin-defined quest (["quest", id])
+#[account]
+#[derive(InitSpace)]
+pub struct Quest {
+    pub id: u64,
+    pub kind: u8,
+    pub mint: Option<Pubkey>, // only for QUEST_TOKEN_VOLUME
+    pub target: u64,
+    pub reward_points: u64,
+    pub end_at: i64,
+    pub bump: u8,
+}
+
+/// Per-wallet quest progress (["quest_progress", quest, wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct QuestProgress {
+    pub quest: Pubkey,
+    pub wallet: Pubkey,
+    pub progress: u64,
+    #[max_len(8)]
+    pub agents: Vec<Pubkey>, // distinct agents sent to, for QUEST_DISTINCT_AGENTS
+    pub completed_at: i64, // 0 = in progress
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+impl QuestProgress {
+    /// Apply one send to the progress; returns true when this send completes the quest
+    pub fn advance(
+        &mut self,
+        quest: &Quest,
+        mint: Option<Pubkey>,
+        amount: u64,
+        agent: Option<Pubkey>,
+        now: i64,
+    ) -> bool {
+        if self.completed_at != 0 {
+            return false;
+        }
+
+        match quest.kind {
+            QUEST_SEND_COUNT => self.progress += 1,
+            QUEST_DISTINCT_AGENTS => {
+                if let Some(agent) = agent {
+                    if !self.agents.contains(&agent) && self.agents.len() < MAX_QUEST_AGENTS {
+                        self.agents.push(agent);
+                    }
+                }
+                self.progress = self.agents.len() as u64;
+            }
+            QUEST_SOL_VOLUME if mint.is_none() => {
+                self.progress = self.progress.saturating_add(amount)
+            }
+            QUEST_TOKEN_VOLUME if mint == quest.mint => {
+                self.progress = self.progress.saturating_add(amount)
+            }
+            _ => {}
+        }
+
+        if self.progress >= quest.target {
+            self.completed_at = now;
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SeasonRecord {
+    pub season: u32,
+    pub points: u64,
+}
+
+/// Points = amount / divisor, clamped to [min_points, max_points]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PointsFormula {
+    pub divisor: u64,
+    pub min_points: u64,
+    pub max_points: u64,
+}
+
+impl PointsFormula {
+    pub fn points_for(&self, amount: u64) -> u64 {
+        (amount / self.divisor).clamp(self.min_points, self.max_points)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.divisor > 0 && self.min_points <= self.max_points,
+            ClawWalletError::InvalidPointsFormula
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MintPointsFormula {
+    pub mint: Pubkey,
+    pub formula: PointsFormula,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MintPriceFeed {
+    pub mint: Pubkey,
+    pub feed: Pubkey,
+    pub decimals: u8,
+}
+
+/// Owner-scheduled payment executable by anyone once due (["scheduled", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct ScheduledPayment {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub payer: Pubkey,
+    pub recipient: Pubkey,    // system account for SOL, token account for SPL
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub created_at: i64,
+    pub execute_after: i64,
+    pub bump: u8,
+}
+
+/// Pre-approved recurring charge by a merchant (["subscription", wallet, merchant])
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub wallet: Pubkey,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub interval: i64,
+    pub next_due: i64,
+    pub max_total: u64, // lifetime cap approved by the owner
+    pub total_charged: u64,
+    pub charges: u32,
+    pub created_at: i64,
+    pub bump: u8,
+    pub status: u8,
+    pub grace_period: i64, // how long failed charges may be retried before the subscription lapses
+    pub failed_since: i64, // 0 = last charge attempt succeeded
+}
+
+impl Subscription {
+    /// Whether a due charge can go through now. An underfunded attempt is recorded instead of
+    /// failing the transaction, and lapses the subscription once the grace period has run out
+    pub fn chargeable(&mut self, funded: bool, now: i64, agent_id: &str) -> Result<bool> {
+        require!(
+            self.status == SUBSCRIPTION_ACTIVE,
+            ClawWalletError::SubscriptionInactive
+        );
+        require!(now >= self.next_due, ClawWalletError::PaymentNotDue);
+        if funded {
+            return Ok(true);
+        }
+
+        if self.failed_since == 0 {
+            self.failed_since = now;
+        }
+        if now >= self.failed_since + self.grace_period {
+            self.set_status(SUBSCRIPTION_LAPSED, agent_id);
+        } else {
+            emit!(SubscriptionChargeFailed {
+                agent_id: agent_id.to_string(),
+                merchant: self.merchant,
+                amount: self.amount,
+                failed_since: self.failed_since,
+                grace_ends_at: self.failed_since + self.grace_period,
+            });
+        }
+        Ok(false)
+    }
+
+    pub fn set_status(&mut self, status: u8, agent_id: &str) {
+        self.status = status;
+        emit!(SubscriptionStatusChanged {
+            agent_id: agent_id.to_string(),
+            merchant: self.merchant,
+            status,
+        });
+    }
+
+    /// Check the charge is due and within the cap, then advance the billing period; returns the amount
+    pub fn record_charge(&mut self, now: i64) -> Result<u64> {
+        require!(now >= self.next_due, ClawWalletError::PaymentNotDue);
+        let total = self
+            .total_charged
+            .checked_add(self.amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            total <= self.max_total,
+            ClawWalletError::SubscriptionCapExceeded
+        );
+
+        self.total_charged = total;
+        self.charges += 1;
+        self.failed_since = 0;
+        // A late charge restarts the schedule from now rather than allowing back-to-back catch-up charges
+        self.next_due = std::cmp::max(self.next_due + self.interval, now + self.interval);
+        Ok(self.amount)
+    }
+}
+
+/// Registered payee for subscriptions and mandates (["merchant", authority])
+#[account]
+#[derive(InitSpace)]
+pub struct Merchant {
+    pub authority: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    pub settlement: Pubkey,               // receives SOL charges
+    pub settlement_token_account: Pubkey, // receives token charges; default = SOL only
+    pub fee_bps: Option<u16>, // admin-set protocol fee for this merchant's charges; None = default schedule
+    pub active: bool,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl Merchant {
+    /// Protocol fee rate for a charge by this merchant
+    pub fn fee_bps_or(&self, default_bps: u16) -> u16 {
+        self.fee_bps.unwrap_or(default_bps)
+    }
+}
+
+/// Merchant pull authorization with a per-period cap (["mandate", wallet, merchant])
+#[account]
+#[derive(InitSpace)]
+pub struct Mandate {
+    pub wallet: Pubkey,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub max_per_period: u64,
+    pub period: i64,
+    pub period_start: i64,
+    pub spent_in_period: u64,
+    pub total_charged: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Mandate {
+    /// Count a pull against the current period's cap, rolling the period forward once elapsed
+    pub fn record_charge(&mut self, amount: u64, now: i64) -> Result<()> {
+        if now >= self.period_start + self.period {
+            let elapsed_periods = (now - self.period_start) / self.period;
+            self.period_start += elapsed_periods * self.period;
+            self.spent_in_period = 0;
+        }
+        let spent = self
+            .spent_in_period
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            spent <= self.max_per_period,
+            ClawWalletError::MandateCapExceeded
+        );
+        self.spent_in_period = spent;
+        self.total_charged = self.total_charged.saturating_add(amount);
+        Ok(())
+    }
+}
+
+/// SOL deposit streamed linearly to a recipient (["stream", wallet, nonce]); the PDA holds the lamports
+#[account]
+#[derive(InitSpace)]
+pub struct Stream {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub deposit: u64,
+    pub withdrawn: u64,
+    pub rate_per_second: u64, // lamports
+    pub start_time: i64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub cliff_time: i64,          // 0 = no cliff
+    pub checkpoint_time: i64,     // accrual resumes from here at the current rate
+    pub checkpoint_streamed: u64, // accrued up to checkpoint_time
+    pub transferable: bool,       // recipient may reassign the stream
+}
+
+impl Stream {
+    /// Accrued as of `now` ignoring the cliff, capped at the deposit
+    fn accrued(&self, now: i64) -> u64 {
+        if now <= self.checkpoint_time {
+            return self.checkpoint_streamed;
+        }
+        let elapsed = (now - self.checkpoint_time) as u64;
+        self.checkpoint_streamed
+            .saturating_add(self.rate_per_second.saturating_mul(elapsed))
+            .min(self.deposit)
+    }
+
+    /// Total accrued to the recipient as of `now`; nothing before the cliff
+    pub fn streamed(&self, now: i64) -> u64 {
+        if now < self.cliff_time {
+            return 0;
+        }
+        self.accrued(now)
+    }
+
+    /// Lock in what has accrued so rate or deposit changes only apply going forward
+    pub fn checkpoint(&mut self, now: i64) {
+        if now > self.checkpoint_time {
+            self.checkpoint_streamed = self.accrued(now);
+            self.checkpoint_time = now;
+        }
+    }
+
+    /// Accrued but not yet withdrawn
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.streamed(now) - self.withdrawn
+    }
+}
+
+/// SOL grant vesting to a beneficiary after a cliff, linearly until end (["vesting", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct VestingGrant {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub beneficiary: Pubkey,
+    pub total: u64, // reduced to the vested amount on revoke
+    pub claimed: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub revoked_at: i64, // 0 = not revoked
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl VestingGrant {
+    /// Total vested as of `now`
+    pub fn vested(&self, now: i64) -> u64 {
+        if self.revoked_at != 0 || now >= self.end_time {
+            return self.total;
+        }
+        if now < self.cliff_time {
+            return 0;
+        }
+        let elapsed = (now - self.start_time) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PayrollEntry {
+    pub agent: Pubkey,        // payee agent wallet
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+}
+
+/// Recurring roster paid from an agent wallet by a keeper crank (["payroll", wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct Payroll {
+    pub wallet: Pubkey,
+    #[max_len(16)]
+    pub entries: Vec<PayrollEntry>,
+    pub period: i64,
+    pub next_run: i64,
+    pub runs: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Payroll {
+    /// Validate and store a roster
+    pub fn set_roster(&mut self, entries: Vec<PayrollEntry>) -> Result<()> {
+        require!(
+            !entries.is_empty() && entries.len() <= MAX_PAYROLL_ENTRIES,
+            ClawWalletError::InvalidPayroll
+        );
+        require!(
+            entries.iter().all(|e| e.amount > 0),
+            ClawWalletError::InvalidAmount
+        );
+        self.entries = entries;
+        require!(
+            self.mints().len() <= MAX_PAYROLL_MINTS,
+            ClawWalletError::InvalidPayroll
+        );
+        Ok(())
+    }
+
+    /// Distinct token mints on the roster, in order of first appearance
+    pub fn mints(&self) -> Vec<Pubkey> {
+        let mut mints: Vec<Pubkey> = Vec::new();
+        for mint in self.entries.iter().filter_map(|e| e.mint) {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+        mints
+    }
+}
+
+/// Funds held between two agent wallets until released or refunded (["escrow", wallet, nonce]).
+/// SOL sits in the PDA itself; SPL tokens sit in an ["escrow_vault", escrow] token account.
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub wallet: Pubkey, // payer agent wallet
+    pub payee: Pubkey,  // payee agent wallet
+    pub nonce: u64,
+    pub rent_payer: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    #[max_len(8)]
+    pub milestones: Vec<u64>, // per-milestone amounts summing to `amount`
+    pub released_mask: u8, // bit i set = milestone i released
+    pub released: u64,
+    pub expires_at: i64,  // unreleased funds return to the payer after this
+    pub arbiter: Pubkey,  // default = no arbiter, disputes disabled
+    pub disputed_at: i64, // 0 = not disputed
+    pub disputed_by: Pubkey,
+}
+
+impl Escrow {
+    /// Validate milestone amounts and return their total
+    pub fn total_of(milestones: &[u64]) -> Result<u64> {
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_ESCROW_MILESTONES,
+            ClawWalletError::InvalidEscrow
+        );
+        require!(
+            milestones.iter().all(|m| *m > 0),
+            ClawWalletError::InvalidAmount
+        );
+        let total = milestones
+            .iter()
+            .try_fold(0u64, |acc, m| acc.checked_add(*m))
+            .ok_or(ClawWalletError::MathOverflow)?;
+        Ok(total)
+    }
+
+    /// Mark milestone `index` released and return its amount
+    pub fn release_milestone(&mut self, index: u8) -> Result<u64> {
+        let amount = *self
+            .milestones
+            .get(index as usize)
+            .ok_or(ClawWalletError::InvalidEscrow)?;
+        require!(
+            self.released_mask & (1 << index) == 0,
+            ClawWalletError::MilestoneReleased
+        );
+        self.released_mask |= 1 << index;
+        self.released += amount;
+        Ok(amount)
+    }
+
+    /// Committed funds not yet released
+    pub fn remaining(&self) -> u64 {
+        self.amount - self.released
+    }
+
+    /// When the expiry crank may refund the payer; a dispute gives the arbiter time to rule first
+    pub fn refundable_at(&self) -> i64 {
+        if self.disputed_at == 0 {
+            return self.expires_at;
+        }
+        std::cmp::max(
+            self.expires_at,
+            self.disputed_at + DISPUTE_RESOLUTION_PERIOD,
+        )
+    }
+}
+
+/// SOL stake backing an escrow arbiter (["arbiter", authority]); the PDA holds the bond
+#[account]
+#[derive(InitSpace)]
+pub struct ArbiterBond {
+    pub authority: Pubkey,
+    pub bond: u64,
+    pub slashed: u64,
+    pub disputes_resolved: u64,
+    pub registered_at: i64,
+    pub unbonding_at: i64, // 0 = bonded; else withdrawable from then on
+    pub bump: u8,
+}
+
+impl ArbiterBond {
+    /// Bonded at or above the protocol minimum and not unbonding
+    pub fn is_eligible(&self, config: &ProtocolConfig) -> bool {
+        self.unbonding_at == 0 && self.bond >= config.min_arbiter_bond
+    }
+}
+
+/// Hash-time-locked payment (["htlc", wallet, nonce]); SOL sits in the PDA, tokens in ["htlc_vault", htlc]
+#[account]
+#[derive(InitSpace)]
+pub struct Htlc {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey, // system account for SOL, token account owner for SPL
+    pub rent_payer: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub hashlock: [u8; 32], // sha256(preimage)
+    pub timeout: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Transfer held until the recipient claims it (["claimable", recipient, wallet, nonce]);
+/// SOL sits in the PDA, tokens in ["claimable_vault", claimable]
+#[account]
+#[derive(InitSpace)]
+pub struct Claimable {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub rent_payer: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub expires_at: i64, // returnable to the sender from then on
+}
+
+/// Request-to-pay issued by an agent wallet (["invoice", issuer, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct Invoice {
+    pub issuer: Pubkey, // issuing agent wallet; receives payment
+    pub nonce: u64,
+    pub payer: Pubkey,        // default = anyone may pay
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,
+    #[max_len(64)]
+    pub memo: String,
+    pub expires_at: i64, // 0 = never
+    pub created_at: i64,
+    pub paid_at: i64,    // 0 = not yet fully paid
+    pub paid_by: Pubkey, // most recent payer
+    pub bump: u8,
+    pub paid_amount: u64, // paid so far across installments
+}
+
+impl Invoice {
+    pub fn outstanding(&self) -> u64 {
+        self.amount.saturating_sub(self.paid_amount)
+    }
+
+    /// Not fully paid, unexpired, and payable by `payer`
+    pub fn require_payable(&self, payer: Pubkey, now: i64) -> Result<()> {
+        require!(self.paid_at == 0, ClawWalletError::InvoiceAlreadyPaid);
+        require!(
+            self.expires_at == 0 || now < self.expires_at,
+            ClawWalletError::InvoiceExpired
+        );
+        require!(
+            self.payer == Pubkey::default() || self.payer == payer,
+            ClawWalletError::InvalidInvoice
+        );
+        require!(payer != self.issuer, ClawWalletError::InvalidInvoice);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RevenueShareEntry {
+    pub recipient: Pubkey, // SOL recipient, or owner of the recipient token account
+    pub share_bps: u16,
+}
+
+/// Standing split of a wallet's incoming invoice settlements and claimables (["revenue_share", wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct RevenueShare {
+    pub wallet: Pubkey,
+    #[max_len(4)]
+    pub shares: Vec<RevenueShareEntry>,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl RevenueShare {
+    pub fn set_shares(&mut self, shares: Vec<RevenueShareEntry>) -> Result<()> {
+        require!(
+            shares.len() <= MAX_REVENUE_SHARES,
+            ClawWalletError::InvalidShares
+        );
+        require!(
+            shares.iter().all(|s| s.recipient != Pubkey::default()),
+            ClawWalletError::InvalidShares
+        );
+        let bps: Vec<u16> = shares.iter().map(|s| s.share_bps).collect();
+        validate_shares(&bps)?;
+        self.shares = shares;
+        Ok(())
+    }
+}
+
+/// SOL crowdfund raised by an agent wallet; the PDA holds the contributions (["crowdfund", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct Crowdfund {
+    pub wallet: Pubkey, // creator agent wallet
+    pub nonce: u64,
+    pub goal: u64,
+    pub deadline: i64,
+    pub raised: u64,
+    pub refund_pool: u64, // lamports being returned pro-rata; set on the first refund of a failed crowdfund
+    pub open_contributions: u32,
+    pub claimed: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Crowdfund {
+    pub fn is_open(&self, now: i64) -> bool {
+        !self.claimed && now < self.deadline
+    }
+
+    /// Deadline passed without reaching the goal, or the goal was reached but not claimed in time
+    pub fn has_failed(&self, now: i64) -> bool {
+        !self.claimed
+            && now >= self.deadline
+            && (self.raised < self.goal
+                || now >= self.deadline.saturating_add(CROWDFUND_CLAIM_WINDOW))
+    }
+}
+
+/// One wallet's running contribution to a crowdfund (["contribution", crowdfund, wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    pub crowdfund: Pubkey,
+    pub wallet: Pubkey, // contributing agent wallet
+    pub rent_payer: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+/// SOL reward locked against a task description hash (["bounty", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct Bounty {
+    pub wallet: Pubkey, // posting agent wallet
+    pub nonce: u64,
+    pub reward: u64,
+    pub task_hash: [u8; 32],
+    pub expires_at: i64, // 0 = never
+    pub status: u8,
+    pub winner: Pubkey, // default = not awarded
+    pub open_claims: u32,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// A wallet's submission against a bounty (["bounty_claim", bounty, wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct BountyClaim {
+    pub bounty: Pubkey,
+    pub wallet: Pubkey, // claimant agent wallet
+    pub submission_hash: [u8; 32],
+    pub submitted_at: i64,
+    pub bump: u8,
+}
+
+/// A service an agent sells at a fixed price (["listing", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub wallet: Pubkey, // selling agent wallet; receives payment
+    pub nonce: u64,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub price: u64,
+    #[max_len(200)]
+    pub metadata_uri: String,
+    pub active: bool,
+    pub orders: u64, // next order id
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Prepaid SOL a consumer agent holds with a provider agent; the PDA holds the balance
+/// (["credits", consumer, provider])
+#[account]
+#[derive(InitSpace)]
+pub struct CreditAccount {
+    pub consumer: Pubkey, // consumer agent wallet
+    pub provider: Pubkey, // provider agent wallet
+    pub balance: u64,
+    pub consumed: u64, // lifetime debits
+    pub rent_payer: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+    pub unit_price: u64,  // postpaid lamports per usage unit; 0 = metering off
+    pub usage_cap: u64,   // most that may be billed per day
+    pub usage_units: u64, // append-only, provider-incremented
+    pub settled_units: u64,
+    pub cap_used: u64, // billed in the current period
+    pub period_start: i64,
+    pub withdraw_requested: u64,
+    pub withdraw_at: i64, // 0 = no withdrawal pending
+}
+
+impl CreditAccount {
+    /// Lamports owed for usage recorded since the last settlement
+    pub fn owed(&self) -> Result<u64> {
+        let owed = (self.usage_units - self.settled_units)
+            .checked_mul(self.unit_price)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        Ok(owed)
+    }
+}
+
+/// Two-party SOL payment channel; the PDA holds both deposits (["channel", wallet_a, wallet_b])
+#[account]
+#[derive(InitSpace)]
+pub struct Channel {
+    pub wallet_a: Pubkey, // opener
+    pub wallet_b: Pubkey,
+    pub signer_a: Pubkey, // off-chain state signer for each side
+    pub signer_b: Pubkey, // default = not joined yet
+    pub deposit_a: u64,
+    pub deposit_b: u64,
+    pub state_nonce: u64, // latest submitted state; 0 = opening deposits
+    pub balance_a: u64,
+    pub balance_b: u64,
+    pub challenge_period: i64,
+    pub closing_at: i64, // 0 = open
+    pub rent_payer: Pubkey,
+    pub opened_at: i64,
+    pub bump: u8,
+    pub epoch: u64, // opener's channel_nonce at open; part of every signed state
+}
+
+impl Channel {
+    /// A balance update may only redistribute the deposits
+    pub fn require_balanced(&self, balance_a: u64, balance_b: u64) -> Result<()> {
+        let total = balance_a
+            .checked_add(balance_b)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            total == self.deposit_a + self.deposit_b,
+            ClawWalletError::InvalidChannelState
+        );
+        Ok(())
+    }
+}
+
+/// Proof that an agent wallet made a payment (["receipt", wallet, nonce])
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentReceipt {
+    pub payer: Pubkey, // paying agent wallet
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub mint: Option<Pubkey>, // None = SOL
+    pub amount: u64,          // received by the payee, after any deducted fee
+    pub reference: Pubkey,
+    pub slot: u64,
+    pub created_at: i64,
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+    pub refunded: u64,           // returned to the payer so far
+    pub resource_hash: [u8; 32], // set by pay_for_resource(_token); [0; 32] otherwise
+}
+
+impl PaymentReceipt {
+    /// Count a refund against the payment; refunds may not exceed what was paid
+    pub fn record_refund(&mut self, amount: u64) -> Result<()> {
+        let refunded = self
+            .refunded
+            .checked_add(amount)
+            .ok_or(ClawWalletError::MathOverflow)?;
+        require!(
+            refunded <= self.amount,
+            ClawWalletError::RefundExceedsPayment
+        );
+        self.refunded = refunded;
+        Ok(())
+    }
+
+    /// What the payee has kept after refunds
+    pub fn net_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.refunded)
+    }
+
+    /// Whether the receipt proves a payment to `payee` of `mint` worth at least `min_amount` after refunds
+    /// (and nonzero), with `reference` and `resource_hash` when given
+    pub fn satisfies(
+        &self,
+        payee: Pubkey,
+        mint: Option<Pubkey>,
+        min_amount: u64,
+        reference: Option<Pubkey>,
+        resource_hash: Option<[u8; 32]>,
+    ) -> bool {
+        let net = self.net_amount();
+        self.payee == payee
+            && self.mint == mint
+            && net > 0
+            && net >= min_amount
+            && reference.map_or(true, |r| r == self.reference)
+            && resource_hash.map_or(true, |h| h == self.resource_hash)
+    }
+}
+
+#[event]
+pub struct WalletCreated {
+    pub agent_id: String,
+    pub wallet: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct SolSent {
+    pub agent_id: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub recipient: Pubkey,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+}
+
+#[event]
+pub struct AgentTransfer {
+    pub from_agent: String,
+    pub to_agent: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+}
+
+#[event]
+pub struct TokenSent {
+    pub agent_id: String,
+    pub mint: Pubkey,
+    pub amount: u64,    // received by the recipient
+    pub ui_amount: u64, // `amount` with interest-bearing mints' accrued interest applied
+    pub fee: u64,
+    pub transfer_fee: u64, // withheld by the mint's Token-2022 transfer-fee extension
+    pub recipient: Pubkey,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+    pub fee_transfer_fee: u64, // withheld from the treasury and referrer legs; `fee` is gross of it
+}
+
+#[event]
+pub struct DailyLimitSet {
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub daily_limit: u64,
+}
+
+#[event]
+pub struct MaxPerTxSet {
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub max_per_tx: u64,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub agent_id: String,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub permissions: u8,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub agent_id: String,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct AgentAuthoritySet {
+    pub agent_id: String,
+    pub agent_authority: Pubkey,
+}
+
+#[event]
+pub struct AgentKeyRotated {
+    pub agent_id: String,
+    pub old_agent_authority: Pubkey,
+    pub new_agent_authority: Pubkey,
+}
+
+#[event]
+pub struct AgentKeyRevoked {
+    pub agent_id: String,
+    pub agent_authority: Pubkey,
+}
+
+#[event]
+pub struct MultisigWalletCreated {
+    pub agent_id: String,
+    pub wallet: Pubkey,
+    pub multisig: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct TransactionProposed {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TransactionApproved {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub owner: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct TransactionExecuted {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+}
+
+#[event]
+pub struct OwnerTransferProposed {
+    pub agent_id: String,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnershipAccepted {
+    pub agent_id: String,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct GuardiansSet {
+    pub agent_id: String,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct RecoveryInitiated {
+    pub agent_id: String,
+    pub guardian: Pubkey,
+    pub new_owner: Pubkey,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct RecoveryApproved {
+    pub agent_id: String,
+    pub guardian: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct WalletRecovered {
+    pub agent_id: String,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct RecoveryCancelled {
+    pub agent_id: String,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct BeneficiarySet {
+    pub agent_id: String,
+    pub beneficiary: Pubkey,
+    pub inactivity_period: i64,
+}
+
+#[event]
+pub struct WalletInherited {
+    pub agent_id: String,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct WalletFrozen {
+    pub agent_id: String,
+}
+
+#[event]
+pub struct WalletUnfrozen {
+    pub agent_id: String,
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct ProtocolPauseSet {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct PausedInstructionsSet {
+    pub admin: Pubkey,
+    pub paused_instructions: u64,
+}
+
+#[event]
+pub struct AllowlistUpdated {
+    pub agent_id: String,
+    pub recipient: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct AllowlistToggled {
+    pub agent_id: String,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct DenylistUpdated {
+    pub wallet: Option<Pubkey>, // None = protocol denylist
+    pub recipient: Pubkey,
+    pub denied: bool,
+}
+
+#[event]
+pub struct TimelockSet {
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub threshold: u64,
+    pub delay: i64,
+}
+
+#[event]
+pub struct TransferQueued {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct QueuedTransferExecuted {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+}
+
+#[event]
+pub struct QueuedTransferCancelled {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ApproversSet {
+    pub agent_id: String,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct PaymentProposed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PaymentApproved {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub approver: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct PaymentExecuted {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+}
+
+#[event]
+pub struct PaymentRejected {
+    pub agent_id: String,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct ExpiredAccountClosed {
+    pub wallet: Pubkey,
+    pub account: Pubkey,
+}
+
+#[event]
+pub struct WalletClosed {
+    pub agent_id: String,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub token_accounts_closed: u8,
+    pub companions_closed: u8, // spend policy, lists, recovery, approvers, metadata, multisig
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub rent_paid: u64,
+}
+
+#[event]
+pub struct MetadataUpdated {
+    pub agent_id: String,
+    pub display_name: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct FeeBpsSet {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct TreasuryChangeProposed {
+    pub admin: Pubkey,
+    pub new_treasury: Pubkey,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct TreasuryChanged {
+    pub admin: Pubkey,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct FeeTiersSet {
+    pub admin: Pubkey,
+    pub tiers: Vec<FeeTier>,
+}
+
+#[event]
+pub struct FeeExemptionUpdated {
+    pub subject: Pubkey,
+    pub reason: u8,
+    pub exempt: bool,
+}
+
+#[event]
+pub struct FeeExemptionUsed {
+    pub wallet: Pubkey,
+    pub subject: Pubkey,
+    pub reason: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeRulesSet {
+    pub admin: Pubkey,
+    pub min_fee: u64,
+    pub rounding: u8,
+}
+
+#[event]
+pub struct FeeCapSet {
+    pub admin: Pubkey,
+    pub max_fee: u64,
+    pub max_token_fee: u64,
+}
+
+#[event]
+pub struct FeeCampaignCreated {
+    pub id: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub mint: Option<Pubkey>,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct FeeCampaignClosed {
+    pub id: u64,
+}
+
+#[event]
+pub struct MintFeeSet {
+    pub mint: Pubkey,
+    pub fee_bps: u16,
+    pub max_fee: u64,
+}
+
+#[event]
+pub struct ReferralRegistered {
+    pub agent_id: String,
+    pub referrer_agent_id: String,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ReferralShareSet {
+    pub admin: Pubkey,
+    pub share_bps: u16,
+}
+
+#[event]
+pub struct ReferralFeeSplit {
+    pub referrer: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralCodeCreated {
+    pub agent_id: String,
+    pub code: String,
+}
+
+#[event]
+pub struct ReferralCodeUsed {
+    pub agent_id: String,
+    pub code: String,
+    pub referrer: Pubkey,
+    pub signups: u64,
+}
+
+#[event]
+pub struct FeeAccrualSet {
+    pub admin: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub treasury: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeBeneficiariesSet {
+    pub admin: Pubkey,
+    pub beneficiaries: Vec<FeeBeneficiary>,
+}
+
+#[event]
+pub struct FeesDistributed {
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub beneficiaries: u8,
+}
+
+#[event]
+pub struct PointsRedeemed {
+    pub agent_id: String,
+    pub points_burned: u64,
+    pub credits: u64,
+}
+
+#[event]
+pub struct PointsDiscountSet {
+    pub admin: Pubkey,
+    pub discount_cost: u64,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct PointsTransferred {
+    pub from_agent: String,
+    pub to_agent: String,
+    pub amount: u64,
+    pub tax: u64,
+}
+
+#[event]
+pub struct PointsTransferTaxSet {
+    pub admin: Pubkey,
+    pub tax_bps: u16,
+}
+
+#[event]
+pub struct ScoreSubmitted {
+    pub agent_id: String,
+    pub points: u64,
+    pub rank: Option<u8>, // 0-based; None = didn't qualify
+}
+
+#[event]
+pub struct EpochDurationSet {
+    pub admin: Pubkey,
+    pub epoch: u64,
+    pub epoch_duration: i64,
+}
+
+#[event]
+pub struct EpochFinalized {
+    pub epoch: u64,
+    pub mint: Option<Pubkey>,
+    pub winners: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+}
+
+#[event]
+pub struct EpochRewardClaimed {
+    pub epoch: u64,
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PointsDecaySet {
+    pub admin: Pubkey,
+    pub decay_bps: u16,
+}
+
+#[event]
+pub struct PointsDecayed {
+    pub agent_id: String,
+    pub periods: u64,
+    pub points_lost: u64,
+}
+
+#[event]
+pub struct PointsCapSet {
+    pub admin: Pubkey,
+    pub points_cap: u64,
+}
+
+#[event]
+pub struct Prestiged {
+    pub agent_id: String,
+    pub prestige: u16,
+    pub points_reset: u64,
+}
+
+#[event]
+pub struct PointsBurned {
+    pub agent_id: String,
+    pub amount: u64,
+    pub reason: u16,
+}
+
+#[event]
+pub struct AchievementUnlocked {
+    pub agent_id: String,
+    pub badge: u8,
+}
+
+#[event]
+pub struct LevelUp {
+    pub agent_id: String,
+    pub level: u8,
+}
+
+#[event]
+pub struct StreakBonus {
+    pub agent_id: String,
+    pub streak_days: u32,
+    pub bonus: u64,
+}
+
+#[event]
+pub struct RewardRateSet {
+    pub admin: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_rate: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub agent_id: String,
+    pub points: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DistributorRootSet {
+    pub id: u64,
+    pub root: [u8; 32],
+    pub mint: Option<Pubkey>,
+}
+
+#[event]
+pub struct DistributionClaimed {
+    pub id: u64,
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PointsCampaignCreated {
+    pub id: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub mint: Option<Pubkey>,
+    pub multiplier_bps: u16,
+}
+
+#[event]
+pub struct PointsCampaignClosed {
+    pub id: u64,
+}
+
+#[event]
+pub struct PointsCampaignApplied {
+    pub id: u64,
+    pub agent_id: String,
+    pub base_points: u64,
+    pub points: u64,
+}
+
+#[event]
+pub struct EarningRulesSet {
+    pub admin: Pubkey,
+    pub min_earn_lamports: u64,
+    pub min_earn_token: u64,
+    pub daily_points_cap: u64,
+    pub repeat_points_bps: u16,
+}
+
+#[event]
+pub struct QuestCreated {
+    pub id: u64,
+    pub kind: u8,
+    pub mint: Option<Pubkey>,
+    pub target: u64,
+    pub reward_points: u64,
+    pub end_at: i64,
+}
+
+#[event]
+pub struct QuestClosed {
+    pub id: u64,
+}
+
+#[event]
+pub struct QuestStarted {
+    pub id: u64,
+    pub agent_id: String,
+}
+
+#[event]
+pub struct QuestCompleted {
+    pub id: u64,
+    pub agent_id: String,
+}
+
+#[event]
+pub struct QuestRewardClaimed {
+    pub id: u64,
+    pub agent_id: String,
+    pub points: u64,
+}
+
+#[event]
+pub struct SeasonRolled {
+    pub admin: Pubkey,
+    pub season: u32,
+    pub started_at: i64,
+}
+
+#[event]
+pub struct SeasonSnapshotted {
+    pub agent_id: String,
+    pub season: u32,
+    pub points: u64,
+}
+
+#[event]
+pub struct PointsFormulasSet {
+    pub admin: Pubkey,
+    pub sol_points: PointsFormula,
+    pub token_points: PointsFormula,
+    pub mint_points: Vec<MintPointsFormula>,
+}
+
+#[event]
+pub struct PriceFeedsSet {
+    pub admin: Pubkey,
+    pub sol_price_feed: Pubkey,
+    pub mint_price_feeds: Vec<MintPriceFeed>,
+    pub usd_points: PointsFormula,
+    pub max_price_age: u64,
+}
+
+#[event]
+pub struct SolBatchSent {
+    pub agent_id: String,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+}
+
+#[event]
+pub struct TokenBatchSent {
+    pub agent_id: String,
+    pub mint: Pubkey,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+    pub transfer_fee: u64, // withheld from the recipient legs by the mint's Token-2022 transfer-fee extension
+}
+
+#[event]
+pub struct MultiSent {
+    pub agent_id: String,
+    pub recipient: Pubkey,
+    pub sol_amount: u64,
+    pub sol_fee: u64,
+    pub mints: Vec<Pubkey>,
+    pub token_amounts: Vec<u64>,
+    pub token_fees: Vec<u64>,
+    pub points_earned: u64,
+    pub memo: Option<String>,
+    pub category: u8,
+    pub references: Vec<Pubkey>, // Solana Pay reference keys
+    pub transfer_fees: Vec<u64>, // per token leg, withheld from the recipient by Token-2022 transfer-fee mints
+}
+
+#[event]
+pub struct PaymentScheduled {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct ScheduledPaymentExecuted {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+}
+
+#[event]
+pub struct ScheduledPaymentCancelled {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct KeeperIncentivesSet {
+    pub admin: Pubkey,
+    pub keeper_tip: u64,
+    pub cleanup_bounty_bps: u16,
+}
+
+#[event]
+pub struct KeeperPaid {
+    pub keeper: Pubkey,
+    pub account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub interval: i64,
+    pub next_due: i64,
+    pub max_total: u64,
+    pub grace_period: i64,
+}
+
+#[event]
+pub struct SubscriptionCharged {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub next_due: i64,
+}
+
+#[event]
+pub struct SubscriptionStatusChanged {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub status: u8,
+}
+
+#[event]
+pub struct SubscriptionChargeFailed {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub amount: u64,
+    pub failed_since: i64,
+    pub grace_ends_at: i64,
+}
+
+#[event]
+pub struct MerchantRegistered {
+    pub merchant: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub settlement: Pubkey,
+    pub settlement_token_account: Pubkey,
+}
+
+#[event]
+pub struct MerchantUpdated {
+    pub merchant: Pubkey,
+    pub settlement: Pubkey,
+    pub settlement_token_account: Pubkey,
+}
+
+#[event]
+pub struct MerchantTermsSet {
+    pub admin: Pubkey,
+    pub merchant: Pubkey,
+    pub fee_bps: Option<u16>,
+    pub active: bool,
+}
+
+#[event]
+pub struct MandateApproved {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub max_per_period: u64,
+    pub period: i64,
+}
+
+#[event]
+pub struct MandateRevoked {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+}
+
+#[event]
+pub struct MandateCharged {
+    pub agent_id: String,
+    pub merchant: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub spent_in_period: u64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub deposit: u64,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub transferable: bool,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub paid_out: u64,
+    pub fee: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct StreamToppedUp {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub amount: u64,
+    pub deposit: u64,
+}
+
+#[event]
+pub struct StreamRateChanged {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub old_rate: u64,
+    pub new_rate: u64,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct StreamRecipientChanged {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub old_recipient: Pubkey,
+    pub new_recipient: Pubkey,
+}
+
+#[event]
+pub struct StreamClosed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub rent_refunded: u64,
+}
+
+#[event]
+pub struct VestingGrantCreated {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct VestedClaimed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub beneficiary: Pubkey,
+    pub vested: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct VestingGrantClosed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub beneficiary: Pubkey,
+    pub rent_refunded: u64,
+}
+
+#[event]
+pub struct PayrollUpdated {
+    pub agent_id: String,
+    pub entries: u8,
+    pub period: i64,
+    pub next_run: i64,
+}
+
+#[event]
+pub struct PayrollClosed {
+    pub agent_id: String,
+    pub runs: u64,
+}
+
+#[event]
+pub struct PayrollRun {
+    pub agent_id: String,
+    pub run: u64,
+    pub entries: u8,
+    pub sol_total: u64,
+    pub sol_fee: u64,
+    pub mints: Vec<Pubkey>,
+    pub token_totals: Vec<u64>,
+    pub token_fees: Vec<u64>,
+    pub next_run: i64,
+}
+
+#[event]
+pub struct EscrowCreated {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub milestones: u8,
+    pub expires_at: i64,
+    pub arbiter: Pubkey,
+}
+
+#[event]
+pub struct EscrowReleased {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub milestone: u8,
+    pub amount: u64,
+    pub fee: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub expired: bool, // true = returned by the expiry crank
+}
+
+#[event]
+pub struct EscrowDisputeOpened {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub arbiter: Pubkey,
+    pub disputed_by: Pubkey,
+}
+
+#[event]
+pub struct EscrowDisputeResolved {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub arbiter: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub payee_amount: u64,
+    pub payer_amount: u64,
+    pub fee: u64,
+    pub resolved_at: i64,
+}
+
+#[event]
+pub struct MinArbiterBondSet {
+    pub admin: Pubkey,
+    pub min_arbiter_bond: u64,
+}
+
+#[event]
+pub struct ArbiterBondChanged {
+    pub arbiter: Pubkey,
+    pub bond: u64,
+    pub unbonding_at: i64,
+}
+
+#[event]
+pub struct ArbiterSlashed {
+    pub admin: Pubkey,
+    pub arbiter: Pubkey,
+    pub amount: u64,
+    pub bond: u64,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct HtlcLocked {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub hashlock: [u8; 32],
+    pub timeout: i64,
+}
+
+#[event]
+pub struct HtlcClaimed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub preimage: [u8; 32],
+}
+
+#[event]
+pub struct HtlcRefunded {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ClaimableSent {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub references: Vec<Pubkey>,
+}
+
+#[event]
+pub struct ClaimableClaimed {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct ClaimableReturned {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ClaimableTtlSet {
+    pub admin: Pubkey,
+    pub claimable_ttl: i64,
+}
+
+#[event]
+pub struct ClaimableExpired {
+    pub agent_id: String,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct InvoiceCreated {
+    pub agent_id: String,
+    pub invoice: Pubkey,
+    pub nonce: u64,
+    pub payer: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub memo: String,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct InvoicePaid {
+    pub agent_id: String, // payer
+    pub invoice: Pubkey,
+    pub issuer: Pubkey,
+    pub nonce: u64,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub paid_amount: u64,
+    pub remaining: u64,
+    pub clamped: u64, // requested beyond the outstanding balance and not drawn
+    pub references: Vec<Pubkey>,
+}
+
+#[event]
+pub struct InvoiceSettled {
+    pub invoice: Pubkey,
+    pub issuer: Pubkey,
+    pub nonce: u64,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub settled_by: Pubkey, // payer of the final installment
+}
+
+#[event]
+pub struct InvoiceClosed {
+    pub agent_id: String,
+    pub invoice: Pubkey,
+    pub paid: bool,
+}
+
+#[event]
+pub struct Refunded {
+    pub agent_id: String, // refunding wallet
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub receipt: Pubkey, // the refunded payment's receipt
+    pub refunded: u64,   // total refunded against it so far
+}
+
+#[event]
+pub struct PaymentSplit {
+    pub agent_id: String,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub shares_bps: Vec<u16>,
+}
+
+#[event]
+pub struct RevenueShareSet {
+    pub agent_id: String,
+    pub shares: Vec<RevenueShareEntry>,
+}
+
+#[event]
+pub struct RevenueShareClosed {
+    pub agent_id: String,
+}
+
+#[event]
+pub struct RevenueShared {
+    pub wallet: Pubkey, // receiving wallet
+    pub source: Pubkey, // invoice or claimable
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+}
+
+#[event]
+pub struct TipSent {
+    pub wallet: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub points_earned: u64,
+}
+
+#[event]
+pub struct TipFeeSet {
+    pub admin: Pubkey,
+    pub tip_fee: u64,
+}
+
+#[event]
+pub struct CrowdfundCreated {
+    pub agent_id: String,
+    pub crowdfund: Pubkey,
+    pub nonce: u64,
+    pub goal: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct CrowdfundContributed {
+    pub agent_id: String, // contributor
+    pub crowdfund: Pubkey,
+    pub amount: u64,
+    pub raised: u64,
+}
+
+#[event]
+pub struct CrowdfundClaimed {
+    pub agent_id: String,
+    pub crowdfund: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct ContributionClosed {
+    pub agent_id: String, // contributor
+    pub crowdfund: Pubkey,
+    pub contributed: u64,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct CrowdfundClosed {
+    pub agent_id: String,
+    pub crowdfund: Pubkey,
+    pub claimed: bool,
+}
+
+#[event]
+pub struct BountyPosted {
+    pub agent_id: String,
+    pub bounty: Pubkey,
+    pub nonce: u64,
+    pub reward: u64,
+    pub task_hash: [u8; 32],
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct BountyClaimSubmitted {
+    pub agent_id: String, // claimant
+    pub bounty: Pubkey,
+    pub submission_hash: [u8; 32],
+}
+
+#[event]
+pub struct BountyAwarded {
+    pub agent_id: String, // poster
+    pub bounty: Pubkey,
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub poster_points: u64,
+    pub winner_points: u64,
+}
+
+#[event]
+pub struct BountyCancelled {
+    pub agent_id: String,
+    pub bounty: Pubkey,
+    pub reward: u64,
+}
+
+#[event]
+pub struct ListingUpdated {
+    pub agent_id: String,
+    pub listing: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub price: u64,
+    pub metadata_uri: String,
+    pub active: bool,
+}
+
+#[event]
+pub struct ListingClosed {
+    pub agent_id: String,
+    pub listing: Pubkey,
+    pub orders: u64,
+}
+
+#[event]
+pub struct ServiceOrdered {
+    pub listing: Pubkey,
+    pub order_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub price: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub transfer_fee: u64, // withheld from the seller by a Token-2022 transfer-fee mint
+}
+
+#[event]
+pub struct CreditsDeposited {
+    pub agent_id: String,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct CreditsConsumed {
+    pub credits: Pubkey,
+    pub consumer: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub balance: u64,
+    pub usage_ref: [u8; 16], // provider's reference for the metered usage
+}
+
+#[event]
+pub struct CreditsWithdrawn {
+    pub agent_id: String,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct CreditsWithdrawRequested {
+    pub agent_id: String,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub withdraw_at: i64,
+}
+
+#[event]
+pub struct UsageTermsSet {
+    pub credits: Pubkey,
+    pub unit_price: u64,
+    pub usage_cap: u64,
+}
+
+#[event]
+pub struct UsageRecorded {
+    pub credits: Pubkey,
+    pub units: u64,
+    pub usage_units: u64,
+    pub owed: u64,
+}
+
+#[event]
+pub struct UsageSettled {
+    pub credits: Pubkey,
+    pub consumer: Pubkey,
+    pub provider: Pubkey,
+    pub units: u64,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct ChannelOpened {
+    pub channel: Pubkey,
+    pub wallet_a: Pubkey,
+    pub wallet_b: Pubkey,
+    pub deposit_a: u64,
+    pub challenge_period: i64,
+    pub epoch: u64, // signed into every off-chain state
+}
+
+#[event]
+pub struct ChannelJoined {
+    pub channel: Pubkey,
+    pub wallet_b: Pubkey,
+    pub deposit_b: u64,
+}
+
+#[event]
+pub struct ChannelStateSubmitted {
+    pub channel: Pubkey,
+    pub submitted_by: Pubkey,
+    pub state_nonce: u64,
+    pub balance_a: u64,
+    pub balance_b: u64,
+    pub closing_at: i64,
+}
+
+#[event]
+pub struct ChannelSettled {
+    pub channel: Pubkey,
+    pub state_nonce: u64,
+    pub balance_a: u64,
+    pub balance_b: u64,
+    pub fee: u64,
+    pub cooperative: bool,
+}
+
+#[event]
+pub struct ResourcePaid {
+    pub agent_id: String,
+    pub wallet: Pubkey,
+    pub payee: Pubkey,
+    pub resource_hash: [u8; 32],
+    pub nonce: u64,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub fee: u64,
+    pub points_earned: u64,
+    pub paid_at: i64,
+    pub references: Vec<Pubkey>,
+    pub transfer_fee: u64, // withheld from the payee by a Token-2022 transfer-fee mint
+}
+
+#[event]
+pub struct ReceiptCreated {
+    pub receipt: Pubkey,
+    pub payer: Pubkey,
+    pub nonce: u64,
+    pub payee: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReceiptClosed {
+    pub receipt: Pubkey,
+    pub payer: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct ConfidentialAccountConfigured {
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct ConfidentialDeposited {
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ConfidentialSent {
+    pub agent_id: String,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub fee: u64,
+}
+
+#[event]
+pub struct ConfidentialFeeSet {
+    pub admin: Pubkey,
+    pub confidential_fee: u64,
+}
+
+#[error_code]
+pub enum ClawWalletError {
+    #[msg("Insufficient funds in wallet")]
+    InsufficientFunds,
+    #[msg("Daily spend limit exceeded")]
+    DailyLimitExceeded,
+    #[msg("Spend policy account required for this wallet")]
+    SpendPolicyRequired,
+    #[msg("Too many per-mint limits on spend policy")]
+    TooManyTokenLimits,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Amount exceeds per-transaction maximum")]
+    MaxPerTxExceeded,
+    #[msg("Signer is not authorized for this wallet")]
+    Unauthorized,
+    #[msg("Session key has expired")]
+    SessionExpired,
+    #[msg("Session key does not allow this instruction")]
+    SessionPermissionDenied,
+    #[msg("Session spend cap exceeded")]
+    SessionCapExceeded,
+    #[msg("Agent authority already set")]
+    AgentAuthorityAlreadySet,
+    #[msg("Invalid agent authority")]
+    InvalidAgentAuthority,
+    #[msg("Wallet has no agent authority")]
+    NoAgentAuthority,
+    #[msg("Invalid multisig owner set")]
+    InvalidMultisigOwners,
+    #[msg("Invalid approval threshold")]
+    InvalidThreshold,
+    #[msg("Signer is not a multisig owner")]
+    NotMultisigOwner,
+    #[msg("Owner already approved this transaction")]
+    AlreadyApproved,
+    #[msg("Approval threshold not met")]
+    ThresholdNotMet,
+    #[msg("Wrong execute instruction for this transaction")]
+    WrongTransactionKind,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+    #[msg("No pending owner transfer")]
+    NoPendingOwner,
+    #[msg("Invalid guardian set")]
+    InvalidGuardians,
+    #[msg("Signer is not a guardian")]
+    NotGuardian,
+    #[msg("A recovery is already in progress")]
+    RecoveryInProgress,
+    #[msg("No recovery in progress")]
+    NoRecoveryInProgress,
+    #[msg("Recovery delay has not elapsed")]
+    RecoveryDelayNotElapsed,
+    #[msg("Inactivity period is too short")]
+    InactivityPeriodTooShort,
+    #[msg("Wallet has not been inactive long enough")]
+    WalletStillActive,
+    #[msg("Wallet is frozen")]
+    WalletFrozen,
+    #[msg("Wallet is not frozen")]
+    WalletNotFrozen,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Allowlist account required for this wallet")]
+    AllowlistRequired,
+    #[msg("Recipient is not on the allowlist")]
+    RecipientNotAllowed,
+    #[msg("Allowlist is full")]
+    AllowlistFull,
+    #[msg("Denylist account required")]
+    DenylistRequired,
+    #[msg("Recipient is on a denylist")]
+    RecipientDenied,
+    #[msg("Denylist is full")]
+    DenylistFull,
+    #[msg("Transfer exceeds timelock threshold; use queue_transfer")]
+    TimelockRequired,
+    #[msg("Timelock delay has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[msg("Invalid approver set")]
+    InvalidApprovers,
+    #[msg("Payment proposal has not been approved")]
+    ProposalNotApproved,
+    #[msg("Invalid batch accounts")]
+    InvalidBatch,
+    #[msg("Approval has expired")]
+    ApprovalExpired,
+    #[msg("Account has not expired yet")]
+    NotExpired,
+    #[msg("Metadata field too long")]
+    MetadataTooLong,
+    #[msg("Fee exceeds maximum")]
+    FeeTooHigh,
+    #[msg("Invalid treasury address")]
+    InvalidTreasury,
+    #[msg("No treasury change pending")]
+    NoPendingTreasury,
+    #[msg("Treasury change delay has not elapsed")]
+    TreasuryChangeNotReady,
+    #[msg("Too many fee tiers")]
+    TooManyFeeTiers,
+    #[msg("Fee tiers must have ascending points and non-increasing fees")]
+    InvalidFeeTiers,
+    #[msg("Fee exemption does not cover this transfer")]
+    InvalidFeeExemption,
+    #[msg("Invalid fee rounding policy")]
+    InvalidFeeRounding,
+    #[msg("Campaign end must be after its start")]
+    InvalidCampaignWindow,
+    #[msg("Fee campaign is not active for this transfer")]
+    CampaignNotActive,
+    #[msg("Account does not belong to this mint")]
+    MintMismatch,
+    #[msg("Referrer already registered")]
+    ReferrerAlreadySet,
+    #[msg("Referrals must be registered before the first transfer")]
+    ReferralWindowClosed,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Referrer account required")]
+    ReferrerRequired,
+    #[msg("Referral codes are 3-16 letters, digits, '-' or '_'")]
+    InvalidReferralCode,
+    #[msg("Fee vault has not been created")]
+    FeeVaultRequired,
+    #[msg("Invalid fee beneficiaries")]
+    InvalidFeeBeneficiaries,
+    #[msg("Fees are split among beneficiaries; use distribute_fees")]
+    FeeSplitActive,
+    #[msg("Points redemption is disabled")]
+    DiscountsDisabled,
+    #[msg("Not enough points")]
+    InsufficientPoints,
+    #[msg("Source and destination wallets must differ")]
+    SameWallet,
+    #[msg("Epoch is not ready to finalize")]
+    InvalidEpoch,
+    #[msg("Wallet is not a winner of this epoch")]
+    NotEpochWinner,
+    #[msg("Reward already claimed")]
+    RewardAlreadyClaimed,
+    #[msg("Points cap not reached")]
+    PointsCapNotReached,
+    #[msg("Transfer would exceed the recipient's points cap")]
+    PointsCapExceeded,
+    #[msg("Badge has not been unlocked")]
+    BadgeLocked,
+    #[msg("Reward token claims are disabled")]
+    RewardsDisabled,
+    #[msg("Invalid merkle proof")]
+    InvalidProof,
+    #[msg("Invalid quest")]
+    InvalidQuest,
+    #[msg("Quest is not completed")]
+    QuestNotCompleted,
+    #[msg("Invalid points formula")]
+    InvalidPointsFormula,
+    #[msg("Too many per-mint points formulas")]
+    TooManyPointsFormulas,
+    #[msg("Too many price feeds")]
+    TooManyPriceFeeds,
+    #[msg("Invalid price feed")]
+    InvalidPriceFeed,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price feed account required")]
+    PriceFeedRequired,
+    #[msg("Memo is empty or too long")]
+    InvalidMemo,
+    #[msg("Memo program account required")]
+    MemoProgramRequired,
+    #[msg("Unknown transfer category")]
+    InvalidCategory,
+    #[msg("Duplicate send: idempotency key already used")]
+    DuplicateSend,
+    #[msg("Scheduled payment is not due yet")]
+    PaymentNotDue,
+    #[msg("Invalid subscription")]
+    InvalidSubscription,
+    #[msg("Subscription cap exceeded")]
+    SubscriptionCapExceeded,
+    #[msg("Subscription is not active")]
+    SubscriptionInactive,
+    #[msg("Invalid merchant")]
+    InvalidMerchant,
+    #[msg("Merchant is not active")]
+    MerchantInactive,
+    #[msg("Invalid mandate")]
+    InvalidMandate,
+    #[msg("Mandate period cap exceeded")]
+    MandateCapExceeded,
+    #[msg("Invalid stream")]
+    InvalidStream,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Stream recipient is not transferable")]
+    StreamNotTransferable,
+    #[msg("Invalid vesting schedule")]
+    InvalidVesting,
+    #[msg("Invalid payroll")]
+    InvalidPayroll,
+    #[msg("Invalid escrow")]
+    InvalidEscrow,
+    #[msg("Escrow milestone already released")]
+    MilestoneReleased,
+    #[msg("Invalid arbiter")]
+    InvalidArbiter,
+    #[msg("Escrow is under dispute")]
+    EscrowDisputed,
+    #[msg("Escrow is not disputed")]
+    EscrowNotDisputed,
+    #[msg("Arbiter bond is missing or below the minimum")]
+    InsufficientBond,
+    #[msg("Invalid HTLC")]
+    InvalidHtlc,
+    #[msg("Preimage does not match the hashlock")]
+    InvalidPreimage,
+    #[msg("HTLC has timed out")]
+    HtlcExpired,
+    #[msg("Claimable transfer has expired")]
+    ClaimExpired,
+    #[msg("Invalid invoice")]
+    InvalidInvoice,
+    #[msg("Invoice has already been paid")]
+    InvoiceAlreadyPaid,
+    #[msg("Invoice has expired")]
+    InvoiceExpired,
+    #[msg("References must be read-only, non-signer accounts and within the limit")]
+    InvalidReference,
+    #[msg("Shares must be non-zero and total 10_000 bps")]
+    InvalidShares,
+    #[msg("Revenue share account is required for this wallet")]
+    RevenueShareRequired,
+    #[msg("Invalid crowdfund")]
+    InvalidCrowdfund,
+    #[msg("Crowdfund is no longer accepting contributions")]
+    CrowdfundEnded,
+    #[msg("Crowdfund has not reached its goal")]
+    GoalNotReached,
+    #[msg("Crowdfund is still running")]
+    CrowdfundActive,
+    #[msg("Invalid bounty")]
+    InvalidBounty,
+    #[msg("Bounty is no longer open")]
+    BountyClosed,
+    #[msg("Bounty has pending claims")]
+    BountyHasClaims,
+    #[msg("Invalid listing")]
+    InvalidListing,
+    #[msg("Listing is not active")]
+    ListingInactive,
+    #[msg("Listing price is above the buyer's maximum")]
+    PriceChanged,
+    #[msg("Invalid credit account")]
+    InvalidCredits,
+    #[msg("Not enough prepaid credits")]
+    InsufficientCredits,
+    #[msg("Credit account still holds a balance")]
+    CreditsNotEmpty,
+    #[msg("Recorded usage would exceed the agreed cap")]
+    UsageCapExceeded,
+    #[msg("Usage must be settled first")]
+    UsageUnsettled,
+    #[msg("Invalid payment channel")]
+    InvalidChannel,
+    #[msg("Invalid or unsigned channel state")]
+    InvalidChannelState,
+    #[msg("Channel is closing")]
+    ChannelClosing,
+    #[msg("Receipt does not match the expected payment")]
+    ReceiptMismatch,
+    #[msg("A receipt requires its rent payer")]
+    ReceiptPayerRequired,
+    #[msg("Missing or invalid transfer hook accounts")]
+    InvalidTransferHook,
+    #[msg("Refunds would exceed the original payment")]
+    RefundExceedsPayment,
+    #[msg("Revoke the wallet's session keys first")]
+    SessionsOpen,
+    #[msg("The approver set changed since this proposal was approved")]
+    ApproversChanged,
+    #[msg("Token account is not the recipient's associated token account")]
+    AtaMismatch,
+    #[msg("No credit withdrawal has been requested")]
+    NoWithdrawalRequested,
+    #[msg("Account still holds funds owed to its recipient")]
+    NotDrained,
+    #[msg("Amount does not cover the minimum fee")]
+    AmountBelowMinFee,
+    #[msg("Account is not a program account of the type being migrated")]
+    NotMigratable,
+    #[msg("Account is already at the current size")]
+    AlreadyMigrated,
+    #[msg("Close the wallet's escrows, streams and other open objects first")]
+    ObjectsOpen,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_needs_every_recipient_allowlisted() {
+        let (allowed, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            split_by_shares(1_000, &[5_000, 5_000]).unwrap(),
+            vec![500, 500]
+        );
+        assert!(!allowlist_admits(&[allowed], &[allowed, other], 1));
+        assert!(allowlist_admits(&[allowed, other], &[allowed, other], 1));
+    }
+
+    #[test]
+    fn token_split_checks_each_account_owner_pair() {
+        let (account_a, owner_a) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (account_b, owner_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let parties = [account_a, owner_a, account_b, owner_b];
+        assert!(!allowlist_admits(&[owner_a], &parties, 2));
+        assert!(allowlist_admits(&[owner_a, account_b], &parties, 2));
+    }
+
+    #[test]
+    fn hook_accounts_come_before_references() {
+        let (mint, hook_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let validation =
+            Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &hook_program).0;
+        let keys = [
+            hook_program,
+            validation,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut lamports = [0u64; 5];
+        // The metas PDA lists one extra account
+        let mut metas = vec![0u8; 16];
+        metas[12..16].copy_from_slice(&1u32.to_le_bytes());
+        let mut datas = vec![Vec::new(), metas, Vec::new(), Vec::new(), Vec::new()];
+        let owner = Pubkey::default();
+        let infos: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(datas.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, &owner, false, 0)
+            })
+            .collect();
+
+        let (hook, references) = split_hook_accounts(&mint, &hook_program, &infos).unwrap();
+        assert_eq!(hook.len(), 3);
+        assert_eq!(
+            references.iter().map(|a| a.key()).collect::<Vec<_>>(),
+            keys[3..].to_vec()
+        );
+        // The hook program and metas PDA must lead
+        assert!(split_hook_accounts(&mint, &hook_program, &infos[1..]).is_err());
+    }
+
+    /// An all-zero account (empty vecs and strings, None options) to set up field by field
+    fn zeroed<T: AnchorDeserialize + anchor_lang::Space>() -> T {
+        let data = vec![0u8; T::INIT_SPACE];
+        T::deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn sol_fee_applies_minimum_then_caps_within_amount() {
+        let mut config: ProtocolConfig = zeroed();
+        config.min_fee = 10_000;
+        assert_eq!(config.sol_fee_at(1_000_000, 50), 10_000);
+        assert_eq!(config.sol_fee_at(3_000, 50), 3_000);
+        assert_eq!(config.sol_fee_at(1_000_000, 0), 0);
+        config.max_fee = 20_000;
+        assert_eq!(config.sol_fee_at(10_000_000, 50), 20_000);
+    }
+
+    #[test]
+    fn token_fee_follows_rounding_and_cap() {
+        let mut config: ProtocolConfig = zeroed();
+        assert_eq!(config.token_fee_at(101, 100), 1);
+        config.fee_rounding = FEE_ROUND_UP;
+        assert_eq!(config.token_fee_at(101, 100), 2);
+        config.max_token_fee = 1;
+        assert_eq!(config.token_fee_at(10_000, 100), 1);
+    }
+
+    #[test]
+    fn shares_split_dust_to_first_and_must_total_10_000() {
+        assert_eq!(
+            split_by_shares(1_001, &[3_334, 3_333, 3_333]).unwrap(),
+            vec![335, 333, 333]
+        );
+        assert!(split_by_shares(1_000, &[5_000, 4_999]).is_err());
+        assert!(split_by_shares(1_000, &[10_000, 0]).is_err());
+    }
+
+    #[test]
+    fn interest_compounds_continuously() {
+        let year = INTEREST_SECONDS_PER_YEAR as i128;
+        // e^0.05 and e^-0.05
+        assert_eq!(compound_interest(1_000_000, 500 * year).unwrap(), 1_051_271);
+        assert_eq!(compound_interest(1_000_000, -500 * year).unwrap(), 951_229);
+        assert_eq!(compound_interest(1_000_000, 0).unwrap(), 1_000_000);
+        // e^3: the whole part of the exponent goes through the e multiplications
+        assert_eq!(
+            compound_interest(1_000_000, 10_000 * 3 * year).unwrap(),
+            20_085_536
+        );
+        assert!(compound_interest(u64::MAX, 500 * year).is_err());
+    }
+
+    #[test]
+    fn channel_states_must_conserve_deposits() {
+        let mut channel: Channel = zeroed();
+        channel.deposit_a = 100;
+        channel.deposit_b = 50;
+        assert!(channel.require_balanced(120, 30).is_ok());
+        assert!(channel.require_balanced(100, 100).is_err());
+        assert!(channel.require_balanced(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn credits_owe_unsettled_units_at_unit_price() {
+        let mut credits: CreditAccount = zeroed();
+        credits.usage_units = 10;
+        credits.settled_units = 4;
+        credits.unit_price = 25;
+        assert_eq!(credits.owed().unwrap(), 150);
+        credits.unit_price = u64::MAX;
+        assert!(credits.owed().is_err());
+    }
+
+    #[test]
+    fn earned_points_reduce_for_repeats_and_stop_at_daily_cap() {
+        let mut config: ProtocolConfig = zeroed();
+        config.repeat_points_bps = 5_000;
+        config.daily_points_cap = 150;
+        let mut wallet: AgentWallet = zeroed();
+        let recipient = Pubkey::new_unique();
+
+        assert_eq!(wallet.earn_points(100, 4, 5, recipient, 0, &config), 0);
+        assert_eq!(wallet.earn_points(100, 5, 5, recipient, 0, &config), 100);
+        assert_eq!(wallet.earn_points(100, 5, 5, recipient, 0, &config), 50);
+        assert_eq!(
+            wallet.earn_points(100, 5, 5, Pubkey::new_unique(), 0, &config),
+            0
+        );
+        assert_eq!(wallet.points_earned_today, 150);
+        // A new day resets the cap
+        assert_eq!(
+            wallet.earn_points(100, 5, 5, Pubkey::new_unique(), SECONDS_PER_DAY, &config),
+            100
+        );
+    }
+
+    #[test]
+    fn level_boosts_earned_points() {
+        let config: ProtocolConfig = zeroed();
+        let mut wallet: AgentWallet = zeroed();
+        wallet.level = 2;
+        assert_eq!(
+            wallet.earn_points(100, 0, 0, Pubkey::new_unique(), 0, &config),
+            110
+        );
+    }
+
+    #[test]
+    fn receipts_verify_net_of_refunds() {
+        let payee = Pubkey::new_unique();
+        let mut receipt: PaymentReceipt = zeroed();
+        receipt.payee = payee;
+        receipt.amount = 100;
+        receipt.refunded = 30;
+        assert_eq!(receipt.net_amount(), 70);
+        assert!(receipt.satisfies(payee, None, 70, None, None));
+        assert!(!receipt.satisfies(payee, None, 71, None, None));
+        assert!(!receipt.satisfies(payee, Some(Pubkey::new_unique()), 0, None, None));
+        assert!(!receipt.satisfies(payee, None, 0, Some(Pubkey::new_unique()), None));
+        receipt.refunded = 100;
+        assert!(!receipt.satisfies(payee, None, 0, None, None));
+    }
+
+    #[test]
+    fn counters_fail_or_saturate_instead_of_wrapping() {
+        let mut wallet: AgentWallet = zeroed();
+        wallet.open_objects[OPEN_ESCROW] = u16::MAX;
+        assert!(wallet.open_object(OPEN_ESCROW).is_err());
+        wallet.close_object(OPEN_STREAM);
+        assert_eq!(wallet.open_objects[OPEN_STREAM], 0);
+
+        wallet.category_tx_counts[0] = u64::MAX;
+        wallet.category_sol_volume[0] = u64::MAX;
+        wallet.record_category(0, 1).unwrap();
+        assert_eq!(wallet.category_tx_counts[0], u64::MAX);
+        assert_eq!(wallet.category_sol_volume[0], u64::MAX);
+        assert!(wallet.record_category(NUM_CATEGORIES as u8, 1).is_err());
+
+        let config: ProtocolConfig = zeroed();
+        wallet.points_earned_today = u64::MAX;
+        wallet.earn_points(1, 0, 0, Pubkey::new_unique(), 0, &config);
+        assert_eq!(wallet.points_earned_today, u64::MAX);
+    }
 }